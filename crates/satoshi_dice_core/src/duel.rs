@@ -0,0 +1,123 @@
+use alloc::format;
+use alloc::string::String;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// Player versus player duel. A stake sent to the duel address opens a challenge, the next stake
+/// of the same amount is matched against it and the hash picks who takes both stakes less the
+/// rake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuelSettings {
+    /// Share of both stakes kept by the house, in basis points
+    pub rake_bps: u16,
+    /// Minutes a challenge waits for an opponent before it is refunded
+    pub timeout_minutes: u64,
+}
+
+impl DuelSettings {
+    /// Sats kept by the house out of a duel over `stake` sats each, rounded down
+    pub fn rake(&self, stake: u64) -> u64 {
+        (stake as u128 * 2 * self.rake_bps.min(10_000) as u128 / 10_000) as u64
+    }
+
+    /// Sats paid to the winner of a duel over `stake` sats each
+    pub fn payout(&self, stake: u64) -> u64 {
+        stake.saturating_mul(2) - self.rake(stake)
+    }
+}
+
+/// The player who takes the stakes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuelSide {
+    /// The player who opened the challenge
+    Challenger,
+    /// The player who was matched against it
+    Opponent,
+}
+
+/// Every step from the nonce and both stakes to the winner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuelRoll {
+    /// The string that gets hashed, `{nonce}{challenger_txid}:{vout}{opponent_txid}:{vout}`
+    pub hash_input: String,
+    /// SHA256 of the hash input
+    pub hash: [u8; 32],
+    /// The first 2 bytes of the hash as a big-endian u16, the challenger wins below 32768
+    pub random_value: u16,
+    pub winner: DuelSide,
+}
+
+/// Pick the winner of a duel between the stakes at `challenger` and `opponent`, each given as
+/// txid and vout
+pub fn roll(nonce: &str, challenger: (&str, u32), opponent: (&str, u32)) -> DuelRoll {
+    let hash_input = format!(
+        "{nonce}{}:{}{}:{}",
+        challenger.0, challenger.1, opponent.0, opponent.1
+    );
+    let hash: [u8; 32] = Sha256::digest(hash_input.as_bytes()).into();
+    let random_value = u16::from_be_bytes([hash[0], hash[1]]);
+    let winner = if random_value < 32_768 {
+        DuelSide::Challenger
+    } else {
+        DuelSide::Opponent
+    };
+
+    DuelRoll {
+        hash_input,
+        hash,
+        random_value,
+        winner,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+    use std::string::ToString;
+
+    #[test]
+    fn test_payout_less_rake() {
+        let duel = DuelSettings {
+            rake_bps: 250,
+            timeout_minutes: 10,
+        };
+        assert_eq!(duel.rake(10_000), 500);
+        assert_eq!(duel.payout(10_000), 19_500);
+        assert_eq!(
+            DuelSettings {
+                rake_bps: 0,
+                timeout_minutes: 10
+            }
+            .payout(999),
+            1_998
+        );
+    }
+
+    #[test]
+    fn test_both_sides_win_half() {
+        let mut challenger_wins = 0;
+
+        for i in 0..10_000 {
+            let nonce = i.to_string();
+            let challenger = format!("challenger_txid_{i}");
+            let opponent = format!("opponent_txid_{i}");
+            let roll = roll(&nonce, (&challenger, 0), (&opponent, 1));
+
+            assert_eq!(
+                roll.hash_input,
+                format!("{nonce}{challenger}:0{opponent}:1")
+            );
+            if roll.winner == DuelSide::Challenger {
+                challenger_wins += 1;
+            }
+        }
+
+        assert!(
+            (4_800..=5_200).contains(&challenger_wins),
+            "Unexpected challenger wins {challenger_wins}"
+        );
+    }
+}
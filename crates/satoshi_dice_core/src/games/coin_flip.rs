@@ -0,0 +1,180 @@
+use super::detail::Detail;
+use super::satoshis_number::SatoshisNumberGame;
+use super::Game;
+use super::GameEvaluation;
+use super::GameInput;
+use super::GameOption;
+use super::GameParameter;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Side the coin landed on. It is stored as the rolled number of a game: 0 for heads, 1 for tails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoinSide {
+    Heads = 0,
+    Tails = 1,
+}
+
+impl CoinSide {
+    pub fn from_rolled_value(rolled_value: i64) -> Option<Self> {
+        match rolled_value {
+            0 => Some(CoinSide::Heads),
+            1 => Some(CoinSide::Tails),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CoinSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoinSide::Heads => write!(f, "heads"),
+            CoinSide::Tails => write!(f, "tails"),
+        }
+    }
+}
+
+/// Flip a coin, the player wins on heads
+///
+/// The coin uses the same roll as [`SatoshisNumberGame`]. A fair coin lands heads on the lower
+/// half of the roll range, the house edge moves part of that half over to tails.
+pub struct CoinFlipGame {
+    house_edge_bps: u16,
+}
+
+impl CoinFlipGame {
+    pub const DEFAULT_HOUSE_EDGE_BPS: u16 = 300;
+
+    /// `house_edge_bps` is the house edge in basis points, e.g. 300 for 3%
+    pub fn new(house_edge_bps: u16) -> Self {
+        Self {
+            house_edge_bps: house_edge_bps.min(10_000),
+        }
+    }
+
+    /// The coin lands heads if the roll is below this threshold
+    pub fn heads_threshold(&self) -> u16 {
+        (32_768 * (10_000 - self.house_edge_bps as u32) / 10_000) as u16
+    }
+
+    pub fn flip(&self, input: &GameInput) -> CoinSide {
+        if SatoshisNumberGame::roll(input).random_value < self.heads_threshold() {
+            CoinSide::Heads
+        } else {
+            CoinSide::Tails
+        }
+    }
+}
+
+impl Default for CoinFlipGame {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_HOUSE_EDGE_BPS)
+    }
+}
+
+impl Game for CoinFlipGame {
+    fn evaluate(&self, input: &GameInput, option: &GameOption, bet_amount: u64) -> GameEvaluation {
+        let roll = SatoshisNumberGame::roll(input).random_value;
+        let side = self.flip(input);
+        let player_wins = side == CoinSide::Heads && matches!(option, GameOption::Multiplier(_));
+
+        GameEvaluation::win_or_loss(
+            side as i64,
+            player_wins,
+            bet_amount,
+            self.payout_multiplier("", option),
+            Detail::new()
+                .number("roll", roll as i64)
+                .number("heads_threshold", self.heads_threshold() as i64)
+                .text("side", &side.to_string())
+                .build(),
+        )
+    }
+
+    fn target(&self, _nonce_hash: &str, _option: &GameOption) -> i64 {
+        self.heads_threshold() as i64
+    }
+
+    fn win_probability(&self, _nonce_hash: &str, _option: &GameOption) -> f64 {
+        self.heads_threshold() as f64 / 65536.0
+    }
+
+    fn payout_multiplier(&self, _nonce_hash: &str, option: &GameOption) -> u64 {
+        match option {
+            GameOption::Multiplier(multiplier) => multiplier.multiplier(),
+            _ => 0,
+        }
+    }
+
+    fn outcome(&self, rolled_value: i64) -> Option<String> {
+        CoinSide::from_rolled_value(rolled_value).map(|side| side.to_string())
+    }
+
+    fn parameters(&self) -> Vec<GameParameter> {
+        vec![GameParameter {
+            name: "house_edge_bps",
+            value: self.house_edge_bps as u64,
+        }]
+    }
+
+    fn name(&self) -> &'static str {
+        "Coin Flip"
+    }
+
+    fn description(&self) -> &'static str {
+        "Flip a coin derived from the hash. Heads doubles your bet, tails loses it."
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::games::satoshis_number::RollVersion;
+    use crate::multiplier::Multiplier;
+    use std::format;
+    use std::string::ToString;
+
+    #[test]
+    fn test_house_edge_moves_heads_threshold() {
+        assert_eq!(CoinFlipGame::new(0).heads_threshold(), 32_768);
+        assert_eq!(CoinFlipGame::new(300).heads_threshold(), 31_784);
+        assert_eq!(CoinFlipGame::new(10_000).heads_threshold(), 0);
+    }
+
+    #[test]
+    fn test_flip_is_stored_as_side() {
+        let game = CoinFlipGame::default();
+        let mut heads = 0;
+
+        for i in 0..1000 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let input = GameInput {
+                nonce: &nonce,
+                txid: &txid,
+                vout: Some(0),
+                client_seed: None,
+                roll_version: RollVersion::LATEST,
+            };
+            let evaluation = game.evaluate(&input, &GameOption::Multiplier(Multiplier::X200), 1000);
+            let side = CoinSide::from_rolled_value(evaluation.rolled_value).unwrap();
+
+            assert_eq!(side, game.flip(&input));
+            assert_eq!(evaluation.is_win, side == CoinSide::Heads);
+            if evaluation.is_win {
+                heads += 1;
+                assert_eq!(evaluation.payout_sats, 2000);
+            }
+        }
+
+        assert!(
+            (420..=550).contains(&heads),
+            "Unexpected heads count {heads}"
+        );
+    }
+}
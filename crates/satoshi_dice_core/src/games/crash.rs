@@ -0,0 +1,257 @@
+use super::detail::Detail;
+use super::satoshis_number::SatoshisNumberGame;
+use super::Game;
+use super::GameEvaluation;
+use super::GameInput;
+use super::GameOption;
+use super::GameParameter;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// 2^52, the range of the random value the crash point is drawn from
+const E: u128 = 1 << 52;
+
+/// Number of leading hash bytes the random value of the crash point is read from
+const RANDOM_BYTES: usize = 8;
+
+/// The multiplier at which the player cashes out, in hundredths, e.g. 250 for 2.50x
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CashoutTarget(u32);
+
+impl CashoutTarget {
+    /// Lowest target a bet may cash out at, 1.01x
+    pub const MIN: u32 = 101;
+
+    /// `None` for targets below [`CashoutTarget::MIN`]
+    pub fn new(hundredths: u32) -> Option<Self> {
+        (hundredths >= Self::MIN).then_some(Self(hundredths))
+    }
+
+    pub fn hundredths(&self) -> u32 {
+        self.0
+    }
+
+    /// Index of the option, the target in hundredths
+    pub fn index(&self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for CashoutTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_multiplier(self.0 as u64))
+    }
+}
+
+/// Display form of a multiplier in hundredths, e.g. "2.50x"
+pub fn format_multiplier(hundredths: u64) -> String {
+    format!("{}.{:02}x", hundredths / 100, hundredths % 100)
+}
+
+/// Bet that the multiplier climbs to the player's cash-out target before it crashes
+///
+/// Every target has its own address, so the target is set by the address the bet is paid to. The
+/// crash point is drawn from the same hash as every other game, with the house edge built into
+/// its distribution, so every target has the same RTP.
+pub struct CrashGame {
+    house_edge_bps: u16,
+}
+
+impl CrashGame {
+    pub const DEFAULT_HOUSE_EDGE_BPS: u16 = 300;
+
+    /// Targets offered with an address each, in hundredths
+    pub const TARGETS: [u32; 8] = [110, 150, 200, 300, 500, 1_000, 2_500, 10_000];
+
+    pub const DISTRIBUTION: &'static str =
+        "h is the first 52 bits of SHA256(hash input) as a big-endian integer. The crash point in \
+         hundredths is max(100, floor((10000 - house_edge_bps) * 2^52 / (100 * (2^52 - h)))), so \
+         the crash point reaches a target t of at least 101 with a chance of \
+         (10000 - house_edge_bps) / (100 * t). A bet on t wins t / 100 times the bet if the crash \
+         point is at or above t.";
+
+    /// `house_edge_bps` is the house edge in basis points, e.g. 300 for 3%
+    pub fn new(house_edge_bps: u16) -> Self {
+        Self {
+            house_edge_bps: house_edge_bps.min(10_000),
+        }
+    }
+
+    /// The crash point of a bet in hundredths, 100 (1.00x) or more.
+    ///
+    /// Bets from roll version 2 on draw the jackpot after the bytes the crash point is read from,
+    /// so a jackpot win does not make a low crash point more likely. Under roll version 1 the two
+    /// overlap, and those games keep the crash point they were played with.
+    pub fn crash_point(&self, input: &GameInput) -> u64 {
+        let hash = SatoshisNumberGame::roll(input).hash;
+        let mut random_bytes = [0u8; RANDOM_BYTES];
+        random_bytes.copy_from_slice(&hash[..RANDOM_BYTES]);
+        let h = (u64::from_be_bytes(random_bytes) >> 12) as u128;
+
+        let crash_point = (10_000 - self.house_edge_bps as u128) * E / (100 * (E - h));
+        (crash_point as u64).max(100)
+    }
+
+    pub fn options() -> Vec<GameOption> {
+        Self::TARGETS
+            .iter()
+            .filter_map(|target| CashoutTarget::new(*target))
+            .map(GameOption::Crash)
+            .collect()
+    }
+}
+
+impl Default for CrashGame {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_HOUSE_EDGE_BPS)
+    }
+}
+
+impl Game for CrashGame {
+    fn evaluate(&self, input: &GameInput, option: &GameOption, bet_amount: u64) -> GameEvaluation {
+        let crash_point = self.crash_point(input);
+        let (player_wins, detail) = match option {
+            GameOption::Crash(target) => (
+                crash_point >= target.hundredths() as u64,
+                Detail::new()
+                    .number("crash_point", crash_point as i64)
+                    .number("cashout_target", target.hundredths() as i64),
+            ),
+            _ => (
+                false,
+                Detail::new().number("crash_point", crash_point as i64),
+            ),
+        };
+
+        GameEvaluation::win_or_loss(
+            crash_point as i64,
+            player_wins,
+            bet_amount,
+            self.payout_multiplier("", option),
+            detail.build(),
+        )
+    }
+
+    fn target(&self, _nonce_hash: &str, option: &GameOption) -> i64 {
+        match option {
+            GameOption::Crash(target) => target.hundredths() as i64,
+            _ => 0,
+        }
+    }
+
+    fn win_probability(&self, _nonce_hash: &str, option: &GameOption) -> f64 {
+        match option {
+            GameOption::Crash(target) => ((10_000 - self.house_edge_bps) as f64
+                / (100.0 * target.hundredths() as f64))
+                .min(1.0),
+            _ => 0.0,
+        }
+    }
+
+    fn payout_multiplier(&self, _nonce_hash: &str, option: &GameOption) -> u64 {
+        match option {
+            GameOption::Crash(target) => target.hundredths() as u64,
+            _ => 0,
+        }
+    }
+
+    fn outcome(&self, rolled_value: i64) -> Option<String> {
+        u64::try_from(rolled_value).ok().map(format_multiplier)
+    }
+
+    fn parameters(&self) -> Vec<GameParameter> {
+        vec![
+            GameParameter {
+                name: "house_edge_bps",
+                value: self.house_edge_bps as u64,
+            },
+            GameParameter {
+                name: "min_cashout_target",
+                value: CashoutTarget::MIN as u64,
+            },
+        ]
+    }
+
+    fn distribution(&self) -> Option<&'static str> {
+        Some(Self::DISTRIBUTION)
+    }
+
+    fn name(&self) -> &'static str {
+        "Crash"
+    }
+
+    fn description(&self) -> &'static str {
+        "A multiplier climbs from 1.00x until it crashes. Pay to the address of your cash-out \
+         target, if the crash point reaches it you win the target times your bet."
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::games::satoshis_number::RollVersion;
+    use std::string::ToString;
+
+    fn input<'a>(nonce: &'a str, txid: &'a str) -> GameInput<'a> {
+        GameInput {
+            nonce,
+            txid,
+            vout: Some(0),
+            client_seed: None,
+            roll_version: RollVersion::LATEST,
+        }
+    }
+
+    #[test]
+    fn test_crash_point_follows_distribution() {
+        let game = CrashGame::default();
+        let target = CashoutTarget::new(200).expect("valid target");
+        let option = GameOption::Crash(target);
+        let mut wins = 0;
+
+        for i in 0..20_000 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let input = input(&nonce, &txid);
+            let crash_point = game.crash_point(&input);
+            assert!(crash_point >= 100);
+
+            let evaluation = game.evaluate(&input, &option, 1_000);
+            assert_eq!(evaluation.rolled_value, crash_point as i64);
+            assert_eq!(evaluation.is_win, crash_point >= 200);
+            if evaluation.is_win {
+                assert_eq!(evaluation.payout_sats, 2_000);
+                wins += 1;
+            }
+        }
+
+        // 48.5% of crash points reach 2.00x
+        assert!((9_500..=9_900).contains(&wins), "Unexpected wins {wins}");
+        assert!((game.rtp("", &option) - 0.97).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_crash_point_does_not_share_bytes_with_jackpot() {
+        assert!(RollVersion::LATEST.jackpot_offset() >= RANDOM_BYTES);
+        assert!(RollVersion::V1.jackpot_offset() < RANDOM_BYTES);
+    }
+
+    #[test]
+    fn test_targets() {
+        assert_eq!(CashoutTarget::new(100), None);
+        assert_eq!(
+            CashoutTarget::new(250).map(|target| target.to_string()),
+            Some("2.50x".to_string())
+        );
+        assert_eq!(CrashGame::options().len(), CrashGame::TARGETS.len());
+        assert_eq!(
+            CrashGame::default().outcome(1_234),
+            Some("12.34x".to_string())
+        );
+    }
+}
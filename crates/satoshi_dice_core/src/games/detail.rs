@@ -0,0 +1,74 @@
+use alloc::format;
+use alloc::string::String;
+
+/// Builds the game specific detail of an evaluation, a flat JSON object such as
+/// `{"roll":1234,"threshold":31784}`
+#[derive(Debug, Clone, Default)]
+pub struct Detail {
+    fields: String,
+}
+
+impl Detail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn number(mut self, name: &str, value: i64) -> Self {
+        self.push_name(name);
+        self.fields.push_str(&format!("{value}"));
+        self
+    }
+
+    pub fn text(mut self, name: &str, value: &str) -> Self {
+        self.push_name(name);
+        push_string(&mut self.fields, value);
+        self
+    }
+
+    pub fn build(self) -> String {
+        format!("{{{}}}", self.fields)
+    }
+
+    fn push_name(&mut self, name: &str) {
+        if !self.fields.is_empty() {
+            self.fields.push(',');
+        }
+        push_string(&mut self.fields, name);
+        self.fields.push(':');
+    }
+}
+
+fn push_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builds_flat_object() {
+        assert_eq!(Detail::new().build(), "{}");
+        assert_eq!(
+            Detail::new()
+                .number("roll", 1234)
+                .text("side", "under")
+                .number("target", -1)
+                .build(),
+            r#"{"roll":1234,"side":"under","target":-1}"#
+        );
+        assert_eq!(
+            Detail::new().text("card", "\"Q\"\n").build(),
+            r#"{"card":"\"Q\"\u000a"}"#
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use super::detail::Detail;
+use super::satoshis_number::RollVersion;
+use super::satoshis_number::SatoshisNumberGame;
+use super::unbiased_pick;
+use super::Game;
+use super::GameEvaluation;
+use super::GameInput;
+use super::GameOption;
+use super::GameParameter;
+use crate::nonce::hash_nonce;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Number of cards in a suit, ace (1) to king (13)
+const CARDS: u16 = 13;
+
+/// Number of cards the reference card is dealt from, 2 to 12
+const REFERENCE_CARDS: u16 = CARDS - 2;
+
+/// Which way the player bets the drawn card goes, picked by the address they pay to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Higher = 0,
+    Lower = 1,
+}
+
+impl Direction {
+    pub fn all() -> [Self; 2] {
+        [Direction::Higher, Direction::Lower]
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Higher => write!(f, "higher"),
+            Direction::Lower => write!(f, "lower"),
+        }
+    }
+}
+
+/// Display name of a card, e.g. "A" for 1 and "Q" for 12
+pub fn card_label(card: i64) -> Option<String> {
+    let label = match card {
+        1 => "A".to_string(),
+        2..=10 => card.to_string(),
+        11 => "J".to_string(),
+        12 => "Q".to_string(),
+        13 => "K".to_string(),
+        _ => return None,
+    };
+    Some(label)
+}
+
+/// Bet on whether the drawn card is higher or lower than the reference card
+///
+/// The reference card is dealt from the nonce hash, so it is known as soon as the nonce is
+/// committed and before any bet is placed. It is never an ace or a king, so both directions can
+/// win. A tie loses. The payout depends on how many cards win, less the house edge.
+pub struct HighLowGame {
+    house_edge_bps: u16,
+}
+
+impl HighLowGame {
+    pub const DEFAULT_HOUSE_EDGE_BPS: u16 = 300;
+
+    /// `house_edge_bps` is the house edge in basis points, e.g. 300 for 3%
+    pub fn new(house_edge_bps: u16) -> Self {
+        Self {
+            house_edge_bps: house_edge_bps.min(10_000),
+        }
+    }
+
+    /// The reference card for a nonce dealt to new games, see
+    /// [`HighLowGame::reference_card_for_version`]
+    pub fn reference_card(nonce_hash: &str) -> u16 {
+        Self::reference_card_for_version(nonce_hash, RollVersion::LATEST)
+    }
+
+    /// The reference card for a nonce, 2 to 12, dealt to games played under `roll_version`.
+    ///
+    /// Under roll version 1 it is the first 2 bytes of the nonce hash modulo 11, which makes low
+    /// cards slightly more likely. Later versions pick it from the bytes of the nonce hash without
+    /// a bias, see [`unbiased_pick`].
+    pub fn reference_card_for_version(nonce_hash: &str, roll_version: RollVersion) -> u16 {
+        match roll_version {
+            RollVersion::V1 => {
+                let value = nonce_hash
+                    .get(..4)
+                    .and_then(|prefix| u16::from_str_radix(prefix, 16).ok())
+                    .unwrap_or_default();
+
+                2 + value % REFERENCE_CARDS
+            }
+            RollVersion::V2 => {
+                let mut bytes = [0u8; 32];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = nonce_hash
+                        .get(2 * i..2 * i + 2)
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                        .unwrap_or_default();
+                }
+
+                2 + unbiased_pick(bytes, REFERENCE_CARDS as u8) as u16
+            }
+        }
+    }
+
+    /// The card drawn for a bet, ace (1) to king (13).
+    ///
+    /// Under roll version 1 it is the first 2 bytes of the bet's hash modulo 13, which makes low
+    /// cards slightly more likely. Later versions pick it without a bias, see [`unbiased_pick`].
+    pub fn draw_card(input: &GameInput) -> u16 {
+        let roll = SatoshisNumberGame::roll(input);
+        match input.roll_version {
+            RollVersion::V1 => 1 + roll.random_value % CARDS,
+            RollVersion::V2 => 1 + unbiased_pick(roll.hash, CARDS as u8) as u16,
+        }
+    }
+
+    fn payout_multiplier_for_version(
+        &self,
+        nonce_hash: &str,
+        option: &GameOption,
+        roll_version: RollVersion,
+    ) -> u64 {
+        let GameOption::HighLow(direction) = option else {
+            return 0;
+        };
+
+        let reference_card = Self::reference_card_for_version(nonce_hash, roll_version);
+        let winning_cards = Self::winning_cards(reference_card, *direction);
+        CARDS as u64 * (10_000 - self.house_edge_bps as u64) / (winning_cards as u64 * 100)
+    }
+
+    fn winning_cards(reference_card: u16, direction: Direction) -> u16 {
+        match direction {
+            Direction::Higher => CARDS - reference_card,
+            Direction::Lower => reference_card - 1,
+        }
+    }
+}
+
+impl Default for HighLowGame {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_HOUSE_EDGE_BPS)
+    }
+}
+
+impl Game for HighLowGame {
+    fn evaluate(&self, input: &GameInput, option: &GameOption, bet_amount: u64) -> GameEvaluation {
+        let nonce_hash = hash_nonce(input.nonce);
+        let reference_card = Self::reference_card_for_version(&nonce_hash, input.roll_version);
+        let drawn_card = Self::draw_card(input);
+
+        let player_wins = match option {
+            GameOption::HighLow(Direction::Higher) => drawn_card > reference_card,
+            GameOption::HighLow(Direction::Lower) => drawn_card < reference_card,
+            _ => false,
+        };
+
+        GameEvaluation::win_or_loss(
+            drawn_card as i64,
+            player_wins,
+            bet_amount,
+            self.payout_multiplier_for_version(&nonce_hash, option, input.roll_version),
+            Detail::new()
+                .number("reference_card", reference_card as i64)
+                .number("drawn_card", drawn_card as i64)
+                .build(),
+        )
+    }
+
+    fn target(&self, nonce_hash: &str, _option: &GameOption) -> i64 {
+        Self::reference_card(nonce_hash) as i64
+    }
+
+    fn target_for_version(
+        &self,
+        nonce_hash: &str,
+        _option: &GameOption,
+        roll_version: RollVersion,
+    ) -> i64 {
+        Self::reference_card_for_version(nonce_hash, roll_version) as i64
+    }
+
+    fn win_probability(&self, nonce_hash: &str, option: &GameOption) -> f64 {
+        let GameOption::HighLow(direction) = option else {
+            return 0.0;
+        };
+
+        Self::winning_cards(Self::reference_card(nonce_hash), *direction) as f64 / CARDS as f64
+    }
+
+    fn payout_multiplier(&self, nonce_hash: &str, option: &GameOption) -> u64 {
+        self.payout_multiplier_for_version(nonce_hash, option, RollVersion::LATEST)
+    }
+
+    fn outcome(&self, rolled_value: i64) -> Option<String> {
+        card_label(rolled_value)
+    }
+
+    fn parameters(&self) -> Vec<GameParameter> {
+        vec![GameParameter {
+            name: "house_edge_bps",
+            value: self.house_edge_bps as u64,
+        }]
+    }
+
+    fn name(&self) -> &'static str {
+        "High/Low"
+    }
+
+    fn description(&self) -> &'static str {
+        "A card is drawn from the hash. Bet whether it is higher or lower than the reference card \
+         dealt for the current nonce. The fewer cards that win, the higher the payout."
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+    use std::string::ToString;
+
+    #[test]
+    fn test_reference_card_is_never_ace_or_king() {
+        for i in 0..1000 {
+            let nonce_hash = hash_nonce(&i.to_string());
+            for roll_version in [RollVersion::V1, RollVersion::V2] {
+                let card = HighLowGame::reference_card_for_version(&nonce_hash, roll_version);
+                assert!((2..=12).contains(&card));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cards_are_dealt_without_bias() {
+        let mut counts = [0u32; CARDS as usize];
+        for i in 0..13_000 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let input = GameInput {
+                nonce: &nonce,
+                txid: &txid,
+                vout: Some(0),
+                client_seed: None,
+                roll_version: RollVersion::LATEST,
+            };
+            counts[HighLowGame::draw_card(&input) as usize - 1] += 1;
+        }
+        for count in counts {
+            assert!((850..=1_150).contains(&count), "Unexpected count {count}");
+        }
+
+        // Bytes from 253 on would make the first cards more likely, they are skipped
+        let mut nonce_hash = "fd".repeat(31);
+        nonce_hash.push_str("05");
+        assert_eq!(
+            HighLowGame::reference_card_for_version(&nonce_hash, RollVersion::LATEST),
+            7
+        );
+
+        // Games stored under roll version 1 keep the card dealt from the first 2 bytes
+        assert_eq!(
+            HighLowGame::reference_card_for_version("0005", RollVersion::V1),
+            7
+        );
+    }
+
+    #[test]
+    fn test_payout_follows_winning_cards() {
+        let game = HighLowGame::default();
+        // Reference card 7: six cards win either way
+        let nonce_hash = "05";
+        assert_eq!(HighLowGame::reference_card(nonce_hash), 7);
+
+        for direction in Direction::all() {
+            let option = GameOption::HighLow(direction);
+            assert_eq!(game.payout_multiplier(nonce_hash, &option), 210);
+            assert_eq!(game.win_probability(nonce_hash, &option), 6.0 / 13.0);
+        }
+    }
+
+    #[test]
+    fn test_direction_decides_winner() {
+        let game = HighLowGame::default();
+
+        for i in 0..200 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let input = GameInput {
+                nonce: &nonce,
+                txid: &txid,
+                vout: Some(0),
+                client_seed: None,
+                roll_version: RollVersion::LATEST,
+            };
+            let reference_card = HighLowGame::reference_card(&hash_nonce(&nonce));
+            let drawn_card = HighLowGame::draw_card(&input);
+
+            let higher = game.evaluate(&input, &GameOption::HighLow(Direction::Higher), 1000);
+            let lower = game.evaluate(&input, &GameOption::HighLow(Direction::Lower), 1000);
+
+            assert_eq!(higher.rolled_value, drawn_card as i64);
+            assert_eq!(higher.is_win, drawn_card > reference_card);
+            assert_eq!(lower.is_win, drawn_card < reference_card);
+            assert!(!(higher.is_win && lower.is_win));
+        }
+    }
+}
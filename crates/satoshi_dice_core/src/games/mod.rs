@@ -0,0 +1,448 @@
+pub mod coin_flip;
+pub mod crash;
+pub mod detail;
+pub mod high_low;
+pub mod over_under;
+pub mod registry;
+pub mod roulette;
+pub mod satoshis_number;
+
+use crate::multiplier::Multiplier;
+use crate::multiplier::MultiplierTable;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
+use core::str::FromStr;
+use satoshis_number::RollVersion;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// Result of evaluating a game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameEvaluation {
+    /// The number/value that was rolled/generated
+    pub rolled_value: i64,
+    /// Whether the player won
+    pub is_win: bool,
+    /// Sats paid out for a win, 0 if the player lost
+    pub payout_sats: u64,
+    /// Part of the bet that is returned without a win, e.g. on a near miss
+    pub refund_sats: Option<u64>,
+    /// Short name of the outcome, e.g. "win" or "loss"
+    pub label: &'static str,
+    /// Game specific details of the outcome as a JSON object, see [`detail::Detail`]
+    pub detail: String,
+}
+
+impl GameEvaluation {
+    pub const WIN: &'static str = "win";
+    pub const LOSS: &'static str = "loss";
+
+    /// A win paying `payout_multiplier` times the bet, e.g. 200 for 2.00x, or a loss that returns
+    /// nothing
+    pub fn win_or_loss(
+        rolled_value: i64,
+        is_win: bool,
+        bet_amount: u64,
+        payout_multiplier: u64,
+        detail: String,
+    ) -> Self {
+        Self {
+            rolled_value,
+            is_win,
+            payout_sats: if is_win {
+                payout_sats(bet_amount, payout_multiplier)
+            } else {
+                0
+            },
+            refund_sats: None,
+            label: if is_win { Self::WIN } else { Self::LOSS },
+            detail,
+        }
+    }
+
+    /// Everything the player gets back, the payout of a win plus any refund
+    pub fn returned_sats(&self) -> u64 {
+        self.payout_sats
+            .saturating_add(self.refund_sats.unwrap_or_default())
+    }
+}
+
+/// Sats paid for `bet_amount` at `payout_multiplier`, e.g. 200 for 2.00x, rounded down
+pub fn payout_sats(bet_amount: u64, payout_multiplier: u64) -> u64 {
+    let payout = bet_amount as u128 * payout_multiplier as u128 / 100;
+    u64::try_from(payout).unwrap_or(u64::MAX)
+}
+
+/// One of `choices` outcomes, each equally likely: the first byte of `hash` below the largest
+/// multiple of `choices` that fits in a byte, modulo `choices`. Bytes from that multiple on are
+/// skipped to avoid a bias, and if all 32 are skipped the hash is hashed again.
+pub(crate) fn unbiased_pick(mut hash: [u8; 32], choices: u8) -> u8 {
+    let limit = 256 - 256 % choices as u16;
+    loop {
+        if let Some(byte) = hash.iter().find(|byte| (**byte as u16) < limit) {
+            return byte % choices;
+        }
+        hash = Sha256::digest(hash).into();
+    }
+}
+
+/// Everything that goes into the hash of a single bet
+#[derive(Debug, Clone, Copy)]
+pub struct GameInput<'a> {
+    pub nonce: &'a str,
+    pub txid: &'a str,
+    /// Output index of the bet within the transaction, `None` for games stored before bets were
+    /// tracked per output
+    pub vout: Option<u32>,
+    /// Seed registered by the player for their address, if any
+    pub client_seed: Option<&'a str>,
+    /// How Satoshi's Number reads its roll from the hash, the version the game was stored with.
+    /// High/Low deals its cards and the jackpot is drawn differently between versions too, every
+    /// other game draws its outcome the same way under every version.
+    pub roll_version: RollVersion,
+}
+
+impl GameInput<'_> {
+    /// The string that gets hashed: nonce and txid, followed by the client seed if there is one
+    /// and `:{vout}` for bets tracked per output.
+    ///
+    /// Games without a client seed or vout hash exactly `{nonce}{txid}` so that older games still
+    /// verify.
+    pub fn hash_input(&self) -> String {
+        let mut hash_input = format!("{}{}", self.nonce, self.txid);
+        if let Some(client_seed) = self.client_seed {
+            hash_input.push_str(client_seed);
+        }
+        if let Some(vout) = self.vout {
+            hash_input.push_str(&format!(":{vout}"));
+        }
+        hash_input
+    }
+}
+
+/// What the player bets on. Every option of a game gets its own address, so the player picks one
+/// by paying to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameOption {
+    /// Win if the roll is below the threshold of the multiplier
+    Multiplier(Multiplier),
+    /// Bet that the drawn card is higher or lower than the reference card
+    HighLow(high_low::Direction),
+    /// Bet that the roll lands on one side of a target picked by the player
+    OverUnder(over_under::BetTarget),
+    /// Bet that the crash point reaches the cash-out target
+    Crash(crash::CashoutTarget),
+    /// Bet on a number or group of numbers of the roulette wheel
+    Roulette(roulette::RouletteBet),
+}
+
+impl GameOption {
+    /// Index of the option within its game, used to derive its address
+    pub fn index(&self) -> u32 {
+        match self {
+            GameOption::Multiplier(multiplier) => multiplier.index(),
+            GameOption::HighLow(direction) => *direction as u32,
+            GameOption::OverUnder(bet) => bet.index(),
+            GameOption::Crash(target) => target.index(),
+            GameOption::Roulette(bet) => bet.index(),
+        }
+    }
+}
+
+impl fmt::Display for GameOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameOption::Multiplier(multiplier) => write!(f, "{multiplier}"),
+            GameOption::HighLow(direction) => write!(f, "{direction}"),
+            GameOption::OverUnder(bet) => write!(f, "{bet}"),
+            GameOption::Crash(target) => write!(f, "{target}"),
+            GameOption::Roulette(bet) => write!(f, "{bet}"),
+        }
+    }
+}
+
+/// Trait that all games must implement.
+///
+/// Options of another game never win and pay nothing.
+pub trait Game: Send + Sync {
+    /// Evaluate the outcome of a bet of `bet_amount` sats
+    fn evaluate(&self, input: &GameInput, option: &GameOption, bet_amount: u64) -> GameEvaluation;
+
+    /// What the roll is compared against while the nonce with `nonce_hash` is active, e.g. the
+    /// roll threshold or the reference card
+    fn target(&self, nonce_hash: &str, option: &GameOption) -> i64;
+
+    /// The target of a game played under `roll_version`, see [`Game::target`] for new games. Only
+    /// games that roll differently between versions need to override it.
+    fn target_for_version(
+        &self,
+        nonce_hash: &str,
+        option: &GameOption,
+        _roll_version: RollVersion,
+    ) -> i64 {
+        self.target(nonce_hash, option)
+    }
+
+    /// Chance that `option` wins while the nonce with `nonce_hash` is active
+    fn win_probability(&self, nonce_hash: &str, option: &GameOption) -> f64;
+
+    /// Payout multiplier of a win while the nonce with `nonce_hash` is active, e.g. 200 for 2.00x
+    fn payout_multiplier(&self, nonce_hash: &str, option: &GameOption) -> u64;
+
+    /// Share of a bet on `option` that is returned to the player on average, e.g. 0.97
+    fn rtp(&self, nonce_hash: &str, option: &GameOption) -> f64 {
+        self.win_probability(nonce_hash, option) * self.payout_multiplier(nonce_hash, option) as f64
+            / 100.0
+    }
+
+    /// Display form of a stored rolled value, for games whose result is not just a number
+    fn outcome(&self, _rolled_value: i64) -> Option<String> {
+        None
+    }
+
+    /// The rolled values `option` wins on, for games where a bet covers a set of outcomes
+    fn winning_outcomes(&self, _option: &GameOption) -> Option<Vec<i64>> {
+        None
+    }
+
+    /// How the outcome is drawn from the hash, for games whose odds do not follow from the roll
+    /// of 0 to 65535 alone
+    fn distribution(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Get the game name
+    fn name(&self) -> &'static str;
+
+    /// Get a description of the game rules
+    fn description(&self) -> &'static str;
+
+    /// Settings the game is played with, e.g. its house edge
+    fn parameters(&self) -> Vec<GameParameter> {
+        Vec::new()
+    }
+}
+
+/// A named setting of a game, for display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameParameter {
+    pub name: &'static str,
+    pub value: u64,
+}
+
+/// Operator settings that games are evaluated with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameSettings {
+    /// Multipliers offered by [`GameType::SatoshisNumber`]
+    pub multipliers: MultiplierTable,
+    /// House edge of [`GameType::CoinFlip`] in basis points
+    pub coin_flip_house_edge_bps: u16,
+    /// House edge of [`GameType::HighLow`] in basis points
+    pub high_low_house_edge_bps: u16,
+    /// House edge of [`GameType::OverUnder`] in basis points
+    pub over_under_house_edge_bps: u16,
+    /// House edge of [`GameType::Crash`] in basis points
+    pub crash_house_edge_bps: u16,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            multipliers: MultiplierTable::default(),
+            coin_flip_house_edge_bps: coin_flip::CoinFlipGame::DEFAULT_HOUSE_EDGE_BPS,
+            high_low_house_edge_bps: high_low::HighLowGame::DEFAULT_HOUSE_EDGE_BPS,
+            over_under_house_edge_bps: over_under::OverUnderGame::DEFAULT_HOUSE_EDGE_BPS,
+            crash_house_edge_bps: crash::CrashGame::DEFAULT_HOUSE_EDGE_BPS,
+        }
+    }
+}
+
+impl GameSettings {
+    /// House edge of `game_type` in basis points, `None` for games whose odds are set otherwise
+    pub fn house_edge_bps(&self, game_type: GameType) -> Option<u16> {
+        match game_type {
+            GameType::CoinFlip => Some(self.coin_flip_house_edge_bps),
+            GameType::HighLow => Some(self.high_low_house_edge_bps),
+            GameType::OverUnder => Some(self.over_under_house_edge_bps),
+            GameType::Crash => Some(self.crash_house_edge_bps),
+            GameType::SatoshisNumber | GameType::Roulette => None,
+        }
+    }
+
+    /// Set the house edge of `game_type`, games without one are left as they are
+    pub fn set_house_edge_bps(&mut self, game_type: GameType, house_edge_bps: u16) {
+        match game_type {
+            GameType::CoinFlip => self.coin_flip_house_edge_bps = house_edge_bps,
+            GameType::HighLow => self.high_low_house_edge_bps = house_edge_bps,
+            GameType::OverUnder => self.over_under_house_edge_bps = house_edge_bps,
+            GameType::Crash => self.crash_house_edge_bps = house_edge_bps,
+            GameType::SatoshisNumber | GameType::Roulette => {}
+        }
+    }
+
+    /// The RTP bets on `game_type` paid out at `payout_multiplier` are expected to return, e.g.
+    /// 0.97. Games whose payout depends on the nonce or the player's target report the RTP their
+    /// house edge aims for.
+    pub fn theoretical_rtp(&self, game_type: GameType, payout_multiplier: u64) -> Option<f64> {
+        let house_edge_bps = match game_type {
+            GameType::SatoshisNumber => {
+                return self
+                    .multipliers
+                    .all()
+                    .iter()
+                    .chain(Multiplier::HISTORICAL.iter())
+                    .find(|multiplier| multiplier.multiplier() == payout_multiplier)
+                    .map(Multiplier::rtp);
+            }
+            GameType::CoinFlip => {
+                let option =
+                    GameOption::Multiplier(self.multipliers.find_stored(None, payout_multiplier)?);
+                return Some(
+                    coin_flip::CoinFlipGame::new(self.coin_flip_house_edge_bps).rtp("", &option),
+                );
+            }
+            GameType::HighLow => self.high_low_house_edge_bps,
+            GameType::OverUnder => self.over_under_house_edge_bps,
+            GameType::Crash => self.crash_house_edge_bps,
+            GameType::Roulette => {
+                let option = GameOption::Roulette(roulette::RouletteBet::Red);
+                return Some(roulette::RouletteGame.rtp("", &option));
+            }
+        };
+
+        Some(1.0 - house_edge_bps as f64 / 10_000.0)
+    }
+}
+
+/// Enum of available game types. The discriminant is stored with every game result.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameType {
+    #[default]
+    SatoshisNumber = 0,
+    CoinFlip = 1,
+    HighLow = 2,
+    OverUnder = 3,
+    Crash = 4,
+    Roulette = 5,
+    // Future games can be added here
+    // DiceRoll,
+}
+
+impl GameType {
+    pub fn all() -> Vec<Self> {
+        vec![
+            GameType::SatoshisNumber,
+            GameType::CoinFlip,
+            GameType::HighLow,
+            GameType::OverUnder,
+            GameType::Crash,
+            GameType::Roulette,
+        ]
+    }
+
+    /// Create from the stored discriminant
+    pub fn from_discriminant(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(GameType::SatoshisNumber),
+            1 => Some(GameType::CoinFlip),
+            2 => Some(GameType::HighLow),
+            3 => Some(GameType::OverUnder),
+            4 => Some(GameType::Crash),
+            5 => Some(GameType::Roulette),
+            _ => None,
+        }
+    }
+
+    /// The options a game can be played with, each of them gets its own address. Over/under has
+    /// none, every bet gets a one-time address for the target the player picked.
+    pub fn options(&self, multipliers: &MultiplierTable) -> Vec<GameOption> {
+        match self {
+            GameType::SatoshisNumber => multipliers
+                .all()
+                .iter()
+                .copied()
+                .map(GameOption::Multiplier)
+                .collect(),
+            GameType::CoinFlip => vec![GameOption::Multiplier(Multiplier::X200)],
+            GameType::HighLow => high_low::Direction::all()
+                .into_iter()
+                .map(GameOption::HighLow)
+                .collect(),
+            GameType::OverUnder => vec![],
+            GameType::Crash => crash::CrashGame::options(),
+            GameType::Roulette => roulette::RouletteBet::all()
+                .into_iter()
+                .map(GameOption::Roulette)
+                .collect(),
+        }
+    }
+
+    /// Create from the index of an option, see [`GameOption::index`]
+    pub fn option_from_index(
+        &self,
+        index: u32,
+        multipliers: &MultiplierTable,
+    ) -> Option<GameOption> {
+        match self {
+            GameType::OverUnder => {
+                over_under::BetTarget::from_index(index).map(GameOption::OverUnder)
+            }
+            // Stored bets keep decoding after the offered targets changed
+            GameType::Crash => crash::CashoutTarget::new(index).map(GameOption::Crash),
+            _ => self
+                .options(multipliers)
+                .into_iter()
+                .find(|option| option.index() == index),
+        }
+    }
+
+    /// Decode the option of a stored game. Games stored before options were recorded only have
+    /// their multiplier.
+    ///
+    /// Multipliers are looked up by their stored value as well, so games keep decoding after the
+    /// table changed.
+    pub fn stored_option(
+        &self,
+        option_index: Option<i64>,
+        multiplier: i64,
+        multipliers: &MultiplierTable,
+    ) -> Option<GameOption> {
+        let option_index = option_index.map(u32::try_from).transpose().ok()?;
+        match (self, option_index) {
+            (GameType::SatoshisNumber | GameType::CoinFlip, _) | (_, None) => multipliers
+                .find_stored(option_index, u64::try_from(multiplier).ok()?)
+                .map(GameOption::Multiplier),
+            (_, Some(index)) => self.option_from_index(index, multipliers),
+        }
+    }
+}
+
+impl FromStr for GameType {
+    type Err = ();
+
+    /// Parse the display name, e.g. "coin-flip"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GameType::all()
+            .into_iter()
+            .find(|game_type| game_type.to_string() == s)
+            .ok_or(())
+    }
+}
+
+impl fmt::Display for GameType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameType::SatoshisNumber => write!(f, "satoshis-number"),
+            GameType::CoinFlip => write!(f, "coin-flip"),
+            GameType::HighLow => write!(f, "high-low"),
+            GameType::OverUnder => write!(f, "over-under"),
+            GameType::Crash => write!(f, "crash"),
+            GameType::Roulette => write!(f, "roulette"),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use super::detail::Detail;
+use super::satoshis_number::SatoshisNumberGame;
+use super::Game;
+use super::GameEvaluation;
+use super::GameInput;
+use super::GameOption;
+use super::GameParameter;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// Number of possible rolls, 0 to 65535
+const ROLLS: u64 = 65_536;
+
+/// Which side of the target the roll has to land on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Under = 0,
+    Over = 1,
+}
+
+impl Side {
+    pub fn from_discriminant(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Side::Under),
+            1 => Some(Side::Over),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Side {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "under" => Ok(Side::Under),
+            "over" => Ok(Side::Over),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Under => write!(f, "under"),
+            Side::Over => write!(f, "over"),
+        }
+    }
+}
+
+/// A target picked by the player, e.g. roll under 12345
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BetTarget {
+    pub side: Side,
+    pub target: u16,
+}
+
+impl BetTarget {
+    /// Index of the bet, the side in the upper and the target in the lower 16 bits
+    pub fn index(&self) -> u32 {
+        ((self.side as u32) << 16) | self.target as u32
+    }
+
+    pub fn from_index(index: u32) -> Option<Self> {
+        Some(Self {
+            side: Side::from_discriminant(index >> 16)?,
+            target: (index & 0xFFFF) as u16,
+        })
+    }
+
+    /// Number of rolls that win. The target itself never wins.
+    pub fn winning_rolls(&self) -> u64 {
+        match self.side {
+            Side::Under => self.target as u64,
+            Side::Over => ROLLS - 1 - self.target as u64,
+        }
+    }
+
+    pub fn wins(&self, rolled: u16) -> bool {
+        match self.side {
+            Side::Under => rolled < self.target,
+            Side::Over => rolled > self.target,
+        }
+    }
+}
+
+impl fmt::Display for BetTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.side, self.target)
+    }
+}
+
+/// Roll over or under a target the player picks
+///
+/// Every bet gets its own address, so the target is known before the bet is paid. The roll is the
+/// same as [`SatoshisNumberGame`]'s, the payout follows from the number of winning rolls less the
+/// house edge.
+pub struct OverUnderGame {
+    house_edge_bps: u16,
+}
+
+impl OverUnderGame {
+    pub const DEFAULT_HOUSE_EDGE_BPS: u16 = 300;
+    /// Lowest payout a target may be offered at, 1.01x
+    pub const MIN_PAYOUT_MULTIPLIER: u64 = 101;
+    /// Highest payout a target may be offered at, 1000x
+    pub const MAX_PAYOUT_MULTIPLIER: u64 = 100_000;
+
+    /// `house_edge_bps` is the house edge in basis points, e.g. 300 for 3%
+    pub fn new(house_edge_bps: u16) -> Self {
+        Self {
+            house_edge_bps: house_edge_bps.min(10_000),
+        }
+    }
+
+    /// Payout multiplier of a target, e.g. 200 for 2.00x, 0 if no roll can win
+    pub fn target_payout(&self, bet: &BetTarget) -> u64 {
+        match bet.winning_rolls() {
+            0 => 0,
+            winning_rolls => ROLLS * (10_000 - self.house_edge_bps as u64) / (winning_rolls * 100),
+        }
+    }
+
+    /// The payout a target can be offered at, `None` if it is outside the accepted range
+    pub fn offered_payout(&self, bet: &BetTarget) -> Option<u64> {
+        let payout = self.target_payout(bet);
+        (Self::MIN_PAYOUT_MULTIPLIER..=Self::MAX_PAYOUT_MULTIPLIER)
+            .contains(&payout)
+            .then_some(payout)
+    }
+}
+
+impl Default for OverUnderGame {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_HOUSE_EDGE_BPS)
+    }
+}
+
+impl Game for OverUnderGame {
+    fn evaluate(&self, input: &GameInput, option: &GameOption, bet_amount: u64) -> GameEvaluation {
+        let rolled = SatoshisNumberGame::roll(input).random_value;
+        let player_wins = match option {
+            GameOption::OverUnder(bet) => bet.wins(rolled),
+            _ => false,
+        };
+        let mut detail = Detail::new().number("roll", rolled as i64);
+        if let GameOption::OverUnder(bet) = option {
+            detail = detail
+                .text("side", &bet.side.to_string())
+                .number("target", bet.target as i64);
+        }
+
+        GameEvaluation::win_or_loss(
+            rolled as i64,
+            player_wins,
+            bet_amount,
+            self.payout_multiplier("", option),
+            detail.build(),
+        )
+    }
+
+    fn target(&self, _nonce_hash: &str, option: &GameOption) -> i64 {
+        match option {
+            GameOption::OverUnder(bet) => bet.target as i64,
+            _ => 0,
+        }
+    }
+
+    fn win_probability(&self, _nonce_hash: &str, option: &GameOption) -> f64 {
+        match option {
+            GameOption::OverUnder(bet) => bet.winning_rolls() as f64 / ROLLS as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn payout_multiplier(&self, _nonce_hash: &str, option: &GameOption) -> u64 {
+        match option {
+            GameOption::OverUnder(bet) => self.target_payout(bet),
+            _ => 0,
+        }
+    }
+
+    fn parameters(&self) -> Vec<GameParameter> {
+        vec![
+            GameParameter {
+                name: "house_edge_bps",
+                value: self.house_edge_bps as u64,
+            },
+            GameParameter {
+                name: "min_payout_multiplier",
+                value: Self::MIN_PAYOUT_MULTIPLIER,
+            },
+            GameParameter {
+                name: "max_payout_multiplier",
+                value: Self::MAX_PAYOUT_MULTIPLIER,
+            },
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "Over/Under"
+    }
+
+    fn description(&self) -> &'static str {
+        "Pick any target and whether the roll lands over or under it. You get a one-time address \
+         for the bet, the less likely the roll, the higher the payout."
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::games::satoshis_number::RollVersion;
+    use std::format;
+    use std::string::ToString;
+
+    #[test]
+    fn test_index_roundtrip() {
+        for bet in [
+            BetTarget {
+                side: Side::Under,
+                target: 12_345,
+            },
+            BetTarget {
+                side: Side::Over,
+                target: 65_535,
+            },
+        ] {
+            assert_eq!(BetTarget::from_index(bet.index()), Some(bet));
+        }
+        assert_eq!(BetTarget::from_index(2 << 16), None);
+    }
+
+    #[test]
+    fn test_payout_follows_winning_rolls() {
+        let game = OverUnderGame::default();
+        let half = BetTarget {
+            side: Side::Under,
+            target: 32_768,
+        };
+        assert_eq!(game.offered_payout(&half), Some(194));
+
+        let impossible = BetTarget {
+            side: Side::Over,
+            target: 65_535,
+        };
+        assert_eq!(game.target_payout(&impossible), 0);
+        assert_eq!(game.offered_payout(&impossible), None);
+
+        // Too likely to pay more than the bet
+        let certain = BetTarget {
+            side: Side::Under,
+            target: 65_000,
+        };
+        assert_eq!(game.offered_payout(&certain), None);
+    }
+
+    #[test]
+    fn test_side_decides_winner() {
+        let game = OverUnderGame::default();
+        let under = GameOption::OverUnder(BetTarget {
+            side: Side::Under,
+            target: 31_000,
+        });
+        let over = GameOption::OverUnder(BetTarget {
+            side: Side::Over,
+            target: 31_000,
+        });
+
+        for i in 0..200 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let input = GameInput {
+                nonce: &nonce,
+                txid: &txid,
+                vout: Some(0),
+                client_seed: None,
+                roll_version: RollVersion::LATEST,
+            };
+            let rolled = SatoshisNumberGame::roll(&input).random_value;
+
+            let under_evaluation = game.evaluate(&input, &under, 100);
+            assert_eq!(under_evaluation.is_win, rolled < 31_000);
+            assert_eq!(game.evaluate(&input, &over, 100).is_win, rolled > 31_000);
+
+            // 2.05x is paid exactly, a float multiplication would round it down to 204 sats
+            if under_evaluation.is_win {
+                assert_eq!(under_evaluation.payout_sats, 205);
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use super::coin_flip::CoinFlipGame;
+use super::crash::CrashGame;
+use super::high_low::HighLowGame;
+use super::over_under::OverUnderGame;
+use super::roulette::RouletteGame;
+use super::satoshis_number::SatoshisNumberGame;
+use super::Game;
+use super::GameSettings;
+use super::GameType;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Every game, built with the operator's settings.
+///
+/// Disabled games take no new bets, but they are still here so that games stored while they were
+/// enabled can be verified.
+pub struct GameRegistry {
+    settings: GameSettings,
+    games: Vec<RegisteredGame>,
+}
+
+struct RegisteredGame {
+    game_type: GameType,
+    game: Box<dyn Game>,
+    enabled: bool,
+}
+
+impl GameRegistry {
+    /// Build every game with `settings`, the games in `disabled` take no bets
+    pub fn new(settings: GameSettings, disabled: &[GameType]) -> Self {
+        let games = GameType::all()
+            .into_iter()
+            .map(|game_type| RegisteredGame {
+                game_type,
+                game: build_game(game_type, &settings),
+                enabled: !disabled.contains(&game_type),
+            })
+            .collect();
+
+        Self { settings, games }
+    }
+
+    pub fn settings(&self) -> &GameSettings {
+        &self.settings
+    }
+
+    pub fn get(&self, game_type: GameType) -> &dyn Game {
+        self.games
+            .iter()
+            .find(|registered| registered.game_type == game_type)
+            .map(|registered| registered.game.as_ref())
+            .expect("every game type to be registered")
+    }
+
+    /// `game_type` as it was played at a house edge of `house_edge_bps`, so that games stored
+    /// before the edge was changed are evaluated with theirs. Without a stored edge the game is
+    /// built with the current settings.
+    pub fn played_with(&self, game_type: GameType, house_edge_bps: Option<u16>) -> Box<dyn Game> {
+        let mut settings = self.settings.clone();
+        if let Some(house_edge_bps) = house_edge_bps {
+            settings.set_house_edge_bps(game_type, house_edge_bps);
+        }
+        build_game(game_type, &settings)
+    }
+
+    pub fn is_enabled(&self, game_type: GameType) -> bool {
+        self.games
+            .iter()
+            .any(|registered| registered.game_type == game_type && registered.enabled)
+    }
+
+    /// The games that take bets, in the order of [`GameType::all`]
+    pub fn enabled(&self) -> impl Iterator<Item = (GameType, &dyn Game)> {
+        self.games
+            .iter()
+            .filter(|registered| registered.enabled)
+            .map(|registered| (registered.game_type, registered.game.as_ref()))
+    }
+}
+
+impl Default for GameRegistry {
+    fn default() -> Self {
+        Self::new(GameSettings::default(), &[])
+    }
+}
+
+fn build_game(game_type: GameType, settings: &GameSettings) -> Box<dyn Game> {
+    match game_type {
+        GameType::SatoshisNumber => Box::new(SatoshisNumberGame),
+        GameType::CoinFlip => Box::new(CoinFlipGame::new(settings.coin_flip_house_edge_bps)),
+        GameType::HighLow => Box::new(HighLowGame::new(settings.high_low_house_edge_bps)),
+        GameType::OverUnder => Box::new(OverUnderGame::new(settings.over_under_house_edge_bps)),
+        GameType::Crash => Box::new(CrashGame::new(settings.crash_house_edge_bps)),
+        GameType::Roulette => Box::new(RouletteGame),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::GameParameter;
+    use alloc::vec;
+
+    #[test]
+    fn test_disabled_games_stay_available() {
+        let registry = GameRegistry::new(GameSettings::default(), &[GameType::CoinFlip]);
+
+        assert!(!registry.is_enabled(GameType::CoinFlip));
+        assert!(registry.is_enabled(GameType::SatoshisNumber));
+        assert_eq!(
+            registry
+                .enabled()
+                .map(|(game_type, _)| game_type)
+                .collect::<Vec<_>>(),
+            vec![
+                GameType::SatoshisNumber,
+                GameType::HighLow,
+                GameType::OverUnder,
+                GameType::Crash,
+                GameType::Roulette
+            ]
+        );
+        assert_eq!(registry.get(GameType::CoinFlip).name(), "Coin Flip");
+    }
+
+    #[test]
+    fn test_played_with_stored_house_edge() {
+        let registry = GameRegistry::default();
+
+        assert_eq!(
+            registry.played_with(GameType::Crash, None).parameters(),
+            registry.get(GameType::Crash).parameters()
+        );
+        assert_eq!(
+            registry
+                .played_with(GameType::Crash, Some(1_000))
+                .parameters()[0],
+            GameParameter {
+                name: "house_edge_bps",
+                value: 1_000,
+            }
+        );
+    }
+}
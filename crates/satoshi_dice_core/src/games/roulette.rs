@@ -0,0 +1,253 @@
+use super::detail::Detail;
+use super::satoshis_number::SatoshisNumberGame;
+use super::unbiased_pick;
+use super::Game;
+use super::GameEvaluation;
+use super::GameInput;
+use super::GameOption;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Pockets of a European wheel, 0 to 36
+const POCKETS: u8 = 37;
+
+const RED: [u8; 18] = [
+    1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36,
+];
+
+/// Colour of a pocket, 0 is green
+pub fn color(number: u8) -> &'static str {
+    match number {
+        0 => "green",
+        number if RED.contains(&number) => "red",
+        _ => "black",
+    }
+}
+
+/// A bet on the wheel, every bet gets its own address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouletteBet {
+    Red,
+    Black,
+    Odd,
+    Even,
+    /// 1 to 12, 13 to 24 or 25 to 36 for dozen 1, 2 or 3
+    Dozen(u8),
+    /// Column 1 holds 1, 4, 7 and so on up to 34
+    Column(u8),
+    /// A single number, 0 to 36
+    Straight(u8),
+}
+
+impl RouletteBet {
+    pub fn all() -> Vec<Self> {
+        let mut bets = Vec::from([
+            RouletteBet::Red,
+            RouletteBet::Black,
+            RouletteBet::Odd,
+            RouletteBet::Even,
+        ]);
+        bets.extend((1..=3).map(RouletteBet::Dozen));
+        bets.extend((1..=3).map(RouletteBet::Column));
+        bets.extend((0..POCKETS).map(RouletteBet::Straight));
+        bets
+    }
+
+    /// Index of the bet, used to derive its address: 0 to 3 for red, black, odd and even, 11 to
+    /// 13 for the dozens, 21 to 23 for the columns and 100 plus the number for straight bets
+    pub fn index(&self) -> u32 {
+        match self {
+            RouletteBet::Red => 0,
+            RouletteBet::Black => 1,
+            RouletteBet::Odd => 2,
+            RouletteBet::Even => 3,
+            RouletteBet::Dozen(dozen) => 10 + *dozen as u32,
+            RouletteBet::Column(column) => 20 + *column as u32,
+            RouletteBet::Straight(number) => 100 + *number as u32,
+        }
+    }
+
+    /// Whether the bet wins on `number`. Zero only wins a straight bet on it.
+    pub fn wins(&self, number: u8) -> bool {
+        match self {
+            RouletteBet::Straight(straight) => number == *straight,
+            _ if number == 0 => false,
+            RouletteBet::Red => color(number) == "red",
+            RouletteBet::Black => color(number) == "black",
+            RouletteBet::Odd => !number.is_multiple_of(2),
+            RouletteBet::Even => number.is_multiple_of(2),
+            RouletteBet::Dozen(dozen) => (number - 1) / 12 + 1 == *dozen,
+            RouletteBet::Column(column) => (number - 1) % 3 + 1 == *column,
+        }
+    }
+
+    /// The numbers the bet wins on
+    pub fn numbers(&self) -> Vec<u8> {
+        (0..POCKETS).filter(|number| self.wins(*number)).collect()
+    }
+}
+
+impl fmt::Display for RouletteBet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouletteBet::Red => write!(f, "red"),
+            RouletteBet::Black => write!(f, "black"),
+            RouletteBet::Odd => write!(f, "odd"),
+            RouletteBet::Even => write!(f, "even"),
+            RouletteBet::Dozen(dozen) => write!(f, "dozen {dozen}"),
+            RouletteBet::Column(column) => write!(f, "column {column}"),
+            RouletteBet::Straight(number) => write!(f, "straight {number}"),
+        }
+    }
+}
+
+/// European roulette with a single zero
+///
+/// Every bet pays 36 divided by the numbers it covers, e.g. 2x for red and 36x for a single
+/// number, so the zero is the house edge of 1/37 on every bet.
+pub struct RouletteGame;
+
+impl RouletteGame {
+    /// The number the ball lands on, 0 to 36.
+    ///
+    /// The first byte of the bet's hash below 222 picks the number modulo 37, so every number is
+    /// equally likely. If all 32 bytes are 222 or above, the hash is hashed again.
+    pub fn spin(input: &GameInput) -> u8 {
+        unbiased_pick(SatoshisNumberGame::roll(input).hash, POCKETS)
+    }
+}
+
+impl Game for RouletteGame {
+    fn evaluate(&self, input: &GameInput, option: &GameOption, bet_amount: u64) -> GameEvaluation {
+        let number = Self::spin(input);
+        let player_wins = match option {
+            GameOption::Roulette(bet) => bet.wins(number),
+            _ => false,
+        };
+
+        GameEvaluation::win_or_loss(
+            number as i64,
+            player_wins,
+            bet_amount,
+            self.payout_multiplier("", option),
+            Detail::new()
+                .number("number", number as i64)
+                .text("color", color(number))
+                .build(),
+        )
+    }
+
+    fn target(&self, _nonce_hash: &str, option: &GameOption) -> i64 {
+        match option {
+            GameOption::Roulette(bet) => bet.index() as i64,
+            _ => 0,
+        }
+    }
+
+    fn win_probability(&self, _nonce_hash: &str, option: &GameOption) -> f64 {
+        match option {
+            GameOption::Roulette(bet) => bet.numbers().len() as f64 / POCKETS as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn payout_multiplier(&self, _nonce_hash: &str, option: &GameOption) -> u64 {
+        match option {
+            GameOption::Roulette(bet) => 3_600 / bet.numbers().len().max(1) as u64,
+            _ => 0,
+        }
+    }
+
+    fn winning_outcomes(&self, option: &GameOption) -> Option<Vec<i64>> {
+        match option {
+            GameOption::Roulette(bet) => Some(
+                bet.numbers()
+                    .into_iter()
+                    .map(|number| number as i64)
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    fn outcome(&self, rolled_value: i64) -> Option<String> {
+        let number = u8::try_from(rolled_value)
+            .ok()
+            .filter(|number| *number < POCKETS)?;
+        Some(format!("{number} {}", color(number)))
+    }
+
+    fn name(&self) -> &'static str {
+        "Roulette"
+    }
+
+    fn description(&self) -> &'static str {
+        "European roulette with a single zero. Red, black, odd and even pay 2x, dozens and \
+         columns 3x and a single number 36x. Every bet has its own address."
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::games::satoshis_number::RollVersion;
+    use std::string::ToString;
+
+    #[test]
+    fn test_bets_cover_the_wheel() {
+        let game = RouletteGame;
+        let bets = RouletteBet::all();
+        assert_eq!(bets.len(), 4 + 3 + 3 + 37);
+
+        for bet in &bets {
+            let option = GameOption::Roulette(*bet);
+            assert!((game.rtp("", &option) - 36.0 / 37.0).abs() < 1e-9, "{bet}");
+        }
+        assert_eq!(RouletteBet::Red.numbers().len(), 18);
+        assert_eq!(
+            RouletteBet::Dozen(3).numbers(),
+            (25..=36).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            RouletteBet::Column(1).numbers(),
+            (1..=34).step_by(3).collect::<Vec<_>>()
+        );
+        assert_eq!(RouletteBet::Straight(0).numbers(), [0]);
+        assert!(!RouletteBet::Even.wins(0));
+        assert_eq!(game.outcome(0), Some("0 green".to_string()));
+    }
+
+    #[test]
+    fn test_spin_is_uniform() {
+        let mut counts = [0u32; POCKETS as usize];
+
+        for i in 0..37_000 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let input = GameInput {
+                nonce: &nonce,
+                txid: &txid,
+                vout: Some(0),
+                client_seed: None,
+                roll_version: RollVersion::LATEST,
+            };
+            let number = RouletteGame::spin(&input);
+            counts[number as usize] += 1;
+
+            let evaluation =
+                RouletteGame.evaluate(&input, &GameOption::Roulette(RouletteBet::Red), 100);
+            assert_eq!(evaluation.is_win, color(number) == "red");
+        }
+
+        for (number, count) in counts.iter().enumerate() {
+            assert!(
+                (800..=1_200).contains(count),
+                "Unexpected count {count} of {number}"
+            );
+        }
+    }
+}
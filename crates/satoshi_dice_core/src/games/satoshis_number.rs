@@ -0,0 +1,383 @@
+use super::detail::Detail;
+use super::Game;
+use super::GameEvaluation;
+use super::GameInput;
+use super::GameOption;
+use alloc::string::String;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// The original Satoshi's Number game
+/// Players bet on whether a hash-derived number will be below a threshold
+pub struct SatoshisNumberGame;
+
+/// How the roll of Satoshi's Number is read from the hash. Every game stores the version it was
+/// played with, so games keep verifying after the roll changed.
+///
+/// Both roll ranges are powers of two, so every roll is equally likely without rejecting any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RollVersion {
+    /// The first 2 bytes of the hash as a big-endian u16, 65,536 rolls
+    V1 = 1,
+    /// The first 4 bytes of the hash as a big-endian u32, 4,294,967,296 rolls. Thresholds can be
+    /// set 65,536 times finer, and a threshold of 16-bit rolls wins on exactly the same hashes.
+    V2 = 2,
+}
+
+impl RollVersion {
+    /// The version new games are played with
+    pub const LATEST: RollVersion = RollVersion::V2;
+
+    /// The version of a stored game, `None` for versions this build does not know
+    pub fn from_stored(version: i64) -> Option<Self> {
+        match version {
+            1 => Some(RollVersion::V1),
+            2 => Some(RollVersion::V2),
+            _ => None,
+        }
+    }
+
+    /// Number of leading hash bytes the roll is read from
+    pub fn roll_bytes(&self) -> usize {
+        match self {
+            RollVersion::V1 => 2,
+            RollVersion::V2 => 4,
+        }
+    }
+
+    /// Offset of the 4 hash bytes the jackpot is drawn from. Under version 1 they follow the 2
+    /// bytes of the roll, later versions draw it after every byte a game reads its outcome from, so
+    /// that winning the jackpot says nothing about the outcome of the bet.
+    pub fn jackpot_offset(&self) -> usize {
+        match self {
+            RollVersion::V1 => 2,
+            RollVersion::V2 => 8,
+        }
+    }
+
+    /// Number of possible rolls, 0 to `rolls() - 1`
+    pub fn rolls(&self) -> u64 {
+        1 << (8 * self.roll_bytes())
+    }
+
+    fn roll(&self, hash: &[u8; 32]) -> u64 {
+        hash[..self.roll_bytes()]
+            .iter()
+            .fold(0, |roll, byte| (roll << 8) | *byte as u64)
+    }
+}
+
+/// The intermediate values of a roll, so that players can check every step
+#[derive(Debug, Clone)]
+pub struct RollBreakdown {
+    /// The string that gets hashed, see [`GameInput::hash_input`]
+    pub hash_input: String,
+    /// SHA256 of the hash input
+    pub hash: [u8; 32],
+    /// The first 2 bytes of the hash interpreted as a big-endian u16, what the other games draw
+    /// their outcome from under every roll version
+    pub random_value: u16,
+    /// The roll of Satoshi's Number under the roll version of the input
+    pub roll: u64,
+}
+
+impl SatoshisNumberGame {
+    /// Derive the random value for a bet
+    pub fn roll(input: &GameInput) -> RollBreakdown {
+        // Hash nonce + txid (+ client seed) to get randomness
+        let hash_input = input.hash_input();
+        let hash_bytes: [u8; 32] = Sha256::digest(hash_input.as_bytes()).into();
+
+        // Use first 2 bytes as u16 for randomness (0-65535 range)
+        let random_value = u16::from_be_bytes([hash_bytes[0], hash_bytes[1]]);
+
+        RollBreakdown {
+            hash_input,
+            hash: hash_bytes,
+            random_value,
+            roll: input.roll_version.roll(&hash_bytes),
+        }
+    }
+}
+
+impl Game for SatoshisNumberGame {
+    fn evaluate(&self, input: &GameInput, option: &GameOption, bet_amount: u64) -> GameEvaluation {
+        let roll = Self::roll(input).roll;
+        let rolled_number = roll as i64;
+        let player_wins = match option {
+            GameOption::Multiplier(multiplier) => multiplier.is_win(input.roll_version, roll),
+            _ => false,
+        };
+
+        GameEvaluation::win_or_loss(
+            rolled_number,
+            player_wins,
+            bet_amount,
+            self.payout_multiplier("", option),
+            Detail::new()
+                .number("roll", rolled_number)
+                .number(
+                    "threshold",
+                    self.target_for_version("", option, input.roll_version),
+                )
+                .build(),
+        )
+    }
+
+    fn target(&self, nonce_hash: &str, option: &GameOption) -> i64 {
+        self.target_for_version(nonce_hash, option, RollVersion::LATEST)
+    }
+
+    fn target_for_version(
+        &self,
+        _nonce_hash: &str,
+        option: &GameOption,
+        roll_version: RollVersion,
+    ) -> i64 {
+        match option {
+            GameOption::Multiplier(multiplier) => multiplier.threshold(roll_version) as i64,
+            _ => 0,
+        }
+    }
+
+    fn win_probability(&self, nonce_hash: &str, option: &GameOption) -> f64 {
+        self.target(nonce_hash, option) as f64 / RollVersion::LATEST.rolls() as f64
+    }
+
+    fn payout_multiplier(&self, _nonce_hash: &str, option: &GameOption) -> u64 {
+        match option {
+            GameOption::Multiplier(multiplier) => multiplier.multiplier(),
+            _ => 0,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "Satoshi's Number"
+    }
+
+    fn description(&self) -> &'static str {
+        "Guess if the hash-derived number will be below the target threshold. \
+         The lower the threshold, the higher the payout multiplier."
+    }
+}
+
+#[allow(clippy::print_stdout)]
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::multiplier::Multiplier;
+    use rayon::prelude::*;
+    use std::collections::HashMap;
+    use std::format;
+    use std::println;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    const TEST_ITERATIONS: usize = 1000;
+
+    fn run_multiplier_test(multiplier: Multiplier) -> (f64, f64, HashMap<&'static str, usize>) {
+        let game = SatoshisNumberGame;
+
+        let results: Vec<bool> = (0..TEST_ITERATIONS)
+            .into_par_iter()
+            .map(|i| {
+                let nonce = i.to_string();
+                let txid = format!("test_txid_{i}");
+                let input = GameInput {
+                    nonce: &nonce,
+                    txid: &txid,
+                    vout: None,
+                    client_seed: None,
+                    roll_version: RollVersion::V1,
+                };
+                let evaluation = game.evaluate(&input, &GameOption::Multiplier(multiplier), 1000);
+                evaluation.is_win
+            })
+            .collect();
+
+        let wins = results.iter().filter(|&&x| x).count();
+        let losses = results.iter().filter(|&&x| !x).count();
+
+        let actual_win_rate = (wins as f64 / TEST_ITERATIONS as f64) * 100.0;
+        let expected_win_rate = (multiplier.get_lower_than() as f64 / 65536.0) * 100.0;
+
+        let mut stats = HashMap::new();
+        stats.insert("wins", wins);
+        stats.insert("losses", losses);
+        stats.insert("total", TEST_ITERATIONS);
+
+        (actual_win_rate, expected_win_rate, stats)
+    }
+
+    #[test]
+    fn test_x200_multiplier() {
+        let multiplier = Multiplier::X200;
+        let (actual, expected, stats) = run_multiplier_test(multiplier);
+
+        println!("X200 Test Results:");
+        println!("Wins: {} | Losses: {}", stats["wins"], stats["losses"]);
+        println!("Expected win rate: {expected:.2}%",);
+        println!("Actual win rate: {actual:.2}%",);
+
+        assert!(
+            (actual - expected).abs() < 3.0,
+            "Win rate deviation too high"
+        );
+    }
+
+    #[test]
+    fn test_game_evaluation() {
+        let game = SatoshisNumberGame;
+        let input = GameInput {
+            nonce: "12345",
+            txid: "test_tx",
+            vout: None,
+            client_seed: None,
+            roll_version: RollVersion::V1,
+        };
+        let evaluation = game.evaluate(&input, &GameOption::Multiplier(Multiplier::X200), 1000);
+
+        // Check that evaluation produces expected fields
+        assert!(evaluation.rolled_value >= 0 && evaluation.rolled_value <= 65535);
+        assert_eq!(evaluation.refund_sats, None);
+        assert_eq!(
+            evaluation.detail,
+            format!(
+                r#"{{"roll":{},"threshold":{}}}"#,
+                evaluation.rolled_value,
+                Multiplier::X200.get_lower_than()
+            )
+        );
+        if evaluation.is_win {
+            assert_eq!(evaluation.payout_sats, 2000);
+            assert_eq!(evaluation.label, GameEvaluation::WIN);
+        } else {
+            assert_eq!(evaluation.payout_sats, 0);
+            assert_eq!(evaluation.label, GameEvaluation::LOSS);
+        }
+    }
+
+    #[test]
+    fn test_roll_breakdown_matches_evaluation() {
+        let input = GameInput {
+            nonce: "12345",
+            txid: "test_tx",
+            vout: None,
+            client_seed: None,
+            roll_version: RollVersion::V1,
+        };
+        let breakdown = SatoshisNumberGame::roll(&input);
+        let evaluation =
+            SatoshisNumberGame.evaluate(&input, &GameOption::Multiplier(Multiplier::X200), 1000);
+
+        assert_eq!(breakdown.hash_input, "12345test_tx");
+        assert_eq!(
+            breakdown.random_value,
+            u16::from_be_bytes([breakdown.hash[0], breakdown.hash[1]])
+        );
+        assert_eq!(breakdown.random_value as i64, evaluation.rolled_value);
+    }
+
+    #[test]
+    fn test_roll_versions() {
+        let fine_x200 = Multiplier::from_rtp(200, 11, 9_700).unwrap();
+
+        for i in 0..1_000 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let v1 = GameInput {
+                nonce: &nonce,
+                txid: &txid,
+                vout: Some(0),
+                client_seed: None,
+                roll_version: RollVersion::V1,
+            };
+            let v2 = GameInput {
+                roll_version: RollVersion::V2,
+                ..v1
+            };
+            let v1_roll = SatoshisNumberGame::roll(&v1);
+            let v2_roll = SatoshisNumberGame::roll(&v2);
+
+            assert_eq!(v1_roll.roll, v1_roll.random_value as u64);
+            assert_eq!(
+                v2_roll.roll,
+                u32::from_be_bytes([
+                    v2_roll.hash[0],
+                    v2_roll.hash[1],
+                    v2_roll.hash[2],
+                    v2_roll.hash[3]
+                ]) as u64
+            );
+            assert_eq!(v2_roll.roll >> 16, v1_roll.roll);
+
+            // Multipliers with a 16-bit threshold win on the same hashes under both versions
+            for multiplier in Multiplier::HISTORICAL {
+                let option = GameOption::Multiplier(multiplier);
+                assert_eq!(
+                    SatoshisNumberGame.evaluate(&v1, &option, 1000).is_win,
+                    SatoshisNumberGame.evaluate(&v2, &option, 1000).is_win
+                );
+            }
+
+            let evaluation =
+                SatoshisNumberGame.evaluate(&v2, &GameOption::Multiplier(fine_x200), 1000);
+            assert_eq!(evaluation.rolled_value, v2_roll.roll as i64);
+            assert_eq!(
+                evaluation.is_win,
+                v2_roll.roll < fine_x200.threshold(RollVersion::V2)
+            );
+        }
+
+        assert_eq!(RollVersion::from_stored(1), Some(RollVersion::V1));
+        assert_eq!(
+            RollVersion::from_stored(RollVersion::LATEST as i64),
+            Some(RollVersion::LATEST)
+        );
+        assert_eq!(RollVersion::from_stored(3), None);
+    }
+
+    #[test]
+    fn test_client_seed_changes_roll() {
+        let without_seed = GameInput {
+            nonce: "12345",
+            txid: "test_tx",
+            vout: None,
+            client_seed: None,
+            roll_version: RollVersion::V1,
+        };
+        let with_seed = GameInput {
+            client_seed: Some("lucky"),
+            ..without_seed
+        };
+
+        assert_eq!(with_seed.hash_input(), "12345test_txlucky");
+        assert_ne!(
+            SatoshisNumberGame::roll(&without_seed).hash,
+            SatoshisNumberGame::roll(&with_seed).hash
+        );
+    }
+
+    #[test]
+    fn test_each_output_is_its_own_bet() {
+        let first = GameInput {
+            nonce: "12345",
+            txid: "test_tx",
+            vout: Some(0),
+            client_seed: Some("lucky"),
+            roll_version: RollVersion::V1,
+        };
+        let second = GameInput {
+            vout: Some(1),
+            ..first
+        };
+
+        assert_eq!(first.hash_input(), "12345test_txlucky:0");
+        assert_ne!(
+            SatoshisNumberGame::roll(&first).hash,
+            SatoshisNumberGame::roll(&second).hash
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use crate::games::satoshis_number::SatoshisNumberGame;
+use crate::games::GameInput;
+
+/// Progressive jackpot on top of every game. Losing bets pay a share into the pot, and any bet
+/// that was played can win all of it.
+///
+/// Whether a bet wins the pot comes from the same hash as its roll, from 4 bytes that no game
+/// reads under the roll version of the bet, see
+/// [`RollVersion::jackpot_offset`](crate::games::satoshis_number::RollVersion::jackpot_offset). It
+/// is as verifiable as the game itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JackpotSettings {
+    /// Share of a losing bet that goes into the pot, in basis points
+    pub contribution_bps: u16,
+    /// A bet wins the pot with a chance of 1 in `odds`
+    pub odds: u32,
+}
+
+impl JackpotSettings {
+    /// A jackpot that takes nothing and is never won
+    pub const DISABLED: JackpotSettings = JackpotSettings {
+        contribution_bps: 0,
+        odds: 0,
+    };
+
+    pub fn is_enabled(&self) -> bool {
+        self.contribution_bps > 0 && self.odds > 0
+    }
+
+    /// Sats a losing bet of `bet_amount` adds to the pot, rounded down
+    pub fn contribution(&self, bet_amount: u64) -> u64 {
+        if !self.is_enabled() {
+            return 0;
+        }
+        (bet_amount as u128 * self.contribution_bps.min(10_000) as u128 / 10_000) as u64
+    }
+
+    /// Whether the bet wins the pot
+    pub fn is_win(&self, input: &GameInput) -> bool {
+        self.is_enabled() && (jackpot_roll(input) as u64) < (1u64 << 32) / self.odds as u64
+    }
+}
+
+/// The 4 bytes at the jackpot offset of the bet's roll version in its hash as a big-endian u32
+pub fn jackpot_roll(input: &GameInput) -> u32 {
+    let hash = SatoshisNumberGame::roll(input).hash;
+    let offset = input.roll_version.jackpot_offset();
+    u32::from_be_bytes([
+        hash[offset],
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::games::satoshis_number::RollVersion;
+    use std::format;
+    use std::string::ToString;
+
+    #[test]
+    fn test_contribution() {
+        let jackpot = JackpotSettings {
+            contribution_bps: 150,
+            odds: 1_000,
+        };
+        assert_eq!(jackpot.contribution(10_000), 150);
+        assert_eq!(jackpot.contribution(99), 1);
+        assert_eq!(jackpot.contribution(66), 0);
+        assert_eq!(JackpotSettings::DISABLED.contribution(10_000), 0);
+    }
+
+    #[test]
+    fn test_jackpot_bytes_follow_roll_version() {
+        let input = GameInput {
+            nonce: "nonce",
+            txid: "txid",
+            vout: Some(0),
+            client_seed: None,
+            roll_version: RollVersion::V1,
+        };
+        let hash = SatoshisNumberGame::roll(&input).hash;
+
+        // Games stored under version 1 keep the draw they were played with
+        assert_eq!(
+            jackpot_roll(&input),
+            u32::from_be_bytes([hash[2], hash[3], hash[4], hash[5]])
+        );
+
+        // The 32-bit roll reads the first 4 bytes, the draw must not share any of them
+        let latest = GameInput {
+            roll_version: RollVersion::LATEST,
+            ..input
+        };
+        assert!(RollVersion::LATEST.jackpot_offset() >= RollVersion::LATEST.roll_bytes());
+        assert_eq!(
+            jackpot_roll(&latest),
+            u32::from_be_bytes([hash[8], hash[9], hash[10], hash[11]])
+        );
+    }
+
+    #[test]
+    fn test_win_rate_follows_odds() {
+        let jackpot = JackpotSettings {
+            contribution_bps: 100,
+            odds: 100,
+        };
+        let mut wins = 0;
+
+        for i in 0..10_000 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let input = GameInput {
+                nonce: &nonce,
+                txid: &txid,
+                vout: Some(0),
+                client_seed: None,
+                roll_version: RollVersion::LATEST,
+            };
+
+            if jackpot.is_win(&input) {
+                wins += 1;
+            }
+            assert!(!JackpotSettings::DISABLED.is_win(&input));
+        }
+
+        assert!((60..=140).contains(&wins), "Unexpected jackpot wins {wins}");
+        assert!(JackpotSettings {
+            contribution_bps: 100,
+            odds: 1
+        }
+        .is_win(&GameInput {
+            nonce: "1",
+            txid: "tx",
+            vout: None,
+            client_seed: None,
+            roll_version: RollVersion::LATEST,
+        }));
+    }
+}
@@ -0,0 +1,14 @@
+//! Game evaluation shared by the server and the browser.
+//!
+//! Everything that decides the outcome of a bet lives here, without any I/O, so that the exact
+//! same code can be compiled to WASM for players to verify their rolls.
+#![no_std]
+
+extern crate alloc;
+
+pub mod duel;
+pub mod games;
+pub mod jackpot;
+pub mod lottery;
+pub mod multiplier;
+pub mod nonce;
@@ -0,0 +1,144 @@
+use alloc::format;
+use alloc::string::String;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// Scheduled lottery. Every payment to the lottery address buys tickets in proportion to its
+/// amount, and at the end of the round one ticket wins the pot less the rake.
+///
+/// The winning ticket comes from the nonce committed when the round opened and the hash of every
+/// ticket purchase, so neither the operator nor the last buyer can pick the winner alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LotterySettings {
+    /// Sats a single ticket costs
+    pub ticket_price_sats: u64,
+    /// Share of the pot kept by the house, in basis points
+    pub rake_bps: u16,
+}
+
+impl LotterySettings {
+    /// Tickets bought with `amount` sats, anything below the price of a ticket is not counted
+    pub fn tickets(&self, amount: u64) -> u64 {
+        amount.checked_div(self.ticket_price_sats).unwrap_or(0)
+    }
+
+    /// Sats kept by the house out of a pot of `pot` sats, rounded down
+    pub fn rake(&self, pot: u64) -> u64 {
+        (pot as u128 * self.rake_bps.min(10_000) as u128 / 10_000) as u64
+    }
+
+    /// Sats paid to the winner of a pot of `pot` sats
+    pub fn payout(&self, pot: u64) -> u64 {
+        pot - self.rake(pot)
+    }
+}
+
+/// Hex encoded SHA256 of every ticket purchase in the order they were bought, each written as
+/// `{txid}:{vout}`
+pub fn tickets_hash<'a>(outpoints: impl IntoIterator<Item = (&'a str, u32)>) -> String {
+    let mut hasher = Sha256::new();
+    for (txid, vout) in outpoints {
+        hasher.update(format!("{txid}:{vout}"));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Every step from the round's nonce and tickets to the winning ticket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LotteryDraw {
+    /// The string that gets hashed, `{nonce}{tickets_hash}`
+    pub hash_input: String,
+    /// SHA256 of the hash input
+    pub hash: [u8; 32],
+    /// The first 8 bytes of the hash as a big-endian u64 modulo the number of tickets, tickets
+    /// are numbered from 0 in the order they were bought
+    pub winning_ticket: u64,
+}
+
+/// Draw the winning ticket out of `total_tickets`, `None` if no ticket was bought
+pub fn draw(nonce: &str, tickets_hash: &str, total_tickets: u64) -> Option<LotteryDraw> {
+    if total_tickets == 0 {
+        return None;
+    }
+
+    let hash_input = format!("{nonce}{tickets_hash}");
+    let hash: [u8; 32] = Sha256::digest(hash_input.as_bytes()).into();
+    let mut random_bytes = [0u8; 8];
+    random_bytes.copy_from_slice(&hash[..8]);
+    let winning_ticket = u64::from_be_bytes(random_bytes) % total_tickets;
+
+    Some(LotteryDraw {
+        hash_input,
+        hash,
+        winning_ticket,
+    })
+}
+
+/// Index of the purchase holding `winning_ticket`, given the number of tickets of every purchase
+/// in the order they were bought
+pub fn winning_purchase(
+    ticket_counts: impl IntoIterator<Item = u64>,
+    winning_ticket: u64,
+) -> Option<usize> {
+    let mut first_ticket = 0u64;
+    for (index, tickets) in ticket_counts.into_iter().enumerate() {
+        first_ticket = first_ticket.saturating_add(tickets);
+        if winning_ticket < first_ticket {
+            return Some(index);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+    use std::string::ToString;
+    use std::vec;
+
+    #[test]
+    fn test_tickets_and_rake() {
+        let lottery = LotterySettings {
+            ticket_price_sats: 1_000,
+            rake_bps: 500,
+        };
+        assert_eq!(lottery.tickets(999), 0);
+        assert_eq!(lottery.tickets(2_500), 2);
+        assert_eq!(lottery.rake(10_001), 500);
+        assert_eq!(lottery.payout(10_001), 9_501);
+        assert_eq!(
+            LotterySettings {
+                ticket_price_sats: 0,
+                rake_bps: 0
+            }
+            .tickets(1_000),
+            0
+        );
+    }
+
+    #[test]
+    fn test_winner_follows_ticket_share() {
+        // The second purchase holds 3 of 4 tickets
+        let ticket_counts = [1, 3];
+        let mut wins = [0; 2];
+
+        for i in 0..4_000 {
+            let nonce = i.to_string();
+            let txid = format!("test_txid_{i}");
+            let hash = tickets_hash([(txid.as_str(), 0), (txid.as_str(), 1)]);
+            let draw = draw(&nonce, &hash, 4).expect("tickets were bought");
+
+            assert_eq!(draw.hash_input, format!("{nonce}{hash}"));
+            let winner = winning_purchase(ticket_counts, draw.winning_ticket).expect("a winner");
+            wins[winner] += 1;
+        }
+
+        assert!((800..=1_200).contains(&wins[0]), "Unexpected wins {wins:?}");
+        assert_eq!(draw("nonce", &tickets_hash(vec![]), 0), None);
+        assert_eq!(winning_purchase([1, 0, 2], 1), Some(2));
+        assert_eq!(winning_purchase([1, 2], 3), None);
+    }
+}
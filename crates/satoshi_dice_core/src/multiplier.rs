@@ -0,0 +1,584 @@
+use crate::games::satoshis_number::RollVersion;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Number of possible 32-bit rolls, the resolution thresholds are kept at
+const FINE_ROLLS: u64 = 1 << 32;
+
+/// A payout multiplier of the dice game and the roll below which it wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Multiplier {
+    value: u64,
+    index: u32,
+    /// Threshold of 32-bit rolls, the threshold of 16-bit rolls are its upper 16 bits
+    lower_than: u32,
+}
+
+impl Multiplier {
+    pub const X105: Multiplier = Multiplier::new(105, 0, 60_541);
+    pub const X110: Multiplier = Multiplier::new(110, 1, 57_789);
+    pub const X133: Multiplier = Multiplier::new(133, 2, 47_796);
+    pub const X150: Multiplier = Multiplier::new(150, 3, 42_379);
+    pub const X200: Multiplier = Multiplier::new(200, 4, 31_784);
+    pub const X300: Multiplier = Multiplier::new(300, 5, 21_189);
+    pub const X1000: Multiplier = Multiplier::new(1000, 6, 6_356);
+    pub const X2500: Multiplier = Multiplier::new(2500, 7, 2_542);
+    pub const X5000: Multiplier = Multiplier::new(5000, 8, 1_271);
+    pub const X10000: Multiplier = Multiplier::new(10000, 9, 635);
+    pub const X100000: Multiplier = Multiplier::new(100000, 10, 64);
+
+    /// The multipliers the game launched with, each with its own implicit house edge. Games stored
+    /// before multipliers were configurable used these, so their indices can never be given
+    /// different odds.
+    pub const HISTORICAL: [Multiplier; 11] = [
+        Multiplier::X105,
+        Multiplier::X110,
+        Multiplier::X133,
+        Multiplier::X150,
+        Multiplier::X200,
+        Multiplier::X300,
+        Multiplier::X1000,
+        Multiplier::X2500,
+        Multiplier::X5000,
+        Multiplier::X10000,
+        Multiplier::X100000,
+    ];
+
+    /// `value` is the payout, e.g. 105 for 1.05x, `index` the derivation index of its address and
+    /// `lower_than` the 16-bit roll below which it wins. It has the same odds under every roll
+    /// version.
+    pub const fn new(value: u64, index: u32, lower_than: u16) -> Self {
+        Self::with_fine_threshold(value, index, (lower_than as u32) << 16)
+    }
+
+    /// A multiplier that wins below the 32-bit roll `lower_than`. Games under roll version 1 win
+    /// below its upper 16 bits.
+    pub const fn with_fine_threshold(value: u64, index: u32, lower_than: u32) -> Self {
+        Self {
+            value,
+            index,
+            lower_than,
+        }
+    }
+
+    /// A multiplier that returns `rtp_bps` basis points of every bet at the resolution of the
+    /// latest roll version, see [`Multiplier::threshold_for_rtp`]. Under roll version 1 it wins
+    /// below the upper 16 bits of that threshold.
+    pub fn from_rtp(value: u64, index: u32, rtp_bps: u16) -> Option<Self> {
+        let threshold = Self::threshold_for_rtp(value, rtp_bps)?;
+        Some(Self::with_fine_threshold(
+            value,
+            index,
+            u32::try_from(threshold).ok()?,
+        ))
+    }
+
+    /// Threshold of the latest roll version that returns `rtp_bps` basis points of every bet to
+    /// the player at a payout of `value`, i.e. `floor(rolls * RTP / multiplier)`. `None` if it does
+    /// not fit into the roll range.
+    pub fn threshold_for_rtp(value: u64, rtp_bps: u16) -> Option<u64> {
+        if value == 0 || rtp_bps > 10_000 {
+            return None;
+        }
+        let rolls = RollVersion::LATEST.rolls();
+        let threshold = rolls * rtp_bps as u64 / (value * 100);
+        (threshold < rolls).then_some(threshold)
+    }
+
+    /// Threshold that pays `value` at a house edge of `house_edge_bps` basis points, see
+    /// [`Multiplier::threshold_for_rtp`]
+    pub fn threshold_for_house_edge(value: u64, house_edge_bps: u16) -> Option<u64> {
+        Self::threshold_for_rtp(value, 10_000u16.checked_sub(house_edge_bps)?)
+    }
+
+    /// Share of every bet that is returned to the player under the latest roll version, e.g. 0.97
+    pub fn rtp(&self) -> f64 {
+        self.lower_than as f64 / FINE_ROLLS as f64 * self.value as f64 / 100.0
+    }
+
+    /// Get the actual multiplier value (e.g., 1.05 becomes 105, 2.0 becomes 200)
+    pub fn multiplier(&self) -> u64 {
+        self.value
+    }
+
+    /// Get the index for derivation path
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The threshold of 16-bit rolls, see [`RollVersion::V1`]
+    pub const fn get_lower_than(&self) -> u16 {
+        (self.lower_than >> 16) as u16
+    }
+
+    /// The roll below which the multiplier wins under `roll_version`
+    pub fn threshold(&self, roll_version: RollVersion) -> u64 {
+        match roll_version {
+            RollVersion::V1 => self.get_lower_than() as u64,
+            RollVersion::V2 => self.lower_than as u64,
+        }
+    }
+
+    pub(crate) fn is_win(&self, roll_version: RollVersion, roll: u64) -> bool {
+        roll < self.threshold(roll_version)
+    }
+}
+
+impl fmt::Display for Multiplier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.multiplier() as f64 / 100.0;
+        if self.multiplier().is_multiple_of(100) {
+            write!(f, "{value:.0}x")
+        } else {
+            write!(f, "{value:.2}x")
+        }
+    }
+}
+
+/// What the stored games of one multiplier index reveal about the odds they were played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoredMultiplier {
+    pub index: u32,
+    pub value: u64,
+    pub roll_version: RollVersion,
+    /// Highest roll a stored game won with, the threshold was above it
+    pub highest_win: Option<u64>,
+    /// Lowest roll a stored game lost with, the threshold was at or below it
+    pub lowest_loss: Option<u64>,
+}
+
+/// Why a set of multipliers was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiplierError {
+    /// No multipliers at all
+    Empty,
+    /// A multiplier with a value of 0
+    ZeroValue { index: u32 },
+    /// Two multipliers share a derivation index
+    DuplicateIndex(u32),
+    /// Two multipliers share a value, which makes stored games ambiguous
+    DuplicateValue(u64),
+    /// The threshold is outside of the range of rolls under `roll_version`
+    ThresholdOutOfRange {
+        value: u64,
+        threshold: u64,
+        roll_version: RollVersion,
+    },
+    /// The return to player leaves no threshold within the range of rolls under `roll_version`
+    InvalidRtp {
+        value: u64,
+        rtp_bps: u16,
+        roll_version: RollVersion,
+    },
+    /// The index of a historical multiplier is used with different odds
+    HistoricalCollision {
+        configured: Multiplier,
+        historical: Multiplier,
+    },
+    /// The index of stored games is used with a different value, or with a threshold that
+    /// decides them differently
+    StoredCollision {
+        configured: Multiplier,
+        stored: StoredMultiplier,
+    },
+    /// The index of stored games is neither configured nor historical, so they could not be
+    /// decoded anymore
+    StoredMissing(StoredMultiplier),
+}
+
+impl fmt::Display for MultiplierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultiplierError::Empty => write!(f, "at least one multiplier is required"),
+            MultiplierError::ZeroValue { index } => {
+                write!(f, "multiplier at index {index} has a value of 0")
+            }
+            MultiplierError::DuplicateIndex(index) => {
+                write!(f, "index {index} is used by more than one multiplier")
+            }
+            MultiplierError::DuplicateValue(value) => {
+                write!(f, "value {value} is used by more than one multiplier")
+            }
+            MultiplierError::ThresholdOutOfRange {
+                value,
+                threshold,
+                roll_version,
+            } => write!(
+                f,
+                "threshold {threshold} of multiplier {value} is outside of 0..{} of roll version \
+                 {}",
+                roll_version.rolls(),
+                *roll_version as u8
+            ),
+            MultiplierError::InvalidRtp {
+                value,
+                rtp_bps,
+                roll_version,
+            } => write!(
+                f,
+                "an RTP of {rtp_bps} bps gives multiplier {value} no threshold within 0..{} of \
+                 roll version {}",
+                roll_version.rolls(),
+                *roll_version as u8
+            ),
+            MultiplierError::HistoricalCollision {
+                configured,
+                historical,
+            } => write!(
+                f,
+                "multiplier {} (index {}, threshold {}) collides with historical multiplier {} \
+                 (index {}, threshold {})",
+                configured.multiplier(),
+                configured.index(),
+                configured.get_lower_than(),
+                historical.multiplier(),
+                historical.index(),
+                historical.get_lower_than()
+            ),
+            MultiplierError::StoredCollision { configured, stored } => write!(
+                f,
+                "multiplier {} (index {}, threshold {}) contradicts games stored at that index \
+                 with multiplier {} under roll version {}",
+                configured.multiplier(),
+                configured.index(),
+                configured.threshold(stored.roll_version),
+                stored.value,
+                stored.roll_version as u8
+            ),
+            MultiplierError::StoredMissing(stored) => write!(
+                f,
+                "games were stored with multiplier {} at index {}, which is not configured",
+                stored.value, stored.index
+            ),
+        }
+    }
+}
+
+impl core::error::Error for MultiplierError {}
+
+/// The multipliers the dice game is offered with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiplierTable {
+    multipliers: Vec<Multiplier>,
+}
+
+impl MultiplierTable {
+    /// Check that the multipliers can be told apart and that none of them gives a historical index
+    /// different odds. Historical values may come back at a new index.
+    ///
+    /// Historical games were all played with 16-bit rolls, so a historical index may be offered
+    /// with finer odds as long as its 16-bit threshold stays the same.
+    pub fn new(multipliers: Vec<Multiplier>) -> Result<Self, MultiplierError> {
+        if multipliers.is_empty() {
+            return Err(MultiplierError::Empty);
+        }
+
+        for (i, multiplier) in multipliers.iter().enumerate() {
+            if multiplier.multiplier() == 0 {
+                return Err(MultiplierError::ZeroValue {
+                    index: multiplier.index(),
+                });
+            }
+
+            for other in &multipliers[..i] {
+                if other.index() == multiplier.index() {
+                    return Err(MultiplierError::DuplicateIndex(multiplier.index()));
+                }
+                if other.multiplier() == multiplier.multiplier() {
+                    return Err(MultiplierError::DuplicateValue(multiplier.multiplier()));
+                }
+            }
+
+            if let Some(historical) = Multiplier::HISTORICAL.iter().find(|historical| {
+                historical.index() == multiplier.index()
+                    && (historical.multiplier() != multiplier.multiplier()
+                        || historical.get_lower_than() != multiplier.get_lower_than())
+            }) {
+                return Err(MultiplierError::HistoricalCollision {
+                    configured: *multiplier,
+                    historical: *historical,
+                });
+            }
+        }
+
+        Ok(Self { multipliers })
+    }
+
+    /// Check that every index that games were stored with is still configured with their value
+    /// and a threshold that decides them the same way. Historical multipliers may be dropped, their
+    /// games are decoded from [`Multiplier::HISTORICAL`], see [`MultiplierTable::new`].
+    pub fn check_stored(&self, stored: &[StoredMultiplier]) -> Result<(), MultiplierError> {
+        for stored in stored {
+            let Some(configured) = self.from_index(stored.index) else {
+                let historical = Multiplier::HISTORICAL.iter().any(|historical| {
+                    historical.index() == stored.index && historical.multiplier() == stored.value
+                });
+                if !historical {
+                    return Err(MultiplierError::StoredMissing(*stored));
+                }
+                continue;
+            };
+
+            let threshold = configured.threshold(stored.roll_version);
+            let consistent = configured.multiplier() == stored.value
+                && stored.highest_win.is_none_or(|roll| roll < threshold)
+                && stored.lowest_loss.is_none_or(|roll| roll >= threshold);
+            if !consistent {
+                return Err(MultiplierError::StoredCollision {
+                    configured,
+                    stored: *stored,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn all(&self) -> &[Multiplier] {
+        &self.multipliers
+    }
+
+    /// Create from index
+    pub fn from_index(&self, index: u32) -> Option<Multiplier> {
+        self.multipliers
+            .iter()
+            .find(|multiplier| multiplier.index() == index)
+            .copied()
+    }
+
+    /// Find the multiplier a game was stored with, by its value and, for games that recorded it,
+    /// its index. Games without an index were all played with historical multipliers.
+    ///
+    /// Historical multipliers are found even if they are no longer offered.
+    pub fn find_stored(&self, index: Option<u32>, value: u64) -> Option<Multiplier> {
+        let find = |multipliers: &[Multiplier]| {
+            multipliers
+                .iter()
+                .find(|multiplier| {
+                    multiplier.multiplier() == value
+                        && index.is_none_or(|i| multiplier.index() == i)
+                })
+                .copied()
+        };
+
+        match index {
+            Some(_) => find(&self.multipliers).or_else(|| find(&Multiplier::HISTORICAL)),
+            None => find(&Multiplier::HISTORICAL),
+        }
+    }
+}
+
+impl Default for MultiplierTable {
+    fn default() -> Self {
+        Self {
+            multipliers: Multiplier::HISTORICAL.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_house_edge_close_to_historical_thresholds() {
+        // The historical thresholds were rounded by hand, a 3% edge lands within one 16-bit roll of
+        // them
+        for multiplier in Multiplier::HISTORICAL {
+            let threshold =
+                Multiplier::threshold_for_house_edge(multiplier.multiplier(), 300).unwrap() >> 16;
+            assert!(
+                threshold.abs_diff(multiplier.get_lower_than() as u64) <= 1,
+                "{multiplier}: {threshold}"
+            );
+        }
+        assert_eq!(Multiplier::threshold_for_house_edge(50, 0), None);
+    }
+
+    #[test]
+    fn test_threshold_from_rtp() {
+        // floor(2^32 * 0.97 / 2)
+        assert_eq!(
+            Multiplier::threshold_for_rtp(200, 9_700),
+            Some(2_083_059_138)
+        );
+        // floor(2^32 * 0.97 / 1000)
+        assert_eq!(
+            Multiplier::threshold_for_rtp(100_000, 9_700),
+            Some(4_166_118)
+        );
+        assert_eq!(Multiplier::threshold_for_rtp(200, 10_001), None);
+        assert_eq!(Multiplier::threshold_for_rtp(50, 10_000), None);
+
+        let rtp = Multiplier::new(200, 12, 31_784).rtp();
+        assert!((rtp - 0.97).abs() < 0.0001, "{rtp}");
+    }
+
+    #[test]
+    fn test_fine_threshold_from_rtp() {
+        // The latest roll version gets 100000x within a millionth of a 97% RTP
+        let fine = Multiplier::from_rtp(100_000, 11, 9_700).unwrap();
+        assert_eq!(fine.threshold(RollVersion::LATEST), 4_166_118);
+        assert!((fine.rtp() - 0.97).abs() < 0.000_001, "{}", fine.rtp());
+
+        // Games under roll version 1 win below the upper 16 bits of the threshold
+        for multiplier in Multiplier::HISTORICAL {
+            let fine =
+                Multiplier::from_rtp(multiplier.multiplier(), multiplier.index(), 9_700).unwrap();
+            assert_eq!(
+                Some(fine.get_lower_than() as u64),
+                Multiplier::threshold_for_rtp(multiplier.multiplier(), 9_700)
+                    .map(|threshold| threshold >> 16)
+            );
+            assert_eq!(
+                multiplier.threshold(RollVersion::V2),
+                (multiplier.get_lower_than() as u64) << 16
+            );
+        }
+        assert_eq!(Multiplier::from_rtp(50, 11, 10_000), None);
+    }
+
+    #[test]
+    fn test_table_validation() {
+        assert!(MultiplierTable::new(Multiplier::HISTORICAL.to_vec()).is_ok());
+        assert!(
+            MultiplierTable::new(vec![Multiplier::X200, Multiplier::new(400, 11, 15_892)]).is_ok()
+        );
+
+        assert_eq!(MultiplierTable::new(vec![]), Err(MultiplierError::Empty));
+        assert_eq!(
+            MultiplierTable::new(vec![
+                Multiplier::new(400, 11, 15_892),
+                Multiplier::new(500, 11, 12_713)
+            ]),
+            Err(MultiplierError::DuplicateIndex(11))
+        );
+        assert_eq!(
+            MultiplierTable::new(vec![
+                Multiplier::new(400, 11, 15_892),
+                Multiplier::new(400, 12, 16_000)
+            ]),
+            Err(MultiplierError::DuplicateValue(400))
+        );
+
+        // Historical values and indices keep their odds
+        let cheaper_x200 = Multiplier::new(200, 4, 32_000);
+        assert_eq!(
+            MultiplierTable::new(vec![cheaper_x200]),
+            Err(MultiplierError::HistoricalCollision {
+                configured: cheaper_x200,
+                historical: Multiplier::X200,
+            })
+        );
+        let moved_index = Multiplier::new(400, 4, 15_892);
+        assert_eq!(
+            MultiplierTable::new(vec![moved_index]),
+            Err(MultiplierError::HistoricalCollision {
+                configured: moved_index,
+                historical: Multiplier::X200,
+            })
+        );
+
+        // A historical value can be offered with new odds at a new index
+        assert!(MultiplierTable::new(vec![Multiplier::new(200, 11, 32_440)]).is_ok());
+
+        // Or with finer odds at its own index, historical games roll 16 bits
+        let finer_x200 = Multiplier::from_rtp(200, 4, 9_700).unwrap();
+        assert_ne!(finer_x200, Multiplier::X200);
+        assert!(MultiplierTable::new(vec![finer_x200]).is_ok());
+    }
+
+    #[test]
+    fn test_stored_games_keep_their_odds() {
+        let x400 = Multiplier::new(400, 11, 15_892);
+        let table = MultiplierTable::new(vec![x400, Multiplier::X200]).unwrap();
+        let stored = StoredMultiplier {
+            index: 11,
+            value: 400,
+            roll_version: RollVersion::V1,
+            highest_win: Some(15_891),
+            lowest_loss: Some(15_892),
+        };
+        assert_eq!(table.check_stored(&[stored]), Ok(()));
+
+        // Historical multipliers that are no longer offered still decode
+        let historical = StoredMultiplier {
+            index: Multiplier::X105.index(),
+            value: Multiplier::X105.multiplier(),
+            ..stored
+        };
+        assert_eq!(table.check_stored(&[historical]), Ok(()));
+
+        // Other multipliers games were stored with cannot be dropped
+        let retired = StoredMultiplier {
+            index: 12,
+            value: 500,
+            ..stored
+        };
+        assert_eq!(
+            table.check_stored(&[retired]),
+            Err(MultiplierError::StoredMissing(retired))
+        );
+
+        let other_value = StoredMultiplier {
+            value: 500,
+            ..stored
+        };
+        assert_eq!(
+            table.check_stored(&[other_value]),
+            Err(MultiplierError::StoredCollision {
+                configured: x400,
+                stored: other_value,
+            })
+        );
+
+        // A stored win at the configured threshold was played with better odds
+        let higher_threshold = StoredMultiplier {
+            highest_win: Some(15_892),
+            lowest_loss: None,
+            ..stored
+        };
+        assert_eq!(
+            table.check_stored(&[higher_threshold]),
+            Err(MultiplierError::StoredCollision {
+                configured: x400,
+                stored: higher_threshold,
+            })
+        );
+
+        // 32-bit rolls are compared against the 32-bit threshold
+        let fine = StoredMultiplier {
+            roll_version: RollVersion::V2,
+            highest_win: Some((15_892 << 16) - 1),
+            lowest_loss: Some(15_892 << 16),
+            ..stored
+        };
+        assert_eq!(table.check_stored(&[fine]), Ok(()));
+    }
+
+    #[test]
+    fn test_stored_games_decode_after_table_change() {
+        let table = MultiplierTable::new(vec![
+            Multiplier::new(400, 11, 15_892),
+            Multiplier::new(105, 12, 61_791),
+        ])
+        .unwrap();
+
+        // Games without an index predate configurable multipliers
+        assert_eq!(table.find_stored(None, 105), Some(Multiplier::X105));
+        assert_eq!(
+            table.find_stored(Some(12), 105),
+            Some(Multiplier::new(105, 12, 61_791))
+        );
+        assert_eq!(table.find_stored(Some(4), 200), Some(Multiplier::X200));
+        assert_eq!(table.find_stored(Some(5), 200), None);
+        assert_eq!(
+            table.find_stored(Some(11), 400),
+            Some(Multiplier::new(400, 11, 15_892))
+        );
+        assert_eq!(table.from_index(4), None);
+    }
+}
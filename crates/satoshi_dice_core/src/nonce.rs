@@ -0,0 +1,51 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// Hex encoded SHA256 of a nonce, which is what we commit to before revealing it
+pub fn hash_nonce(nonce: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(nonce);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Whether a revealed nonce matches its published commitment
+pub fn verify_nonce_hash(nonce: &str, nonce_hash: &str) -> bool {
+    hash_nonce(nonce).eq_ignore_ascii_case(nonce_hash)
+}
+
+/// Derive the nonce at `position` of a hash chain with `length` links.
+///
+/// Position 0 is the terminal that gets published up front, position `length` is the seed itself.
+/// Nonces are used from position 1 upwards, so every revealed nonce hashes to the one used before
+/// it, and the first one hashes to the terminal.
+pub fn chain_nonce(seed: &str, length: u32, position: u32) -> String {
+    let mut nonce = seed.to_string();
+    for _ in position..length {
+        nonce = hash_nonce(&nonce);
+    }
+    nonce
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_nonces_hash_to_previous() {
+        let seed = "seed";
+        let length = 5;
+        let terminal = chain_nonce(seed, length, 0);
+
+        assert_eq!(hash_nonce(&chain_nonce(seed, length, 1)), terminal);
+        for position in 2..=length {
+            assert_eq!(
+                hash_nonce(&chain_nonce(seed, length, position)),
+                chain_nonce(seed, length, position - 1)
+            );
+        }
+        assert_eq!(chain_nonce(seed, length, length), seed);
+    }
+}
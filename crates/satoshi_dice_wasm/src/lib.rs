@@ -0,0 +1,201 @@
+//! `wasm-bindgen` bindings for [`satoshi_dice_core`], so that players can verify their rolls in
+//! the browser with the same code the server evaluates bets with.
+//!
+//! Build with `wasm-pack build crates/satoshi_dice_wasm --target web`.
+
+use satoshi_dice_core::games::registry::GameRegistry;
+use satoshi_dice_core::games::satoshis_number::RollVersion;
+use satoshi_dice_core::games::satoshis_number::SatoshisNumberGame;
+use satoshi_dice_core::games::GameInput;
+use satoshi_dice_core::games::GameSettings;
+use satoshi_dice_core::games::GameType;
+use satoshi_dice_core::lottery;
+use satoshi_dice_core::multiplier::Multiplier;
+use satoshi_dice_core::multiplier::MultiplierTable;
+use satoshi_dice_core::nonce::hash_nonce;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsError;
+
+/// Every step from nonce and txid to the outcome of a bet
+#[wasm_bindgen(getter_with_clone)]
+pub struct Evaluation {
+    /// The string that gets hashed
+    pub hash_input: String,
+    /// Hex encoded SHA256 of the hash input
+    pub hash: String,
+    /// The roll of Satoshi's Number: the first 2 bytes of the hash as a big-endian u16 under roll
+    /// version 1, the first 4 as a u32 under version 2
+    pub rolled_number: u64,
+    /// The value stored as the result of the game, e.g. the drawn card
+    pub result_number: i64,
+    /// What the result is compared against, e.g. the roll threshold or the reference card
+    pub target: i64,
+    pub is_win: bool,
+    /// Coin side or card, for games whose result is not just a number
+    pub outcome: Option<String>,
+    /// Sats paid for the win, 0 without a bet amount
+    pub payout_sats: u64,
+    /// Part of the bet returned without a win
+    pub refund_sats: Option<u64>,
+    /// Short name of the outcome, e.g. "win" or "loss"
+    pub label: String,
+    /// Game specific details as a JSON object
+    pub detail: String,
+}
+
+/// Every step from a lottery round's nonce and tickets to its winner
+#[wasm_bindgen(getter_with_clone)]
+pub struct LotteryDrawInfo {
+    /// Hex encoded SHA256 of every ticket purchase
+    pub tickets_hash: String,
+    /// The string that gets hashed
+    pub hash_input: String,
+    /// Hex encoded SHA256 of the hash input
+    pub hash: String,
+    /// Number of the winning ticket, counted from 0 across all purchases
+    pub winning_ticket: u64,
+    /// Index of the purchase holding the winning ticket
+    pub winner_index: u32,
+}
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct MultiplierInfo {
+    /// Stored multiplier value, e.g. 200 for 2.00x
+    pub value: u64,
+    /// Display name, e.g. "2x"
+    pub label: String,
+    pub threshold: u16,
+    pub win_probability: f64,
+}
+
+/// Evaluate a bet as stored: `game_type` and `game_option` are the stored discriminants,
+/// `multiplier` the stored value, e.g. 200 for 2.00x. `game_option` is omitted for games stored
+/// before options were recorded, `vout` for games stored before bets were tracked per output.
+/// `house_edge_bps` is the house edge the game was played with, for games that have one,
+/// `threshold` gives the threshold of a multiplier the operator configured in addition to the
+/// built-in ones, in rolls of the game's roll version, `bet_amount` the sats that were bet to
+/// compute the payout. `roll_version` is the stored roll version, the latest one if omitted.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate(
+    game_type: u8,
+    game_option: Option<u32>,
+    nonce: &str,
+    txid: &str,
+    vout: Option<u32>,
+    client_seed: Option<String>,
+    multiplier: u64,
+    house_edge_bps: Option<u16>,
+    threshold: Option<u32>,
+    bet_amount: Option<u64>,
+    roll_version: Option<u8>,
+) -> Result<Evaluation, JsError> {
+    let game_type = GameType::from_discriminant(game_type as i64)
+        .ok_or_else(|| JsError::new(&format!("Unknown game type: {game_type}")))?;
+    let roll_version = match roll_version {
+        Some(version) => RollVersion::from_stored(version as i64)
+            .ok_or_else(|| JsError::new(&format!("Unknown roll version: {version}")))?,
+        None => RollVersion::LATEST,
+    };
+    let mut settings = GameSettings::default();
+    if let (Some(threshold), Some(index)) = (threshold, game_option) {
+        let configured = match roll_version {
+            RollVersion::V1 => Multiplier::new(
+                multiplier,
+                index,
+                u16::try_from(threshold)
+                    .map_err(|_| JsError::new(&format!("Threshold out of range: {threshold}")))?,
+            ),
+            RollVersion::V2 => Multiplier::with_fine_threshold(multiplier, index, threshold),
+        };
+        settings.multipliers =
+            MultiplierTable::new(vec![configured]).map_err(|e| JsError::new(&e.to_string()))?;
+    }
+    let option = game_type
+        .stored_option(
+            game_option.map(i64::from),
+            multiplier as i64,
+            &settings.multipliers,
+        )
+        .ok_or_else(|| JsError::new(&format!("Unknown option of {game_type}")))?;
+    if let Some(house_edge_bps) = house_edge_bps {
+        settings.set_house_edge_bps(game_type, house_edge_bps);
+    }
+
+    let input = GameInput {
+        nonce,
+        txid,
+        vout,
+        client_seed: client_seed.as_deref(),
+        roll_version,
+    };
+    let roll = SatoshisNumberGame::roll(&input);
+    let games = GameRegistry::new(settings, &[]);
+    let game = games.get(game_type);
+    let evaluation = game.evaluate(&input, &option, bet_amount.unwrap_or_default());
+
+    Ok(Evaluation {
+        hash_input: roll.hash_input,
+        hash: hex::encode(roll.hash),
+        rolled_number: roll.roll,
+        result_number: evaluation.rolled_value,
+        target: game.target_for_version(&hash_nonce(nonce), &option, roll_version),
+        is_win: evaluation.is_win,
+        outcome: game.outcome(evaluation.rolled_value),
+        payout_sats: evaluation.payout_sats,
+        refund_sats: evaluation.refund_sats,
+        label: evaluation.label.to_string(),
+        detail: evaluation.detail,
+    })
+}
+
+/// Draw the winner of a lottery round: `txids`, `vouts` and `ticket_counts` describe every
+/// ticket purchase of the round in the order they were bought.
+#[wasm_bindgen(js_name = drawLottery)]
+pub fn draw_lottery(
+    nonce: &str,
+    txids: Vec<String>,
+    vouts: Vec<u32>,
+    ticket_counts: Vec<u64>,
+) -> Result<LotteryDrawInfo, JsError> {
+    if txids.len() != vouts.len() || txids.len() != ticket_counts.len() {
+        return Err(JsError::new(
+            "Every purchase needs a txid, vout and ticket count",
+        ));
+    }
+
+    let tickets_hash =
+        lottery::tickets_hash(txids.iter().map(String::as_str).zip(vouts.iter().copied()));
+    let draw = lottery::draw(nonce, &tickets_hash, ticket_counts.iter().sum())
+        .ok_or_else(|| JsError::new("No tickets were bought"))?;
+    let winner_index = lottery::winning_purchase(ticket_counts, draw.winning_ticket)
+        .ok_or_else(|| JsError::new("Winning ticket out of range"))?;
+
+    Ok(LotteryDrawInfo {
+        tickets_hash,
+        hash_input: draw.hash_input,
+        hash: hex::encode(draw.hash),
+        winning_ticket: draw.winning_ticket,
+        winner_index: winner_index as u32,
+    })
+}
+
+/// Whether a revealed nonce matches the nonce hash that was published before the game
+#[wasm_bindgen(js_name = verifyNonceHash)]
+pub fn verify_nonce_hash(nonce: &str, nonce_hash: &str) -> bool {
+    satoshi_dice_core::nonce::verify_nonce_hash(nonce, nonce_hash)
+}
+
+/// The built-in multipliers, operators may offer others
+#[wasm_bindgen(js_name = listMultipliers)]
+pub fn list_multipliers() -> Vec<MultiplierInfo> {
+    Multiplier::HISTORICAL
+        .into_iter()
+        .map(|multiplier| MultiplierInfo {
+            value: multiplier.multiplier(),
+            label: multiplier.to_string(),
+            threshold: multiplier.get_lower_than(),
+            win_probability: multiplier.get_lower_than() as f64 / 65536.0,
+        })
+        .collect()
+}
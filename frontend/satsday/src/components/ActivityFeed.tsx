@@ -241,7 +241,7 @@ const ActivityFeed = () => {
                       <p className="text-sm font-medium text-gray-300">
                         Result:{" "}
                         <span className="text-white">
-                          {activity.result_number}
+                          {activity.outcome ?? activity.result_number}
                         </span>
                       </p>
                       <p className="text-sm font-medium text-gray-300">
@@ -15,15 +15,16 @@ export default function HowItWorksContent() {
     {
       icon: Hash,
       title: "Seed Combination",
-      description: "The transaction hash is combined with our server seed.",
+      description:
+        "The transaction hash is combined with our server nonce and your client seed.",
       detail:
-        "Your transaction hash + our server seed = provably fair randomness.",
+        "Our nonce + your transaction hash + your client seed = provably fair randomness.",
     },
     {
       icon: Dice6,
       title: "Result Generation",
       description:
-        "The combined hash generates a number from 1-65535 for the dice result.",
+        "The first 4 bytes of the SHA-256 hash give a number from 0 to 4294967295 for the dice result.",
       detail:
         "Mathematical algorithms convert the hash into a fair dice roll you can verify.",
     },
@@ -106,11 +107,14 @@ export default function HowItWorksContent() {
             </div>
           </div>
 
-          {/* TODO: fix the description */}
           <div className="bg-muted/30 p-4 rounded-lg">
             <p className="text-sm text-muted-foreground text-center">
-              <strong>Formula:</strong> HMAC-SHA256(client_tx, server_seed) →
-              Dice Result (1-65535)
+              <strong>Formula:</strong> SHA256(nonce + txid + client_seed +
+              ":" + vout) → first 4 bytes → Dice Result (0-4294967295)
+            </p>
+            <p className="text-xs text-muted-foreground text-center mt-2">
+              Games played before 32-bit rolls use the first 2 bytes
+              (0-65535).
             </p>
           </div>
         </CardContent>
@@ -20,9 +20,11 @@ interface LegacyWebSocketMessage {
 interface BackendWebSocketMessage {
   type: "game_result";
   id: string;
+  game_type?: string;
   amount_sent: number;
   multiplier: number;
   result_number: number;
+  outcome?: string | null;
   target_number: number;
   is_win: boolean;
   payout?: number;
@@ -137,9 +139,11 @@ export function useGameWebSocket(
           // Real-time game result - convert to GameHistoryItem format
           const gameItem: GameHistoryItem = {
             id: data.id,
+            game_type: data.game_type,
             amount_sent: data.amount_sent,
             multiplier: data.multiplier,
             result_number: data.result_number,
+            outcome: data.outcome,
             target_number: data.target_number,
             is_win: data.is_win,
             payout: data.payout,
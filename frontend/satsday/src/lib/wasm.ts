@@ -0,0 +1,11 @@
+// The game evaluation of crates/satoshi_dice_wasm, generated by `just build-wasm`
+import init, { evaluate, verifyNonceHash } from "@/wasm/satoshi_dice_wasm";
+
+let initialized: Promise<unknown> | null = null;
+
+// Load the WASM module once, before any of its functions are called
+export async function loadGameEvaluation() {
+  initialized ??= init();
+  await initialized;
+  return { evaluate, verifyNonceHash };
+}
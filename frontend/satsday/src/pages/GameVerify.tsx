@@ -9,39 +9,59 @@ import Navbar from "@/components/Navbar";
 import Footer from "@/components/Footer";
 import { Link, useSearchParams } from "react-router-dom";
 import { toast } from "sonner";
+import { fetchGameVerification, GameVerification } from "@/services/api";
+import { loadGameEvaluation } from "@/lib/wasm";
 
-// SHA256 implementation for browser
-async function sha256(message: string): Promise<ArrayBuffer> {
-  const msgBuffer = new TextEncoder().encode(message);
-  const hashBuffer = await crypto.subtle.digest("SHA-256", msgBuffer);
-  return hashBuffer;
-}
+// Stored discriminant of Satoshi's Number, the only game whose result is the roll itself
+const SATOSHIS_NUMBER = 0;
 
-// Convert ArrayBuffer to hex string
-function bufferToHex(buffer: ArrayBuffer): string {
-  return Array.from(new Uint8Array(buffer))
-    .map((b) => b.toString(16).padStart(2, "0"))
-    .join("");
+// Number of hash bytes the roll is read from: 2 under roll version 1, 4 under version 2
+function rollBytesOf(rollVersion: number): number {
+  return rollVersion >= 2 ? 4 : 2;
 }
 
 interface VerificationResult {
   isValid: boolean;
+  game: GameVerification | null;
+  hashInput: string;
+  hashInputValid: boolean;
+  nonceHashValid: boolean;
+  rollVersion: number;
+  rollBytes: number;
   rolledNumber: number;
+  result: string;
   targetNumber: number;
   isWin: boolean;
-  multiplier: number;
-  winChance: number;
+  matchesStored: boolean;
+  winChance: number | null;
   hashHex: string;
   error?: string;
 }
 
+function failure(error: string): VerificationResult {
+  return {
+    isValid: false,
+    game: null,
+    hashInput: "",
+    hashInputValid: false,
+    nonceHashValid: false,
+    rollVersion: 1,
+    rollBytes: 2,
+    rolledNumber: 0,
+    result: "",
+    targetNumber: 0,
+    isWin: false,
+    matchesStored: false,
+    winChance: null,
+    hashHex: "",
+    error,
+  };
+}
+
 export default function GameVerify() {
   const [searchParams, setSearchParams] = useSearchParams();
   const [txHash, setTxHash] = useState(searchParams.get("tx") || "");
-  const [nonce, setNonce] = useState(searchParams.get("nonce") || "");
-  const [multiplier, setMultiplier] = useState(
-    searchParams.get("multiplier") || "",
-  );
+  const [vout, setVout] = useState(searchParams.get("vout") || "");
   const [verificationResult, setVerificationResult] =
     useState<VerificationResult | null>(null);
   const [isVerifying, setIsVerifying] = useState(false);
@@ -50,18 +70,13 @@ export default function GameVerify() {
   useEffect(() => {
     const params = new URLSearchParams();
     if (txHash) params.set("tx", txHash);
-    if (nonce) params.set("nonce", nonce);
-    if (multiplier) params.set("multiplier", multiplier);
+    if (vout) params.set("vout", vout);
     setSearchParams(params);
-  }, [txHash, nonce, multiplier, setSearchParams]);
+  }, [txHash, vout, setSearchParams]);
 
-  // Auto-verify if all params are present on load
+  // Auto-verify if the transaction is given on load
   useEffect(() => {
-    if (
-      searchParams.get("tx") &&
-      searchParams.get("nonce") &&
-      searchParams.get("multiplier")
-    ) {
+    if (searchParams.get("tx")) {
       verifyGame();
     }
   }, []); // Only run on mount
@@ -73,80 +88,123 @@ export default function GameVerify() {
   };
 
   const verifyGame = async () => {
-    if (!txHash.trim() || !nonce.trim() || !multiplier.trim()) {
-      setVerificationResult({
-        isValid: false,
-        rolledNumber: 0,
-        targetNumber: 0,
-        isWin: false,
-        multiplier: 0,
-        winChance: 0,
-        hashHex: "",
-        error: "Please fill in all fields",
-      });
+    if (!txHash.trim()) {
+      setVerificationResult(failure("Please enter a transaction hash"));
       return;
     }
 
     setIsVerifying(true);
 
     try {
-      // Parse multiplier
-      const multiplierValue = parseFloat(multiplier);
-      if (isNaN(multiplierValue) || multiplierValue <= 0) {
-        throw new Error("Invalid multiplier value");
+      let output: number | undefined;
+      if (vout.trim()) {
+        output = Number(vout);
+        if (!Number.isInteger(output) || output < 0) {
+          throw new Error("Invalid output index");
+        }
       }
 
-      // Replicate the game logic from transaction_processor.rs
-      // hash_input = format!("{}{}", current_nonce, outpoint.outpoint.txid);
-      const hashInput = `${nonce}${txHash}`;
-
-      // SHA256 hash
-      const hashBuffer = await sha256(hashInput);
-      const hashBytes = new Uint8Array(hashBuffer);
-      const hashHex = bufferToHex(hashBuffer);
-
-      // Use first 2 bytes as u16 for randomness (0-65535 range)
-      // let random_value = u16::from_be_bytes([hash_bytes[0], hash_bytes[1]]);
-      const randomValue = (hashBytes[0] << 8) | hashBytes[1]; // Big-endian u16
-      const rolledNumber = randomValue;
+      const game = await fetchGameVerification(txHash.trim(), output);
+      switch (game.status) {
+        case "pending_reveal": {
+          const revealAt = game.expected_reveal_at
+            ? ` It is expected to be revealed at ${new Date(
+                game.expected_reveal_at * 1000,
+              ).toLocaleString()}.`
+            : "";
+          throw new Error(
+            `The nonce of this game is still in use, so it cannot be verified yet.${revealAt}`,
+          );
+        }
+        case "refunded":
+          throw new Error(
+            "No nonce was active when this bet was made, so it was refunded instead of played.",
+          );
+        case "donation":
+          throw new Error(
+            "This transaction exceeded the max bet and was recorded as a donation, nothing was rolled.",
+          );
+      }
+      if (!game.nonce || !game.steps) {
+        throw new Error("The server did not return the steps of this game");
+      }
 
-      // Calculate target number based on multiplier
-      // From the frontend: target_number: (65536.0 * 1000.0 / multiplier.multiplier() as f64) as i64
-      const targetNumber = Math.floor(
-        (65536.0 * 1000.0) / (multiplierValue * 100),
+      // Evaluate the bet with the same Rust code the server runs, compiled to
+      // WASM
+      const { evaluate, verifyNonceHash } = await loadGameEvaluation();
+
+      // The nonce must match the hash that was published before the game
+      const nonceHashValid = verifyNonceHash(game.nonce, game.nonce_hash);
+
+      const isSatoshisNumber = game.game_type_id === SATOSHIS_NUMBER;
+      const rollVersion = game.steps.roll_version;
+      const evaluation = evaluate(
+        game.game_type_id,
+        game.option_index ?? undefined,
+        game.nonce,
+        game.input_tx_id,
+        game.input_vout ?? undefined,
+        game.client_seed ?? undefined,
+        BigInt(game.multiplier_value),
+        game.house_edge_bps ?? undefined,
+        // Operators may offer multipliers beyond the built-in ones, whose
+        // threshold only the server knows
+        isSatoshisNumber ? game.steps.target : undefined,
+        undefined,
+        rollVersion,
       );
-
-      // Check if player wins: rolled_number < target_number
-      const isWin = rolledNumber < targetNumber;
-
-      // Calculate win chance percentage
-      const winChance = (targetNumber / 65536) * 100;
+      const hashInput = evaluation.hash_input;
+      const hashHex = evaluation.hash;
+      const rolledNumber = Number(evaluation.rolled_number);
+      const resultNumber = Number(evaluation.result_number);
+      const result = evaluation.outcome ?? String(resultNumber);
+      const targetNumber = Number(evaluation.target);
+      const isWin = evaluation.is_win;
+      evaluation.free();
+
+      // The hash input is built from the nonce, the transaction, the client
+      // seed in effect when the bet was made and the output of the bet, it must
+      // be the one the server hashed
+      const hashInputValid = hashInput === game.steps.hash_input;
+      const matchesStored =
+        !game.stored ||
+        (game.stored.rolled_number === resultNumber &&
+          game.stored.is_win === isWin);
+
+      // The roll is the first 2 (roll version 1) or 4 (roll version 2) bytes
+      // of the hash as a big-endian number
+      const rollBytes = rollBytesOf(rollVersion);
+      const winChance = isSatoshisNumber
+        ? (targetNumber / 2 ** (8 * rollBytes)) * 100
+        : null;
 
       setVerificationResult({
-        isValid: true,
+        isValid: nonceHashValid && hashInputValid && matchesStored,
+        game,
+        hashInput,
+        hashInputValid,
+        nonceHashValid,
+        rollVersion,
+        rollBytes,
         rolledNumber,
+        result,
         targetNumber,
         isWin,
-        multiplier: multiplierValue,
+        matchesStored,
         winChance,
         hashHex,
       });
     } catch (error) {
-      setVerificationResult({
-        isValid: false,
-        rolledNumber: 0,
-        targetNumber: 0,
-        isWin: false,
-        multiplier: 0,
-        winChance: 0,
-        hashHex: "",
-        error: error instanceof Error ? error.message : "Verification failed",
-      });
+      setVerificationResult(
+        failure(error instanceof Error ? error.message : "Verification failed"),
+      );
     } finally {
       setIsVerifying(false);
     }
   };
 
+  const rollRange = (rollBytes: number) => `0-${2 ** (8 * rollBytes) - 1}`;
+
   return (
     <div className="min-h-screen bg-background">
       <Navbar />
@@ -158,8 +216,9 @@ export default function GameVerify() {
             Game Verification
           </h1>
           <p className="text-muted-foreground max-w-2xl mx-auto">
-            Verify any Satoshi Dice game result by providing the transaction
-            hash and nonce. Our games are provably fair and fully transparent.
+            Verify any game result by providing the transaction hash. The result
+            is recomputed in your browser with the exact code our server runs.
+            Our games are provably fair and fully transparent.
           </p>
           <Link to="/game">
             <Button variant="ghost" size="sm">
@@ -190,38 +249,23 @@ export default function GameVerify() {
                     className="font-mono text-sm"
                   />
                   <p className="text-xs text-muted-foreground">
-                    The Bitcoin transaction ID from your game
-                  </p>
-                </div>
-
-                <div className="space-y-2">
-                  <Label htmlFor="nonce">Nonce</Label>
-                  <Input
-                    id="nonce"
-                    placeholder="Enter the nonce used for this game"
-                    value={nonce}
-                    onChange={(e) => setNonce(e.target.value)}
-                    className="font-mono text-sm"
-                  />
-                  <p className="text-xs text-muted-foreground">
-                    The random nonce that was active when your game was
-                    processed
+                    The Ark transaction ID of your bet
                   </p>
                 </div>
 
                 <div className="space-y-2">
-                  <Label htmlFor="multiplier">Multiplier</Label>
+                  <Label htmlFor="vout">Output (optional)</Label>
                   <Input
-                    id="multiplier"
-                    placeholder="Enter the multiplier (e.g., 2.0, 10.0)"
-                    value={multiplier}
-                    onChange={(e) => setMultiplier(e.target.value)}
+                    id="vout"
+                    placeholder="Enter the output index of your bet"
+                    value={vout}
+                    onChange={(e) => setVout(e.target.value)}
                     type="number"
-                    step="0.01"
-                    min="1"
+                    step="1"
+                    min="0"
                   />
                   <p className="text-xs text-muted-foreground">
-                    The payout multiplier for the game address you sent to
+                    Only needed if the transaction paid more than one bet
                   </p>
                 </div>
               </div>
@@ -251,12 +295,12 @@ export default function GameVerify() {
                   <div>
                     <h4 className="font-semibold mb-1">Combine Inputs</h4>
                     <p className="text-sm text-muted-foreground">
-                      We concatenate the nonce + transaction hash to create a
-                      unique string:
-                      {txHash && nonce && (
+                      We concatenate the revealed nonce, the transaction hash,
+                      your client seed if you registered one and ":" followed
+                      by the output of your bet:
+                      {verificationResult?.hashInput && (
                         <code className="block mt-1 p-2 bg-card/50 rounded text-xs break-all">
-                          {nonce}
-                          {txHash}
+                          {verificationResult.hashInput}
                         </code>
                       )}
                     </p>
@@ -286,9 +330,10 @@ export default function GameVerify() {
                       Extract Random Number
                     </h4>
                     <p className="text-sm text-muted-foreground">
-                      We take the first 2 bytes of the hash and convert them to
-                      a number between 0-65535. This gives us a fair,
-                      unpredictable random number.
+                      We take the first 4 bytes of the hash and convert them to
+                      a number between 0-4294967295. Games played before
+                      32-bit rolls (roll version 1) use the first 2 bytes, a
+                      number between 0-65535.
                     </p>
                   </div>
                 </div>
@@ -298,13 +343,15 @@ export default function GameVerify() {
                     4
                   </div>
                   <div>
-                    <h4 className="font-semibold mb-1">Calculate Target</h4>
+                    <h4 className="font-semibold mb-1">Look Up Target</h4>
                     <p className="text-sm text-muted-foreground">
-                      Based on your multiplier, we calculate the target number:
-                      {multiplier && (
+                      Each multiplier wins below its threshold, counted in
+                      rolls of the game's roll version:
+                      {verificationResult?.winChance != null && (
                         <code className="block mt-1 p-2 bg-card/50 rounded text-xs">
-                          Target = 65536 × 10 ÷ {multiplier} ={" "}
-                          {Math.floor((65536 * 10) / parseFloat(multiplier))}
+                          Target of {verificationResult.game.multiplier}x ={" "}
+                          {verificationResult.targetNumber} of{" "}
+                          {2 ** (8 * verificationResult.rollBytes)}
                         </code>
                       )}
                     </p>
@@ -344,10 +391,10 @@ export default function GameVerify() {
             <CardHeader>
               <div className="flex items-center justify-between">
                 <CardTitle className="flex items-center gap-2">
-                  {verificationResult.error ? (
-                    <AlertCircle className="w-5 h-5 text-destructive" />
-                  ) : (
+                  {verificationResult.isValid ? (
                     <CheckCircle2 className="w-5 h-5 text-green-500" />
+                  ) : (
+                    <AlertCircle className="w-5 h-5 text-destructive" />
                   )}
                   Verification Result
                 </CardTitle>
@@ -365,7 +412,7 @@ export default function GameVerify() {
               </div>
             </CardHeader>
             <CardContent className="space-y-4">
-              {verificationResult.error ? (
+              {verificationResult.error || !verificationResult.game ? (
                 <Alert variant="destructive">
                   <AlertCircle className="h-4 w-4" />
                   <AlertDescription>
@@ -374,6 +421,20 @@ export default function GameVerify() {
                 </Alert>
               ) : (
                 <div className="space-y-6">
+                  {!verificationResult.isValid && (
+                    <Alert variant="destructive">
+                      <AlertCircle className="h-4 w-4" />
+                      <AlertDescription>
+                        {!verificationResult.nonceHashValid &&
+                          "The nonce does not match the nonce hash published before the game. "}
+                        {!verificationResult.hashInputValid &&
+                          "The hash input is not made of the nonce, transaction, client seed and output of this game. "}
+                        {!verificationResult.matchesStored &&
+                          "The recomputed roll differs from the stored result."}
+                      </AlertDescription>
+                    </Alert>
+                  )}
+
                   {/* Result Summary */}
                   <div className="text-center">
                     <div
@@ -386,61 +447,86 @@ export default function GameVerify() {
                       {verificationResult.isWin ? "🎉 YOU WON!" : "😔 YOU LOST"}
                     </div>
                     <p className="text-muted-foreground">
-                      Rolled {verificationResult.rolledNumber}, needed{" "}
-                      {verificationResult.targetNumber} or lower
+                      {verificationResult.winChance != null
+                        ? `Rolled ${verificationResult.rolledNumber}, needed below ${verificationResult.targetNumber}`
+                        : `${verificationResult.game.game_type}: ${verificationResult.result}`}
                     </p>
                   </div>
 
                   {/* Game Details */}
                   <div className="grid grid-cols-2 md:grid-cols-4 gap-4">
                     <div className="text-center p-4 bg-card/50 rounded-lg">
-                      <div className="text-2xl font-bold text-primary">
-                        {verificationResult.rolledNumber}
+                      <div className="text-2xl font-bold text-primary break-all">
+                        {verificationResult.result}
                       </div>
                       <div className="text-sm text-muted-foreground">
-                        Rolled Number
+                        Result
                       </div>
                     </div>
 
                     <div className="text-center p-4 bg-card/50 rounded-lg">
-                      <div className="text-2xl font-bold text-primary">
+                      <div className="text-2xl font-bold text-primary break-all">
                         {verificationResult.targetNumber}
                       </div>
                       <div className="text-sm text-muted-foreground">
-                        Target (Max)
+                        Target
                       </div>
                     </div>
 
                     <div className="text-center p-4 bg-card/50 rounded-lg">
                       <div className="text-2xl font-bold text-primary">
-                        {verificationResult.multiplier}x
+                        {verificationResult.game.multiplier}x
                       </div>
                       <div className="text-sm text-muted-foreground">
                         Multiplier
                       </div>
                     </div>
 
-                    <div className="text-center p-4 bg-card/50 rounded-lg">
-                      <div className="text-2xl font-bold text-green-500">
-                        {verificationResult.winChance.toFixed(1)}%
+                    {verificationResult.winChance != null ? (
+                      <div className="text-center p-4 bg-card/50 rounded-lg">
+                        <div className="text-2xl font-bold text-green-500">
+                          {verificationResult.winChance.toFixed(1)}%
+                        </div>
+                        <div className="text-sm text-muted-foreground">
+                          Win Chance
+                        </div>
                       </div>
-                      <div className="text-sm text-muted-foreground">
-                        Win Chance
+                    ) : (
+                      <div className="text-center p-4 bg-card/50 rounded-lg">
+                        <div className="text-2xl font-bold text-primary">
+                          {verificationResult.game.option}
+                        </div>
+                        <div className="text-sm text-muted-foreground">
+                          Bet
+                        </div>
                       </div>
-                    </div>
+                    )}
                   </div>
 
                   {/* Technical Details */}
                   <div className="space-y-4">
                     <h3 className="font-semibold">Technical Details</h3>
                     <div className="space-y-2 font-mono text-sm">
+                      <div>
+                        <span className="text-muted-foreground">Nonce:</span>
+                        <div className="bg-card/50 p-2 rounded border break-all">
+                          {verificationResult.game.nonce}
+                        </div>
+                      </div>
+                      <div>
+                        <span className="text-muted-foreground">
+                          Nonce Hash:
+                        </span>
+                        <div className="bg-card/50 p-2 rounded border break-all">
+                          {verificationResult.game.nonce_hash}
+                        </div>
+                      </div>
                       <div>
                         <span className="text-muted-foreground">
                           Hash Input:
                         </span>
                         <div className="bg-card/50 p-2 rounded border break-all">
-                          {nonce}
-                          {txHash}
+                          {verificationResult.hashInput}
                         </div>
                       </div>
                       <div>
@@ -453,11 +539,16 @@ export default function GameVerify() {
                       </div>
                       <div>
                         <span className="text-muted-foreground">
-                          First 2 bytes:
+                          First {verificationResult.rollBytes} bytes (roll
+                          version {verificationResult.rollVersion},{" "}
+                          {rollRange(verificationResult.rollBytes)}):
                         </span>
                         <div className="bg-card/50 p-2 rounded border">
-                          {verificationResult.hashHex.substring(0, 4)} →{" "}
-                          {verificationResult.rolledNumber}
+                          {verificationResult.hashHex.substring(
+                            0,
+                            verificationResult.rollBytes * 2,
+                          )}{" "}
+                          → {verificationResult.rolledNumber}
                         </div>
                       </div>
                     </div>
@@ -477,28 +568,34 @@ export default function GameVerify() {
             <div className="space-y-2">
               <p>
                 <strong>1. Hash Generation:</strong> We combine the nonce +
-                transaction hash and create a SHA256 hash
+                transaction hash + client seed + ":" + output and create a
+                SHA256 hash. Games without a client seed or output leave them
+                out.
               </p>
               <p>
-                <strong>2. Random Number:</strong> We take the first 2 bytes of
-                the hash as a big-endian 16-bit number (0-65535)
+                <strong>2. Random Number:</strong> We take the first 4 bytes of
+                the hash as a big-endian 32-bit number (0-4294967295). Games
+                stored with roll version 1 take the first 2 bytes as a 16-bit
+                number (0-65535)
               </p>
               <p>
                 <strong>3. Win Condition:</strong> You win if the rolled number
-                is less than or equal to the target number
+                is less than the target number
               </p>
               <p>
-                <strong>4. Target Calculation:</strong> Target = (65536 × 1000)
-                ÷ (multiplier × 100)
+                <strong>4. Target Calculation:</strong> Target = rolls × RTP ÷
+                multiplier, with 4294967296 rolls under roll version 2 and 65536
+                under roll version 1
               </p>
             </div>
 
             <Alert>
               <AlertCircle className="h-4 w-4" />
               <AlertDescription>
-                This verification process is identical to what happens on our
-                server. The game outcome is determined by cryptographic hashing,
-                making it impossible to manipulate.
+                This verification runs the same Rust code as our server,
+                compiled to WebAssembly and executed in your browser. The game
+                outcome is determined by cryptographic hashing, making it
+                impossible to manipulate.
               </AlertDescription>
             </Alert>
           </CardContent>
@@ -100,9 +100,9 @@ export default function SatoshisNumber() {
 
   const { loading: isLoading, error, value: gameDataUnsorted } = gameDataState;
   const gameDataAddresses = gameDataUnsorted
-    ? gameDataUnsorted.game_addresses.sort(
-        (a, b) => a.multiplier_value - b.multiplier_value,
-      )
+    ? gameDataUnsorted.game_addresses
+        .filter((a) => a.game_type === 0)
+        .sort((a, b) => a.multiplier_value - b.multiplier_value)
     : [];
 
   const selectedAddress =
@@ -229,7 +229,7 @@ export default function SatoshisNumber() {
           </h1>
           <p className="text-muted-foreground max-w-2xl mx-auto">
             Send sats to the address below to play against Satoshi. Satoshi will
-            think of a number between 1 and 65535. Win if the number is lower
+            think of a number between 0 and 4294967295. Win if the number is lower
             than your selected threshold - higher risk means bigger rewards!
           </p>
           <div className="flex flex-col sm:flex-row gap-2 justify-center">
@@ -2,16 +2,27 @@ const API_BASE_URL = import.meta.env.VITE_API_URL || "http://localhost:12345";
 
 export interface GameHistoryItem {
   id: string;
+  game_type?: string;
   amount_sent: number;
   multiplier: number;
+  option?: string;
   result_number: number;
+  outcome?: string | null;
   target_number: number;
   is_win: boolean;
   payout?: number;
+  refund?: number | null;
+  label?: string | null;
+  detail?: Record<string, unknown> | null;
   input_tx_id: string;
+  input_vout?: number | null;
   output_tx_id: string | null;
+  output_vout?: number | null;
   nonce?: string;
   nonce_hash: string;
+  client_seed?: string | null;
+  roll_version?: number;
+  chain_position?: number | null;
   timestamp: number;
 }
 
@@ -37,3 +48,186 @@ export async function fetchGameHistory(
 
   return response.json();
 }
+
+export interface LotteryTicketItem {
+  round_id: number | null;
+  player: string;
+  input_tx_id: string;
+  input_vout: number;
+  amount: number;
+  first_ticket: number;
+  ticket_count: number;
+  timestamp: number;
+}
+
+export interface LotteryRoundItem {
+  id: number;
+  status: "open" | "closed" | "drawn";
+  address: string;
+  nonce_hash: string;
+  nonce: string | null;
+  ticket_price: number;
+  rake_bps: number;
+  starts_at: number;
+  ends_at: number;
+  drawn_at: number | null;
+  pot: number;
+  ticket_count: number;
+  purchase_count: number;
+  tickets_hash: string | null;
+  winning_ticket: number | null;
+  winner: LotteryTicketItem | null;
+  payout: number | null;
+  payout_tx_id: string | null;
+}
+
+export interface LotteryRoundResponse {
+  round: LotteryRoundItem;
+  tickets: LotteryTicketItem[];
+}
+
+export interface LotteryRoundsResponse {
+  rounds: LotteryRoundItem[];
+  total: number;
+  page: number;
+  page_size: number;
+  total_pages: number;
+}
+
+export async function fetchCurrentLotteryRound(): Promise<LotteryRoundResponse> {
+  const response = await fetch(`${API_BASE_URL}/lottery/current`);
+
+  if (!response.ok) {
+    throw new Error("Failed to fetch lottery round");
+  }
+
+  return response.json();
+}
+
+export async function fetchLotteryRounds(
+  page = 1,
+  pageSize = 20,
+): Promise<LotteryRoundsResponse> {
+  const response = await fetch(
+    `${API_BASE_URL}/lottery/rounds?page=${page}&page_size=${pageSize}`,
+  );
+
+  if (!response.ok) {
+    throw new Error("Failed to fetch lottery rounds");
+  }
+
+  return response.json();
+}
+
+export interface DuelItem {
+  id: number;
+  status: "open" | "matched" | "expired" | "settled" | "refunded";
+  stake: number;
+  rake_bps: number;
+  challenger: string;
+  challenger_tx_id: string;
+  challenger_vout: number;
+  expires_at: number;
+  opponent: string | null;
+  opponent_tx_id: string | null;
+  opponent_vout: number | null;
+  nonce_hash: string | null;
+  nonce: string | null;
+  winner: string | null;
+  payout: number | null;
+  payout_tx_id: string | null;
+  created_at: number;
+  matched_at: number | null;
+  settled_at: number | null;
+}
+
+export interface DuelsResponse {
+  address: string;
+  rake_bps: number;
+  timeout_minutes: number;
+  open: DuelItem[];
+  duels: DuelItem[];
+  total: number;
+  page: number;
+  page_size: number;
+  total_pages: number;
+}
+
+export async function fetchDuels(
+  page = 1,
+  pageSize = 20,
+): Promise<DuelsResponse> {
+  const response = await fetch(
+    `${API_BASE_URL}/duels?page=${page}&page_size=${pageSize}`,
+  );
+
+  if (!response.ok) {
+    throw new Error("Failed to fetch duels");
+  }
+
+  return response.json();
+}
+
+export type VerificationStatus =
+  | "verified"
+  | "computed"
+  | "mismatch"
+  | "pending_reveal"
+  | "donation"
+  | "refunded";
+
+export interface RollSteps {
+  hash_input: string;
+  hash: string;
+  roll_version: number;
+  roll_bytes: string;
+  rolled_number: number;
+  target: number;
+  is_win: boolean;
+  result_number: number;
+  outcome: string | null;
+}
+
+export interface GameVerification {
+  status: VerificationStatus;
+  game_type: string;
+  option: string;
+  input_tx_id: string;
+  input_vout: number | null;
+  multiplier: number;
+  game_type_id: number;
+  option_index: number | null;
+  multiplier_value: number;
+  house_edge_bps: number | null;
+  nonce: string | null;
+  nonce_hash: string;
+  client_seed: string | null;
+  chain_position: number | null;
+  nonce_hash_valid: boolean | null;
+  expected_reveal_at: number | null;
+  steps: RollSteps | null;
+  stored: { rolled_number: number; is_win: boolean } | null;
+}
+
+export async function fetchGameVerification(
+  txid: string,
+  vout?: number,
+): Promise<GameVerification> {
+  const path = vout === undefined ? txid : `${txid}/${vout}`;
+  const response = await fetch(`${API_BASE_URL}/verify/${path}`);
+
+  if (response.status === 404) {
+    throw new Error("No game was found for this transaction");
+  }
+  if (response.status === 409) {
+    const { outpoints } = (await response.json()) as { outpoints: string[] };
+    throw new Error(
+      `This transaction paid several bets, verify one of ${outpoints.join(", ")}`,
+    );
+  }
+  if (!response.ok) {
+    throw new Error("Failed to fetch game verification");
+  }
+
+  return response.json();
+}
@@ -1,4 +1,4 @@
-import { type GameData } from "@/types/game";
+import { type GameCatalog, type GameData } from "@/types/game";
 
 const API_BASE_URL =
   import.meta.env.VITE_API_BASE_URL || "http://localhost:12345";
@@ -16,4 +16,17 @@ export const gameService = {
       throw error;
     }
   },
+
+  async fetchCatalog(): Promise<GameCatalog> {
+    try {
+      const response = await fetch(`${API_BASE_URL}/catalog`);
+      if (!response.ok) {
+        throw new Error("Failed to fetch game catalog");
+      }
+      return await response.json();
+    } catch (error) {
+      console.error("Error fetching game catalog:", error);
+      throw error;
+    }
+  },
 };
@@ -1,4 +1,9 @@
-import { GameHistoryItem } from "./api";
+import {
+  DuelItem,
+  GameHistoryItem,
+  LotteryRoundItem,
+  LotteryTicketItem,
+} from "./api";
 
 export interface DonationItem {
   id: string;
@@ -18,12 +23,17 @@ export interface LegacyWebSocketMessage {
 export interface BackendWebSocketMessage {
   type: "game_result";
   id: string;
+  game_type?: string;
   amount_sent: number;
   multiplier: number;
   result_number: number;
+  outcome?: string | null;
   target_number: number;
   is_win: boolean;
   payout?: number;
+  refund?: number | null;
+  label?: string | null;
+  detail?: Record<string, unknown> | null;
   input_tx_id: string;
   output_tx_id: string | null;
   nonce?: string;
@@ -40,10 +50,59 @@ export interface DonationWebSocketMessage {
   timestamp: number;
 }
 
+export interface JackpotWebSocketMessage {
+  type: "jackpot";
+  balance: number;
+  win: {
+    amount: number;
+    winner: string;
+    input_tx_id: string;
+    input_vout: number;
+  } | null;
+  timestamp: number;
+}
+
+export interface CrashPointWebSocketMessage {
+  type: "crash_point";
+  input_tx_id: string;
+  input_vout: number;
+  crash_point: number;
+  cashout_target: number;
+  is_win: boolean;
+  timestamp: number;
+}
+
+export interface LotteryTicketWebSocketMessage extends LotteryTicketItem {
+  type: "lottery_ticket";
+}
+
+export interface LotteryDrawWebSocketMessage extends LotteryRoundItem {
+  type: "lottery_draw";
+}
+
+export interface DuelOpenedWebSocketMessage extends DuelItem {
+  type: "duel_opened";
+}
+
+export interface DuelMatchedWebSocketMessage extends DuelItem {
+  type: "duel_matched";
+}
+
+export interface DuelSettledWebSocketMessage extends DuelItem {
+  type: "duel_settled";
+}
+
 export type WebSocketMessage =
   | LegacyWebSocketMessage
   | BackendWebSocketMessage
-  | DonationWebSocketMessage;
+  | DonationWebSocketMessage
+  | JackpotWebSocketMessage
+  | CrashPointWebSocketMessage
+  | LotteryTicketWebSocketMessage
+  | LotteryDrawWebSocketMessage
+  | DuelOpenedWebSocketMessage
+  | DuelMatchedWebSocketMessage
+  | DuelSettledWebSocketMessage;
 
 export type WebSocketCallback = (
   data: GameHistoryItem | GameHistoryItem[] | DonationItem,
@@ -120,6 +179,9 @@ class GameWebSocketService {
               target_number: data.target_number,
               is_win: data.is_win,
               payout: data.payout,
+              refund: data.refund,
+              label: data.label,
+              detail: data.detail,
               input_tx_id: data.input_tx_id,
               output_tx_id: data.output_tx_id,
               nonce: data.nonce,
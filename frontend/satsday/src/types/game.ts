@@ -1,10 +1,15 @@
 export interface GameAddress {
+  game_type: number;
+  option: string;
   address: string;
   max_roll: number;
   multiplier: string;
   multiplier_value: number;
   win_probability: number;
+  rtp: number;
+  house_edge: number;
   max_bet_amount: number;
+  winning_outcomes: number[] | null;
 }
 
 export interface GameInfo {
@@ -14,5 +19,28 @@ export interface GameInfo {
 
 export interface GameData {
   game_addresses: GameAddress[];
+  nonce_hash: string;
   info: GameInfo;
 }
+
+export interface GameLimits {
+  max_payout_sats: number;
+  max_bet_amount: number | null;
+}
+
+export interface CatalogGame {
+  game_type: number;
+  id: string;
+  name: string;
+  description: string;
+  parameters: Record<string, number>;
+  distribution: string | null;
+  address_per_bet: boolean;
+  addresses: GameAddress[];
+  limits: GameLimits;
+}
+
+export interface GameCatalog {
+  games: CatalogGame[];
+  nonce_hash: string;
+}
@@ -0,0 +1,3 @@
+ALTER TABLE game_results DROP COLUMN client_seed;
+
+DROP TABLE IF EXISTS client_seeds;
@@ -0,0 +1,10 @@
+CREATE TABLE client_seeds
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    address     TEXT                              NOT NULL UNIQUE,
+    client_seed TEXT                              NOT NULL,
+    created_at  DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at  DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE game_results ADD COLUMN client_seed TEXT;
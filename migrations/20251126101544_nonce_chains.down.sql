@@ -0,0 +1,5 @@
+ALTER TABLE game_results DROP COLUMN chain_position;
+
+ALTER TABLE nonces DROP COLUMN chain_position;
+
+DROP TABLE IF EXISTS nonce_chains;
@@ -0,0 +1,13 @@
+CREATE TABLE nonce_chains
+(
+    id            INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    seed          TEXT                              NOT NULL UNIQUE,
+    terminal_hash TEXT                              NOT NULL UNIQUE,
+    length        INTEGER                           NOT NULL,
+    position      INTEGER                           NOT NULL DEFAULT 0,
+    created_at    DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE nonces ADD COLUMN chain_position INTEGER;
+
+ALTER TABLE game_results ADD COLUMN chain_position INTEGER;
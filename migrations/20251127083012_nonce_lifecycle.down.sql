@@ -0,0 +1,3 @@
+ALTER TABLE nonces DROP COLUMN revealed_at;
+ALTER TABLE nonces DROP COLUMN retired_at;
+ALTER TABLE nonces DROP COLUMN activated_at;
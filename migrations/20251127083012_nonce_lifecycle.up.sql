@@ -0,0 +1,16 @@
+ALTER TABLE nonces ADD COLUMN activated_at DATETIME;
+ALTER TABLE nonces ADD COLUMN retired_at DATETIME;
+ALTER TABLE nonces ADD COLUMN revealed_at DATETIME;
+
+-- Nonces from before this migration were replaced on every rotation and restart, so each one was
+-- retired when the next one was created. The newest one was active when the server stopped, it
+-- stays active until the first rotation after the upgrade and is revealed like any other.
+UPDATE nonces
+SET activated_at = created_at,
+    retired_at   = (
+        SELECT next.created_at
+        FROM nonces AS next
+        WHERE next.id > nonces.id
+        ORDER BY next.id
+        LIMIT 1
+    );
@@ -0,0 +1 @@
+CREATE INDEX idx_game_results_nonce ON game_results (nonce);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_game_results_outpoint;
+
+ALTER TABLE game_results DROP COLUMN input_vout;
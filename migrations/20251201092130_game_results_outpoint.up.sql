@@ -0,0 +1,4 @@
+-- Every output of a transaction is its own bet. Games stored before this migration have no vout.
+ALTER TABLE game_results ADD COLUMN input_vout INTEGER;
+
+CREATE UNIQUE INDEX idx_game_results_outpoint ON game_results (input_tx_id, input_vout);
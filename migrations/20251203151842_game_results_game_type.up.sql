@@ -0,0 +1,2 @@
+-- Discriminant of the game that was played, every game stored before this migration was Satoshi's Number
+ALTER TABLE game_results ADD COLUMN game_type INTEGER NOT NULL DEFAULT 0;
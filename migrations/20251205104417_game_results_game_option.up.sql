@@ -0,0 +1,3 @@
+-- Index of the option the player bet on within its game, see `GameOption::index`. Games stored
+-- before this migration are decoded from their multiplier.
+ALTER TABLE game_results ADD COLUMN game_option INTEGER;
@@ -0,0 +1,17 @@
+-- One-time addresses for over/under bets on a target picked by the player. Rows are kept after
+-- they expire so that a derivation index is never handed out twice.
+CREATE TABLE bet_intents
+(
+    id                INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    derivation_index  INTEGER                           NOT NULL UNIQUE,
+    address           TEXT                              NOT NULL UNIQUE,
+    side              INTEGER                           NOT NULL,
+    target            INTEGER                           NOT NULL,
+    payout_multiplier INTEGER                           NOT NULL,
+    created_at        DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at        DATETIME                          NOT NULL,
+    paid_at           DATETIME,
+    expired_at        DATETIME
+);
+
+CREATE INDEX idx_bet_intents_open ON bet_intents (expired_at, paid_at);
@@ -0,0 +1,3 @@
+ALTER TABLE game_results DROP COLUMN outcome_detail;
+ALTER TABLE game_results DROP COLUMN outcome_label;
+ALTER TABLE game_results DROP COLUMN refund_amount;
@@ -0,0 +1,7 @@
+-- Part of the bet returned without a win. `winning_amount` holds everything paid to the player,
+-- including the refund.
+ALTER TABLE game_results ADD COLUMN refund_amount INTEGER;
+-- Short name of the outcome, e.g. "win" or "loss". Missing for games stored before this migration.
+ALTER TABLE game_results ADD COLUMN outcome_label TEXT;
+-- Game specific details of the outcome as a JSON object
+ALTER TABLE game_results ADD COLUMN outcome_detail TEXT;
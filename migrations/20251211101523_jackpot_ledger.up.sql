@@ -0,0 +1,18 @@
+-- Every change to the progressive jackpot, the pot is the sum of all amounts. Losing bets add
+-- their contribution, a win takes out the whole pot as a negative amount.
+CREATE TABLE jackpot_ledger
+(
+    id             INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- "contribution" or "win"
+    entry_type     TEXT                              NOT NULL,
+    amount         INTEGER                           NOT NULL,
+    input_tx_id    TEXT                              NOT NULL,
+    input_vout     INTEGER                           NOT NULL,
+    -- Who the pot is paid to, only set for wins
+    player_address TEXT,
+    -- Transaction that paid a win, missing until the payout went through
+    payout_tx_id   TEXT,
+    created_at     DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_jackpot_ledger_bet ON jackpot_ledger (input_tx_id, input_vout, entry_type);
@@ -0,0 +1,2 @@
+DROP TABLE lottery_tickets;
+DROP TABLE lottery_rounds;
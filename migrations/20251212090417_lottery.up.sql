@@ -0,0 +1,48 @@
+-- Rounds of the scheduled lottery. The nonce is committed through its hash when the round opens
+-- and only published once the round is drawn.
+CREATE TABLE lottery_rounds
+(
+    id                 INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    nonce              TEXT                              NOT NULL,
+    nonce_hash         TEXT                              NOT NULL,
+    ticket_price       INTEGER                           NOT NULL,
+    rake_bps           INTEGER                           NOT NULL,
+    -- Tickets are bought with VTXOs created in [starts_at, ends_at)
+    starts_at          DATETIME                          NOT NULL,
+    ends_at            DATETIME                          NOT NULL,
+    -- Set when the round stops taking tickets, just before it is drawn
+    closed_at          DATETIME,
+    -- Everything below is set when the round is drawn
+    drawn_at           DATETIME,
+    tickets_hash       TEXT,
+    winning_ticket     INTEGER,
+    winner_ticket_id   INTEGER,
+    pot_amount         INTEGER,
+    rake_amount        INTEGER,
+    payout_amount      INTEGER,
+    -- Transaction that paid the winner, missing until the payout went through
+    payout_tx_id       TEXT,
+    created_at         DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_lottery_rounds_window ON lottery_rounds (starts_at, ends_at);
+
+-- Payments to the lottery address. A payment below the ticket price adds to the pot without a
+-- ticket, one that comes in after its round was closed has no round and is refunded.
+CREATE TABLE lottery_tickets
+(
+    id             INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    round_id       INTEGER,
+    input_tx_id    TEXT                              NOT NULL,
+    input_vout     INTEGER                           NOT NULL,
+    player_address TEXT                              NOT NULL,
+    amount         INTEGER                           NOT NULL,
+    -- Number of the first ticket of the purchase within its round, counted from 0
+    first_ticket   INTEGER                           NOT NULL,
+    ticket_count   INTEGER                           NOT NULL,
+    refund_tx_id   TEXT,
+    created_at     DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_lottery_tickets_outpoint ON lottery_tickets (input_tx_id, input_vout);
+CREATE INDEX idx_lottery_tickets_round ON lottery_tickets (round_id);
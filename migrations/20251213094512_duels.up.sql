@@ -0,0 +1,36 @@
+-- Player versus player duels. A stake sent to the duel address opens a challenge, the next stake
+-- of the same amount is matched against it and the winner takes both stakes less the rake.
+--
+-- A duel is "open" until it is "matched", or "expired" once nobody matched it in time. Paying the
+-- winner settles a matched duel, refunding the challenger settles an expired one as "refunded".
+CREATE TABLE duels
+(
+    id                 INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    status             TEXT                              NOT NULL DEFAULT 'open',
+    stake_amount       INTEGER                           NOT NULL,
+    rake_bps           INTEGER                           NOT NULL,
+    challenger_address TEXT                              NOT NULL,
+    challenger_tx_id   TEXT                              NOT NULL,
+    challenger_vout    INTEGER                           NOT NULL,
+    -- Only stakes created before then are matched against the challenge
+    expires_at         DATETIME                          NOT NULL,
+    -- Everything below is set when the duel is matched
+    opponent_address   TEXT,
+    opponent_tx_id     TEXT,
+    opponent_vout      INTEGER,
+    -- The nonce that was active when the opponent's stake was created
+    nonce              TEXT,
+    nonce_hash         TEXT,
+    winner_address     TEXT,
+    rake_amount        INTEGER,
+    payout_amount      INTEGER,
+    matched_at         DATETIME,
+    -- Transaction that paid the winner or refunded the challenger
+    payout_tx_id       TEXT,
+    settled_at         DATETIME,
+    created_at         DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_duels_challenger ON duels (challenger_tx_id, challenger_vout);
+CREATE UNIQUE INDEX idx_duels_opponent ON duels (opponent_tx_id, opponent_vout);
+CREATE INDEX idx_duels_status ON duels (status, stake_amount);
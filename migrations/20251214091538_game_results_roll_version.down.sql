@@ -0,0 +1 @@
+ALTER TABLE game_results DROP COLUMN roll_version;
@@ -0,0 +1,3 @@
+-- How the roll of Satoshi's Number was read from the hash, see `RollVersion`. Every game stored
+-- before this migration rolled 16 bits.
+ALTER TABLE game_results ADD COLUMN roll_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,25 @@
+-- Outbox of every payment we send. A payout is "planned" before anything is signed, "signed" once
+-- the Ark txid is known and "submitted" right before the Ark server sees it, so a crash anywhere in
+-- between is reconciled at startup instead of paying twice. A payout ends "finalized", or "failed"
+-- if it never reached the Ark server, in which case its source may be paid again.
+CREATE TABLE payouts
+(
+    id               INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- What is paid, e.g. "game_result" and the id of the game result
+    source           TEXT                              NOT NULL,
+    source_id        INTEGER                           NOT NULL,
+    transaction_type TEXT                              NOT NULL,
+    address          TEXT                              NOT NULL,
+    amount           INTEGER                           NOT NULL,
+    status           TEXT                              NOT NULL DEFAULT 'planned',
+    ark_txid         TEXT,
+    -- The VTXOs spent by the Ark transaction, comma separated `txid:vout`
+    inputs           TEXT,
+    error            TEXT,
+    created_at       DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at       DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- A source has at most one payout that is not failed
+CREATE UNIQUE INDEX idx_payouts_source ON payouts (source, source_id) WHERE status != 'failed';
+CREATE INDEX idx_payouts_status ON payouts (status);
@@ -0,0 +1,2 @@
+ALTER TABLE game_results DROP COLUMN output_vout;
+ALTER TABLE payouts DROP COLUMN vout;
@@ -0,0 +1,3 @@
+-- Several payouts can share one Ark transaction, each is paid by its own output of it
+ALTER TABLE payouts ADD COLUMN vout INTEGER;
+ALTER TABLE game_results ADD COLUMN output_vout INTEGER;
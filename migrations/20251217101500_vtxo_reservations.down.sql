@@ -0,0 +1 @@
+DROP TABLE vtxo_reservations;
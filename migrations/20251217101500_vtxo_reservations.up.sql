@@ -0,0 +1,11 @@
+-- VTXOs spent by a payout transaction that is not finalized yet. Every process sending payouts
+-- skips them in coin selection, and an outpoint can only be reserved by one Ark transaction.
+CREATE TABLE vtxo_reservations
+(
+    -- `txid:vout` of the reserved VTXO
+    outpoint   TEXT PRIMARY KEY NOT NULL,
+    ark_txid   TEXT             NOT NULL,
+    created_at DATETIME         NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_vtxo_reservations_ark_txid ON vtxo_reservations (ark_txid);
@@ -0,0 +1,15 @@
+-- Every seed an address registered, so that a bet is evaluated with the seed that was in effect
+-- when its VTXO was created rather than whatever the address registered afterwards
+CREATE TABLE client_seed_history
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    address     TEXT                              NOT NULL,
+    client_seed TEXT                              NOT NULL,
+    created_at  DATETIME                          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_client_seed_history_address ON client_seed_history (address, created_at);
+
+INSERT INTO client_seed_history (address, client_seed, created_at)
+SELECT address, client_seed, updated_at
+FROM client_seeds;
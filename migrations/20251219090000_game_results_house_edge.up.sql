@@ -0,0 +1,3 @@
+-- House edge in basis points the game was played with, for games that have one. Games stored
+-- before this migration are filled in with the configured edge when the server next starts.
+ALTER TABLE game_results ADD COLUMN house_edge_bps INTEGER;
@@ -0,0 +1,329 @@
+use crate::db;
+use crate::db::GameResult;
+use crate::db::Nonce;
+use crate::games::registry::GameRegistry;
+use crate::games::satoshis_number::RollVersion;
+use crate::games::GameInput;
+use crate::games::GameOption;
+use crate::games::GameType;
+use crate::nonce_service::hash_nonce;
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::Pool;
+use sqlx::Sqlite;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// The check a stored game failed
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditCheck {
+    /// The stored game type does not decode to a known [`GameType`]
+    UnknownGameType,
+    /// The stored option, or the multiplier of games stored before options were recorded, does
+    /// not decode to a [`GameOption`] of the game
+    UnknownOption,
+    /// The stored roll version is not a [`RollVersion`] this build knows
+    UnknownRollVersion,
+    /// The nonce the game was played with was never committed in the `nonces` table
+    MissingNonce,
+    /// `sha256(nonce)` differs from the committed nonce hash
+    NonceHashMismatch,
+    RolledNumber,
+    IsWinner,
+    WinningAmount,
+    RefundAmount,
+}
+
+impl fmt::Display for AuditCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditCheck::UnknownGameType => write!(f, "unknown game type"),
+            AuditCheck::UnknownOption => write!(f, "unknown option"),
+            AuditCheck::UnknownRollVersion => write!(f, "unknown roll version"),
+            AuditCheck::MissingNonce => write!(f, "missing nonce"),
+            AuditCheck::NonceHashMismatch => write!(f, "nonce hash mismatch"),
+            AuditCheck::RolledNumber => write!(f, "rolled number"),
+            AuditCheck::IsWinner => write!(f, "is winner"),
+            AuditCheck::WinningAmount => write!(f, "winning amount"),
+            AuditCheck::RefundAmount => write!(f, "refund amount"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub game_id: i64,
+    pub input_tx_id: String,
+    pub check: AuditCheck,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct AuditReport {
+    pub games_checked: usize,
+    /// Donations and refunds, which have no roll to check
+    pub games_skipped: usize,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl AuditReport {
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Re-derive every stored game and compare it against what was recorded
+pub async fn audit_game_results(pool: &Pool<Sqlite>, games: &GameRegistry) -> Result<AuditReport> {
+    let stored_games = db::get_all_game_results(pool).await?;
+
+    let mut report = AuditReport::default();
+    for game in stored_games {
+        // Donations and refunds were never rolled
+        if game.rolled_number < 0 {
+            report.games_skipped += 1;
+            continue;
+        }
+
+        let committed_nonce = db::get_nonce(pool, &game.nonce).await?;
+        report
+            .discrepancies
+            .extend(check_game(&game, committed_nonce.as_ref(), games));
+        report.games_checked += 1;
+    }
+
+    Ok(report)
+}
+
+/// All discrepancies between a stored game and its recomputed outcome
+pub fn check_game(
+    game: &GameResult,
+    committed_nonce: Option<&Nonce>,
+    games: &GameRegistry,
+) -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+    let mut report = |check, expected: String, actual: String| {
+        discrepancies.push(Discrepancy {
+            game_id: game.id,
+            input_tx_id: game.input_tx_id.clone(),
+            check,
+            expected,
+            actual,
+        })
+    };
+
+    let computed_hash = hash_nonce(&game.nonce);
+    match committed_nonce {
+        None => report(
+            AuditCheck::MissingNonce,
+            computed_hash,
+            "no commitment".to_string(),
+        ),
+        Some(nonce) if nonce.nonce_hash != computed_hash => report(
+            AuditCheck::NonceHashMismatch,
+            computed_hash,
+            nonce.nonce_hash.clone(),
+        ),
+        Some(_) => {}
+    }
+
+    let Some(game_type) = GameType::from_discriminant(game.game_type) else {
+        report(
+            AuditCheck::UnknownGameType,
+            "a known game type".to_string(),
+            game.game_type.to_string(),
+        );
+        return discrepancies;
+    };
+
+    let Some(option) = game_type.stored_option(
+        game.game_option,
+        game.multiplier,
+        &games.settings().multipliers,
+    ) else {
+        report(
+            AuditCheck::UnknownOption,
+            format!("an option of {game_type}"),
+            format!("{:?} (multiplier {})", game.game_option, game.multiplier),
+        );
+        return discrepancies;
+    };
+
+    let Some(roll_version) = RollVersion::from_stored(game.roll_version) else {
+        report(
+            AuditCheck::UnknownRollVersion,
+            "a known roll version".to_string(),
+            game.roll_version.to_string(),
+        );
+        return discrepancies;
+    };
+
+    let input = GameInput {
+        nonce: &game.nonce,
+        txid: &game.input_tx_id,
+        vout: game.input_vout.map(|vout| vout as u32),
+        client_seed: game.client_seed.as_deref(),
+        roll_version,
+    };
+    let game_bet_amount = u64::try_from(game.bet_amount).unwrap_or_default();
+    let house_edge_bps = game.house_edge_bps.and_then(|bps| u16::try_from(bps).ok());
+    let evaluation =
+        games
+            .played_with(game_type, house_edge_bps)
+            .evaluate(&input, &option, game_bet_amount);
+
+    if evaluation.rolled_value != game.rolled_number {
+        report(
+            AuditCheck::RolledNumber,
+            evaluation.rolled_value.to_string(),
+            game.rolled_number.to_string(),
+        );
+    }
+
+    if evaluation.is_win != game.is_winner {
+        report(
+            AuditCheck::IsWinner,
+            evaluation.is_win.to_string(),
+            game.is_winner.to_string(),
+        );
+    }
+
+    let winning_amount = Some(evaluation.returned_sats() as i64).filter(|amount| *amount > 0);
+    // Games stored before outcomes were labelled had their payout rounded through a float
+    let legacy_winning_amount = evaluation
+        .is_win
+        .then(|| (game.bet_amount as f64 * (game.multiplier as f64 / 100.0)) as u64 as i64);
+    let is_legacy_rounding =
+        game.outcome_label.is_none() && legacy_winning_amount == game.winning_amount;
+    if winning_amount != game.winning_amount && !is_legacy_rounding {
+        report(
+            AuditCheck::WinningAmount,
+            format!("{winning_amount:?}"),
+            format!("{:?}", game.winning_amount),
+        );
+    }
+
+    let refund_amount = evaluation.refund_sats.map(|refund| refund as i64);
+    if refund_amount != game.refund_amount {
+        report(
+            AuditCheck::RefundAmount,
+            format!("{refund_amount:?}"),
+            format!("{:?}", game.refund_amount),
+        );
+    }
+
+    discrepancies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_derivation::Multiplier;
+    use time::OffsetDateTime;
+
+    fn played_game() -> (GameResult, Nonce) {
+        let nonce = "12345".to_string();
+        let input_tx_id = "test_tx".to_string();
+        let multiplier = Multiplier::X200;
+        let evaluation = GameRegistry::default()
+            .get(GameType::SatoshisNumber)
+            .evaluate(
+                &GameInput {
+                    nonce: &nonce,
+                    txid: &input_tx_id,
+                    vout: Some(0),
+                    client_seed: None,
+                    roll_version: RollVersion::LATEST,
+                },
+                &GameOption::Multiplier(multiplier),
+                1000,
+            );
+
+        let game = GameResult {
+            id: 1,
+            nonce: nonce.clone(),
+            rolled_number: evaluation.rolled_value,
+            input_tx_id,
+            input_vout: Some(0),
+            output_tx_id: None,
+            output_vout: None,
+            bet_amount: 1000,
+            winning_amount: evaluation.is_win.then_some(2000),
+            player_address: "player".to_string(),
+            is_winner: evaluation.is_win,
+            payment_successful: true,
+            timestamp: OffsetDateTime::now_utc(),
+            multiplier: multiplier.multiplier() as i64,
+            client_seed: None,
+            chain_position: None,
+            game_type: GameType::SatoshisNumber as i64,
+            game_option: Some(multiplier.index() as i64),
+            refund_amount: None,
+            outcome_label: Some(evaluation.label.to_string()),
+            outcome_detail: Some(evaluation.detail),
+            roll_version: RollVersion::LATEST as i64,
+            house_edge_bps: None,
+        };
+        let committed_nonce = Nonce {
+            id: 1,
+            nonce_hash: hash_nonce(&nonce),
+            nonce,
+            created_at: OffsetDateTime::now_utc(),
+            expires_at: OffsetDateTime::now_utc(),
+            chain_position: None,
+            activated_at: None,
+            retired_at: None,
+            revealed_at: None,
+        };
+
+        (game, committed_nonce)
+    }
+
+    #[test]
+    fn test_consistent_game_passes() {
+        let (game, nonce) = played_game();
+
+        assert!(check_game(&game, Some(&nonce), &GameRegistry::default()).is_empty());
+    }
+
+    #[test]
+    fn test_tampered_game_is_reported() {
+        let (mut game, mut nonce) = played_game();
+        game.is_winner = !game.is_winner;
+        nonce.nonce_hash = hash_nonce("other");
+
+        let checks = check_game(&game, Some(&nonce), &GameRegistry::default())
+            .into_iter()
+            .map(|d| d.check)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            checks,
+            vec![AuditCheck::NonceHashMismatch, AuditCheck::IsWinner]
+        );
+        assert_eq!(
+            check_game(&game, None, &GameRegistry::default())[0].check,
+            AuditCheck::MissingNonce
+        );
+    }
+
+    #[test]
+    fn test_game_is_checked_with_its_roll_version() {
+        let (mut game, nonce) = played_game();
+
+        // Rolled with 32 bits, a 16-bit roll is a different number
+        game.roll_version = RollVersion::V1 as i64;
+        let checks = check_game(&game, Some(&nonce), &GameRegistry::default())
+            .into_iter()
+            .map(|d| d.check)
+            .collect::<Vec<_>>();
+        assert!(checks.contains(&AuditCheck::RolledNumber));
+
+        game.roll_version = 0;
+        assert_eq!(
+            check_game(&game, Some(&nonce), &GameRegistry::default())[0].check,
+            AuditCheck::UnknownRollVersion
+        );
+    }
+}
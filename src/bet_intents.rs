@@ -0,0 +1,210 @@
+use crate::db;
+use crate::games::over_under::BetTarget;
+use crate::games::over_under::OverUnderGame;
+use crate::games::over_under::Side;
+use crate::games::GameSettings;
+use crate::ArkClient;
+use anyhow::Context;
+use anyhow::Result;
+use ark_core::ArkAddress;
+use sqlx::Pool;
+use sqlx::Sqlite;
+use std::sync::Arc;
+use time::Duration as TimeDuration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tokio::sync::Mutex;
+use tokio::time::interval;
+use tokio::time::Duration;
+
+/// How often unpaid intents are checked for expiry
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A change to the scripts the transaction processor is subscribed to
+#[derive(Debug, Clone, Copy)]
+pub enum SubscriptionUpdate {
+    Subscribe(ArkAddress),
+}
+
+/// Hands out one-time addresses for over/under bets and expires them when they run out of time
+/// unpaid. Their addresses stay watched, so that bets that reach them later are refunded.
+#[derive(Clone)]
+pub struct BetIntentService {
+    db_pool: Pool<Sqlite>,
+    ark_client: Arc<ArkClient>,
+    game_settings: GameSettings,
+    ttl: TimeDuration,
+    subscription_updates: mpsc::UnboundedSender<SubscriptionUpdate>,
+    /// Held from picking a derivation index until it is stored, so no index is handed out twice
+    create_lock: Arc<Mutex<()>>,
+}
+
+impl BetIntentService {
+    pub fn new(
+        db_pool: Pool<Sqlite>,
+        ark_client: Arc<ArkClient>,
+        game_settings: GameSettings,
+        ttl_minutes: u64,
+        subscription_updates: mpsc::UnboundedSender<SubscriptionUpdate>,
+    ) -> Self {
+        Self {
+            db_pool,
+            ark_client,
+            game_settings,
+            ttl: TimeDuration::minutes(ttl_minutes as i64),
+            subscription_updates,
+            create_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// The payout a target is offered at, `None` if it is not accepted
+    pub fn offered_payout(&self, bet: &BetTarget) -> Option<u64> {
+        OverUnderGame::new(self.game_settings.over_under_house_edge_bps).offered_payout(bet)
+    }
+
+    /// Derive a fresh address for a bet on `bet` and start watching it
+    pub async fn create(&self, bet: BetTarget) -> Result<db::BetIntent> {
+        let payout_multiplier = self
+            .offered_payout(&bet)
+            .with_context(|| format!("Target {bet} is not accepted"))?;
+
+        let _create_lock = self.create_lock.lock().await;
+
+        let derivation_index = db::next_bet_intent_index(&self.db_pool).await?;
+        let address = self
+            .ark_client
+            .register_bet_intent(u32::try_from(derivation_index)?, bet)?;
+        let expires_at = OffsetDateTime::now_utc() + self.ttl;
+        db::insert_bet_intent(
+            &self.db_pool,
+            derivation_index,
+            &address.encode(),
+            bet.side as i64,
+            bet.target as i64,
+            payout_multiplier as i64,
+            expires_at,
+        )
+        .await
+        .context("Failed to store bet intent")?;
+
+        if self
+            .subscription_updates
+            .send(SubscriptionUpdate::Subscribe(address))
+            .is_err()
+        {
+            tracing::error!("Transaction monitor is not running, bet intent will not be watched");
+        }
+
+        tracing::info!(
+            derivation_index,
+            %bet,
+            payout_multiplier,
+            address = address.encode(),
+            "🎯 Created bet intent"
+        );
+
+        db::get_bet_intent_by_address(&self.db_pool, &address.encode())
+            .await?
+            .context("Bet intent missing after insert")
+    }
+
+    /// Expire intents that ran out of time without a bet, later bets to their addresses are
+    /// refunded
+    async fn expire_unpaid(&self) -> Result<()> {
+        let now = OffsetDateTime::now_utc();
+
+        let expired = db::get_unpaid_bet_intents_expired_before(&self.db_pool, now).await?;
+        for intent in expired {
+            // The bet may have come in since we looked
+            if !db::mark_bet_intent_expired(&self.db_pool, intent.id).await? {
+                continue;
+            }
+
+            tracing::debug!(
+                derivation_index = intent.derivation_index,
+                address = intent.address,
+                "⌛ Bet intent expired unpaid"
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn start_cleanup(&self) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut timer = interval(CLEANUP_INTERVAL);
+
+            loop {
+                timer.tick().await;
+
+                if let Err(e) = service.expire_unpaid().await {
+                    tracing::error!("Failed to expire bet intents: {:#}", e);
+                }
+            }
+        });
+    }
+}
+
+/// The bet of a stored intent
+pub fn bet_target(intent: &db::BetIntent) -> Option<BetTarget> {
+    Some(BetTarget {
+        side: Side::from_discriminant(u32::try_from(intent.side).ok()?)?,
+        target: u16::try_from(intent.target).ok()?,
+    })
+}
+
+/// Whether a bet whose VTXO was created at `created_at` came in after `intent` expired without an
+/// earlier bet. Such bets are refunded instead of played.
+pub fn is_late_bet(intent: &db::BetIntent, created_at: OffsetDateTime) -> bool {
+    created_at >= intent.expires_at
+        && intent
+            .paid_at
+            .is_none_or(|paid_at| paid_at >= intent.expires_at)
+}
+
+/// Record a bet to the intent of `address`, so that the address stays watched and spendable.
+/// Returns whether the bet is late, see [`is_late_bet`].
+pub async fn claim_bet_intent(
+    pool: &Pool<Sqlite>,
+    address: &str,
+    created_at: OffsetDateTime,
+) -> Result<bool> {
+    let intent = db::get_bet_intent_by_address(pool, address)
+        .await?
+        .with_context(|| format!("No bet intent for address {address}"))?;
+
+    db::mark_bet_intent_paid(pool, address).await?;
+
+    Ok(is_late_bet(&intent, created_at))
+}
+
+/// Register the address of every intent with the client, so that their bets are seen, late ones
+/// included, and their funds can be spent
+pub async fn restore_bet_intent_addresses(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+) -> Result<usize> {
+    let intents = db::get_bet_intents(pool).await?;
+
+    for intent in &intents {
+        let bet = bet_target(intent)
+            .with_context(|| format!("Invalid bet stored for intent {}", intent.id))?;
+        let address =
+            ark_client.register_bet_intent(u32::try_from(intent.derivation_index)?, bet)?;
+
+        if address.encode() != intent.address {
+            anyhow::bail!(
+                "Bet intent {} derives to {} instead of the stored {}",
+                intent.id,
+                address.encode(),
+                intent.address
+            );
+        }
+    }
+
+    tracing::info!(count = intents.len(), "♻️ Restored bet intent addresses");
+
+    Ok(intents.len())
+}
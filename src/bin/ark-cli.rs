@@ -1,7 +1,10 @@
 use anyhow::Result;
 use clap::Parser;
 use rand::thread_rng;
+use satoshi_dice::bet_intents;
 use satoshi_dice::db;
+use satoshi_dice::games::registry::GameRegistry;
+use satoshi_dice::games::GameType;
 use satoshi_dice::logger;
 use satoshi_dice::ArkClient;
 use satoshi_dice::Config;
@@ -59,25 +62,50 @@ enum Commands {
         )]
         dry_run: bool,
     },
+    /// Re-derive every stored game and report discrepancies
+    Verify {
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text, help = "Report format")]
+        format: ReportFormat,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Text,
+    Json,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    logger::init_tracing(LevelFilter::DEBUG, false)?;
+    let cli = Cli::parse();
+
+    // Keep stdout clean for machine readable reports
+    let log_level = match cli.command {
+        Commands::Verify {
+            format: ReportFormat::Json,
+        } => LevelFilter::OFF,
+        _ => LevelFilter::DEBUG,
+    };
+    logger::init_tracing(log_level, false)?;
 
     rustls::crypto::ring::default_provider()
         .install_default()
         .expect("to be able to install crypto providers");
 
-    let cli = Cli::parse();
-
     let config = Config::from_file(&cli.config)?;
 
     let db_url = config.database.clone();
     let pool = SqlitePoolOptions::new().connect(db_url.as_str()).await?;
     MIGRATOR.run(&pool).await?;
 
+    // Auditing only needs the database, there is no need to connect to the Ark server
+    if let Commands::Verify { format } = cli.command {
+        return verify(&pool, &config.game_registry(), format).await;
+    }
+
     let client = ArkClient::new(config.clone()).await?;
+    // Funds and bets on bet intent addresses have to be visible to every command
+    bet_intents::restore_bet_intent_addresses(&pool, &client).await?;
 
     match cli.command {
         Commands::Start { port } => {
@@ -86,11 +114,11 @@ async fn main() -> Result<()> {
             tracing::info!("📍 Offchain address: {}", client.get_address());
             tracing::info!("🚢 Boarding address: {}", client.get_boarding_address());
             tracing::info!("🚢 Max bet amount: {}", config.max_payout_sats);
-            for (game_type, multiplier, address) in game_addresses {
+            for (game_type, option, address) in game_addresses {
                 tracing::info!(
                     "👾Game Address {} {}: {}",
                     game_type.to_string(),
-                    multiplier,
+                    option,
                     address.encode()
                 );
             }
@@ -119,11 +147,11 @@ async fn main() -> Result<()> {
         }
         Commands::GameAddresses => {
             let game_addresses = client.get_game_addresses();
-            for (game_type, multiplier, address) in game_addresses {
+            for (game_type, option, address) in game_addresses {
                 tracing::info!(
                     "👾Game Address {} {}: {}",
                     game_type as u8,
-                    multiplier,
+                    option,
                     address.encode()
                 );
             }
@@ -164,7 +192,7 @@ async fn main() -> Result<()> {
             let vtxos = client.list_vtxos(game_addresses_list.as_slice()).await?;
             tracing::info!(number = vtxos.len(), "📡 Total VTXOs on Ark server");
             let mut all_received = bitcoin::Amount::ZERO;
-            for (game, multiplier, ark_address) in client.get_game_addresses() {
+            for (game, option, ark_address) in client.get_game_addresses() {
                 let per_address = vtxos
                     .iter()
                     .filter(|vtxo| vtxo.script == ark_address.to_p2tr_script_pubkey())
@@ -175,7 +203,7 @@ async fn main() -> Result<()> {
                     number_of_games = per_address.len(),
                     total_received = %total_received,
                     address = ark_address.encode(),
-                    "👾 Game Address {game}-{multiplier}",
+                    "👾 Game Address {game}-{option}",
                 );
             }
             tracing::info!(
@@ -221,13 +249,30 @@ async fn main() -> Result<()> {
             // Per-multiplier stats
             tracing::info!("📊 Win Rate by Multiplier:");
             let multiplier_stats = db::get_stats_by_multiplier(&pool).await?;
+            let settings = config.game_settings();
             for stat in multiplier_stats {
+                let known_game_type = GameType::from_discriminant(stat.game_type);
+                let game_type = known_game_type
+                    .map(|game_type| game_type.to_string())
+                    .unwrap_or_else(|| stat.game_type.to_string());
                 let multiplier_display = stat.multiplier as f64 / 100.0;
                 let win_rate = if stat.total_games > 0 {
                     (stat.total_winners as f64 / stat.total_games as f64) * 100.0
                 } else {
                     0.0
                 };
+                // Theoretical RTP of the current settings against what players actually got back
+                let theoretical_rtp = known_game_type
+                    .and_then(|game_type| {
+                        settings.theoretical_rtp(game_type, stat.multiplier as u64)
+                    })
+                    .map(|rtp| format!("{:.2}%", rtp * 100.0))
+                    .unwrap_or_else(|| "unknown".to_string());
+                let realized_rtp = if stat.total_bet_amount > 0 {
+                    (stat.total_payout_amount as f64 / stat.total_bet_amount as f64) * 100.0
+                } else {
+                    0.0
+                };
                 let house_profit = stat.total_bet_amount - stat.total_payout_amount;
                 let house_profit_display = if house_profit >= 0 {
                     format!("+{}", bitcoin::Amount::from_sat(house_profit as u64))
@@ -236,11 +281,14 @@ async fn main() -> Result<()> {
                 };
 
                 tracing::info!(
+                    game_type,
                     multiplier = format!("{:.2}x", multiplier_display),
                     games = stat.total_games,
                     winners = stat.total_winners,
                     losers = stat.total_losers,
                     win_rate = format!("{:.2}%", win_rate),
+                    theoretical_rtp,
+                    realized_rtp = format!("{:.2}%", realized_rtp),
                     total_bet = %bitcoin::Amount::from_sat(stat.total_bet_amount as u64),
                     total_payout = %bitcoin::Amount::from_sat(stat.total_payout_amount as u64),
                     house_profit = house_profit_display,
@@ -288,8 +336,14 @@ async fn main() -> Result<()> {
             }
 
             // Create nonce service
-            let nonce_service =
-                satoshi_dice::nonce_service::spawn_nonce_service(pool.clone(), 1, 1).await;
+            let nonce_service = satoshi_dice::nonce_service::spawn_nonce_service(
+                pool.clone(),
+                1,
+                1,
+                config.nonce_mode,
+                config.hash_chain_length,
+            )
+            .await?;
 
             // Run the missed games recovery
             let client_arc = std::sync::Arc::new(client);
@@ -298,6 +352,8 @@ async fn main() -> Result<()> {
                 &pool,
                 &nonce_service,
                 config.max_payout_sats,
+                &config.game_registry(),
+                config.jackpot_settings(),
                 dry_run,
             )
             .await
@@ -315,6 +371,47 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Commands::Verify { .. } => unreachable!("handled before connecting to the Ark server"),
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::print_stdout)]
+async fn verify(
+    pool: &sqlx::SqlitePool,
+    games: &GameRegistry,
+    format: ReportFormat,
+) -> Result<()> {
+    let report = satoshi_dice::audit::audit_game_results(pool, games).await?;
+
+    match format {
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        ReportFormat::Text => {
+            println!(
+                "Checked {} games ({} donations and refunds skipped), found {} discrepancies",
+                report.games_checked,
+                report.games_skipped,
+                report.discrepancies.len()
+            );
+            for discrepancy in &report.discrepancies {
+                println!(
+                    "game {} ({}): {} - expected {}, found {}",
+                    discrepancy.game_id,
+                    discrepancy.input_tx_id,
+                    discrepancy.check,
+                    discrepancy.expected,
+                    discrepancy.actual
+                );
+            }
+        }
+    }
+
+    if !report.is_clean() {
+        return Err(anyhow::anyhow!(
+            "{} discrepancies found",
+            report.discrepancies.len()
+        ));
     }
 
     Ok(())
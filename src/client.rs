@@ -1,11 +1,18 @@
 mod send_vtxo;
 mod settle;
 
+pub use send_vtxo::Funds;
+pub use send_vtxo::PreparedSend;
+pub use send_vtxo::SignedSend;
+
 use crate::config::Config;
 use crate::esplora::EsploraClient;
+use crate::games::over_under::BetTarget;
+use crate::games::GameOption;
 use crate::games::GameType;
 use crate::key_derivation::KeyDerivation;
 use crate::key_derivation::Multiplier;
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use ark_core::boarding_output::list_boarding_outpoints;
@@ -15,8 +22,11 @@ use ark_core::vtxo::list_virtual_tx_outpoints;
 use ark_core::ArkAddress;
 use ark_core::BoardingOutput;
 use ark_core::Vtxo;
+use bitcoin::hashes::sha256;
+use bitcoin::hashes::Hash;
 use bitcoin::key::Secp256k1;
 use bitcoin::key::TweakedPublicKey;
+use bitcoin::secp256k1::schnorr;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::secp256k1::SecretKey;
 use bitcoin::secp256k1::{self};
@@ -25,6 +35,7 @@ use bitcoin::OutPoint;
 use bitcoin::Txid;
 use bitcoin::XOnlyPublicKey;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::task::block_in_place;
@@ -34,11 +45,20 @@ pub struct ArkClient {
     esplora_client: EsploraClient,
     server_info: ark_core::server::Info,
     main_address: (Vtxo, SecretKey),
+    /// Address that buys lottery tickets, its funds are the pots of open rounds
+    lottery_address: (Vtxo, SecretKey),
+    /// Address that takes duel stakes, its funds are the stakes of open and unpaid duels
+    duel_address: (Vtxo, SecretKey),
     boarding_output: BoardingOutput,
     secp: Secp256k1<secp256k1::All>,
     game_addresses: Vec<GameArkAddress>,
+    /// One-time addresses of over/under bets, registered as intents are created or restored
+    bet_intent_addresses: std::sync::RwLock<Vec<GameArkAddress>>,
+    key_derivation: KeyDerivation,
     /// Cached spendable VTXOs, updated periodically
     cached_spendable_vtxos: Arc<RwLock<HashMap<Vtxo, Vec<ark_core::server::VirtualTxOutPoint>>>>,
+    /// VTXOs selected by an Ark transaction that is not finalized yet, coin selection skips them
+    reserved_vtxos: RwLock<HashSet<OutPoint>>,
 }
 
 #[derive(Debug)]
@@ -56,6 +76,8 @@ pub struct SubscriptionEvent {
     pub vout: u32,
     pub amount: Amount,
     pub script_pubkey: bitcoin::ScriptBuf,
+    /// Unix timestamp at which the Ark server created the VTXO
+    pub created_at: i64,
 }
 
 impl ArkClient {
@@ -74,6 +96,7 @@ impl ArkClient {
             .to_string();
 
         let key_derivation = KeyDerivation::from_seed(&master_seed, bitcoin::Network::Bitcoin)?;
+        let game_keys = key_derivation.get_all_game_keys(&config.game_settings().multipliers)?;
 
         // Derive main key
         let main_sk_bytes = key_derivation.get_main_secret_key()?;
@@ -104,11 +127,32 @@ impl ArkClient {
             server_info.network,
         )?;
 
+        let lottery_sk = key_derivation.get_lottery_key()?.private_key;
+        let lottery_vtxo = Vtxo::new_default(
+            &secp,
+            server_info.signer_pk.x_only_public_key().0,
+            PublicKey::from_secret_key(&secp, &lottery_sk)
+                .x_only_public_key()
+                .0,
+            server_info.unilateral_exit_delay,
+            server_info.network,
+        )?;
+
+        let duel_sk = key_derivation.get_duel_key()?.private_key;
+        let duel_vtxo = Vtxo::new_default(
+            &secp,
+            server_info.signer_pk.x_only_public_key().0,
+            PublicKey::from_secret_key(&secp, &duel_sk)
+                .x_only_public_key()
+                .0,
+            server_info.unilateral_exit_delay,
+            server_info.network,
+        )?;
+
         // Generate all game addresses using key derivation
         let mut game_addresses = Vec::new();
-        for multiplier in Multiplier::all() {
-            let game_sk_bytes = key_derivation.get_game_secret_key(multiplier)?;
-            let game_sk = SecretKey::from_slice(&game_sk_bytes)?;
+        for (game_type, option, game_key) in game_keys {
+            let game_sk = game_key.private_key;
             let game_pk = PublicKey::from_secret_key(&secp, &game_sk);
 
             let game_vtxo = Vtxo::new_default(
@@ -120,8 +164,8 @@ impl ArkClient {
             )?;
 
             game_addresses.push(GameArkAddress {
-                game_type: GameType::SatoshisNumber, // For now, all addresses are SatoshisNumber
-                multiplier,
+                game_type,
+                option,
                 vtxo: game_vtxo,
                 secret_key: game_sk,
             });
@@ -132,10 +176,15 @@ impl ArkClient {
             esplora_client,
             server_info,
             main_address: (main_vtxo, main_sk),
+            lottery_address: (lottery_vtxo, lottery_sk),
+            duel_address: (duel_vtxo, duel_sk),
             game_addresses,
+            bet_intent_addresses: std::sync::RwLock::new(Vec::new()),
+            key_derivation,
             boarding_output,
             secp,
             cached_spendable_vtxos: Arc::new(RwLock::new(HashMap::new())),
+            reserved_vtxos: RwLock::new(HashSet::new()),
         })
     }
 
@@ -143,10 +192,55 @@ impl ArkClient {
         self.main_address.0.to_ark_address()
     }
 
+    pub fn get_lottery_address(&self) -> ArkAddress {
+        self.lottery_address.0.to_ark_address()
+    }
+
+    pub fn get_duel_address(&self) -> ArkAddress {
+        self.duel_address.0.to_ark_address()
+    }
+
+    /// Fixed addresses that hold player funds outside the games, the lottery and duel addresses
+    pub(crate) fn pool_addresses(&self) -> [&(Vtxo, SecretKey); 2] {
+        [&self.lottery_address, &self.duel_address]
+    }
+
     pub fn get_boarding_address(&self) -> bitcoin::Address {
         self.boarding_output.address().clone()
     }
 
+    /// Check that `signature` is a BIP-340 signature of `sha256(message)` by the owner of
+    /// `address`.
+    ///
+    /// Only addresses with the default VTXO script can be proven this way, `owner` is the key
+    /// that script is built from.
+    pub fn verify_address_signature(
+        &self,
+        address: &ArkAddress,
+        owner: XOnlyPublicKey,
+        message: &[u8],
+        signature: &schnorr::Signature,
+    ) -> Result<()> {
+        let owner_vtxo = Vtxo::new_default(
+            &self.secp,
+            self.server_info.signer_pk.x_only_public_key().0,
+            owner,
+            self.server_info.unilateral_exit_delay,
+            self.server_info.network,
+        )?;
+        if owner_vtxo.to_ark_address().encode() != address.encode() {
+            bail!("Key {owner} does not own address {}", address.encode());
+        }
+
+        let digest = sha256::Hash::hash(message);
+        let message = secp256k1::Message::from_digest(digest.to_byte_array());
+        self.secp
+            .verify_schnorr(signature, &message, &owner)
+            .context("Invalid signature")?;
+
+        Ok(())
+    }
+
     pub async fn get_balance(&self, recoverable: bool) -> Result<Balance> {
         let runtime = tokio::runtime::Handle::current();
         let find_outpoints_fn =
@@ -192,7 +286,14 @@ impl ArkClient {
             .await?;
         spendable_vtxos.insert(main.0, main.1);
 
-        for game_address in &self.game_addresses {
+        for (pool_vtxo, _) in self.pool_addresses() {
+            let spendable = self
+                ._spendable_vtxos(pool_vtxo.clone(), select_recoverable_vtxos)
+                .await?;
+            spendable_vtxos.insert(spendable.0, spendable.1);
+        }
+
+        for game_address in self.all_game_addresses() {
             let spendable = self
                 ._spendable_vtxos(game_address.vtxo.clone(), select_recoverable_vtxos)
                 .await?;
@@ -226,7 +327,13 @@ impl ArkClient {
             ._spendable_vtxos(self.main_address.0.clone(), select_recoverable_vtxos)
             .await?;
         spendable_vtxos.insert(main.0, main.1);
-        for game_address in &self.game_addresses {
+        for (pool_vtxo, _) in self.pool_addresses() {
+            let spendable = self
+                ._spendable_vtxos(pool_vtxo.clone(), select_recoverable_vtxos)
+                .await?;
+            spendable_vtxos.insert(spendable.0, spendable.1);
+        }
+        for game_address in self.all_game_addresses() {
             let spendable = self
                 ._spendable_vtxos(game_address.vtxo.clone(), select_recoverable_vtxos)
                 .await?;
@@ -346,21 +453,82 @@ impl ArkClient {
         Ok(parent_addresses)
     }
 
-    pub fn get_game_addresses(&self) -> Vec<(GameType, Multiplier, ArkAddress)> {
+    /// The fixed address of every game option, bet intent addresses are not included
+    pub fn get_game_addresses(&self) -> Vec<(GameType, GameOption, ArkAddress)> {
         let vec = self.game_addresses.clone();
         vec.iter()
-            .map(|a| (a.game_type, a.multiplier, a.vtxo.to_ark_address()))
+            .map(|a| (a.game_type, a.option, a.vtxo.to_ark_address()))
             .collect()
     }
 
-    /// Legacy method for backward compatibility
+    /// Legacy method for backward compatibility, only covers the multiplier addresses
     pub fn get_game_addresses_legacy(&self) -> Vec<(Multiplier, ArkAddress)> {
         let vec = self.game_addresses.clone();
         vec.iter()
-            .map(|a| (a.multiplier, a.vtxo.to_ark_address()))
+            .filter_map(|a| match a.option {
+                GameOption::Multiplier(multiplier) => Some((multiplier, a.vtxo.to_ark_address())),
+                _ => None,
+            })
             .collect()
     }
 
+    /// Addresses of the bet intents that are currently watched
+    pub fn get_bet_intent_addresses(&self) -> Vec<(GameType, GameOption, ArkAddress)> {
+        self.bet_intent_addresses
+            .read()
+            .expect("bet intent addresses lock poisoned")
+            .iter()
+            .map(|a| (a.game_type, a.option, a.vtxo.to_ark_address()))
+            .collect()
+    }
+
+    /// Fixed game addresses followed by bet intent addresses, with their keys
+    pub(crate) fn all_game_addresses(&self) -> Vec<GameArkAddress> {
+        let mut addresses = self.game_addresses.clone();
+        addresses.extend(
+            self.bet_intent_addresses
+                .read()
+                .expect("bet intent addresses lock poisoned")
+                .iter()
+                .cloned(),
+        );
+        addresses
+    }
+
+    /// Derive the one-time address of a bet intent and start treating it as a game address
+    pub fn register_bet_intent(&self, derivation_index: u32, bet: BetTarget) -> Result<ArkAddress> {
+        let intent_key = self.key_derivation.get_bet_intent_key(derivation_index)?;
+        let intent_sk = intent_key.private_key;
+        let intent_pk = PublicKey::from_secret_key(&self.secp, &intent_sk);
+
+        let intent_vtxo = Vtxo::new_default(
+            &self.secp,
+            self.server_info.signer_pk.x_only_public_key().0,
+            intent_pk.x_only_public_key().0,
+            self.server_info.unilateral_exit_delay,
+            self.server_info.network,
+        )?;
+        let address = intent_vtxo.to_ark_address();
+
+        let mut intent_addresses = self
+            .bet_intent_addresses
+            .write()
+            .expect("bet intent addresses lock poisoned");
+        if !intent_addresses
+            .iter()
+            .any(|a| a.vtxo.to_ark_address().encode() == address.encode())
+        {
+            intent_addresses.push(GameArkAddress {
+                game_type: GameType::OverUnder,
+                option: GameOption::OverUnder(bet),
+                vtxo: intent_vtxo,
+                secret_key: intent_sk,
+            });
+        }
+
+        Ok(address)
+    }
+
     pub fn dust_value(&self) -> Amount {
         self.server_info.dust
     }
@@ -405,18 +573,26 @@ impl ArkClient {
         })
     }
 
-    /// Find the game type and multiplier for a given address
-    pub fn find_game_info(&self, address: &ArkAddress) -> Option<(GameType, Multiplier)> {
-        self.game_addresses
+    /// Find the game type and option for a given address
+    pub fn find_game_info(&self, address: &ArkAddress) -> Option<(GameType, GameOption)> {
+        self.all_game_addresses()
             .iter()
             .find(|game_addr| game_addr.vtxo.to_ark_address().encode() == address.encode())
-            .map(|game_addr| (game_addr.game_type, game_addr.multiplier))
+            .map(|game_addr| (game_addr.game_type, game_addr.option))
     }
 
-    /// Subscribe to script pubkeys for real-time notifications
-    pub async fn subscribe_to_scripts(&self, scripts: Vec<ArkAddress>) -> Result<String> {
+    /// Subscribe to script pubkeys for real-time notifications, adding them to `subscription_id`
+    /// if given
+    pub async fn subscribe_to_scripts(
+        &self,
+        scripts: Vec<ArkAddress>,
+        subscription_id: Option<String>,
+    ) -> Result<String> {
         let length = scripts.len();
-        let subscription_id = self.grpc_client.subscribe_to_scripts(scripts, None).await?;
+        let subscription_id = self
+            .grpc_client
+            .subscribe_to_scripts(scripts, subscription_id)
+            .await?;
         tracing::info!(
             subscription_id = subscription_id,
             scripts = length,
@@ -451,17 +627,24 @@ impl ArkClient {
 
         let mut subscription_stream = self.grpc_client.get_subscription(subscription_id).await?;
 
-        let game_addresses = self.get_game_addresses();
-
         let stream = async_stream::stream! {
             while let Some(result) = subscription_stream.next().await {
                 match result {
                     Ok(SubscriptionResponse::Event(response)) => {
 
                         let new_vtxos = response.new_vtxos;
+                        // Bet intents come and go while the subscription is open
+                        let mut game_addresses = self
+                            .all_game_addresses()
+                            .into_iter()
+                            .map(|a| a.vtxo.to_ark_address())
+                            .collect::<Vec<_>>();
+                        game_addresses.extend(
+                            self.pool_addresses().map(|(vtxo, _)| vtxo.to_ark_address()),
+                        );
 
                         for new_vtxo in new_vtxos {
-                            for (_, _, address) in &game_addresses {
+                            for address in &game_addresses {
                                 if new_vtxo.clone().script == address.to_sub_dust_script_pubkey() ||
                                 new_vtxo.clone().script == address.to_p2tr_script_pubkey(){
                                     yield Ok(SubscriptionEvent {
@@ -469,6 +652,7 @@ impl ArkClient {
                                         vout: new_vtxo.outpoint.vout,
                                         amount: new_vtxo.amount,
                                         script_pubkey: new_vtxo.script.clone(),
+                                        created_at: new_vtxo.created_at,
                                     });
                                 }
                             }
@@ -489,7 +673,7 @@ impl ArkClient {
 #[derive(Debug, Clone)]
 pub struct GameArkAddress {
     pub game_type: GameType,
-    pub multiplier: Multiplier,
+    pub option: GameOption,
     pub vtxo: Vtxo,
     pub secret_key: SecretKey,
 }
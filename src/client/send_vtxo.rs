@@ -8,12 +8,59 @@ use ark_core::send::sign_ark_transaction;
 use ark_core::send::sign_checkpoint_transaction;
 use ark_core::send::OffchainTransactions;
 use ark_core::ArkAddress;
-use bitcoin::psbt;
+use ark_core::Vtxo;
 use bitcoin::secp256k1;
 use bitcoin::secp256k1::schnorr;
+use bitcoin::secp256k1::SecretKey;
 use bitcoin::Amount;
+use bitcoin::OutPoint;
+use bitcoin::Psbt;
+use bitcoin::TxOut;
 use bitcoin::Txid;
 use bitcoin::XOnlyPublicKey;
+use std::collections::HashSet;
+
+/// The VTXOs a send may spend. The lottery and duel addresses hold player funds, so they only pay
+/// for their own settlements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Funds {
+    /// The main and game addresses
+    House,
+    /// The lottery address, whose funds are the pots of open rounds
+    Lottery,
+    /// The duel address, whose funds are the stakes of open and unpaid duels
+    Duel,
+}
+
+/// An Ark transaction whose VTXOs are selected and reserved but that is not signed yet
+pub struct PreparedSend {
+    /// The [`Txid`] the Ark transaction will have, signing does not change it
+    pub ark_txid: Txid,
+    /// The VTXOs the transaction spends
+    pub inputs: Vec<OutPoint>,
+    /// Outputs that pay someone else, the change comes after them
+    recipients: usize,
+    /// Address the change goes back to
+    change: Vtxo,
+    ark_tx: Psbt,
+    checkpoint_txs: Vec<Psbt>,
+    vtxo_inputs: Vec<send::VtxoInput>,
+}
+
+/// An Ark transaction that is built and signed but was not submitted to the Ark server yet
+pub struct SignedSend {
+    /// The [`Txid`] the Ark transaction will have
+    pub ark_txid: Txid,
+    /// The VTXOs the transaction spends
+    pub inputs: Vec<OutPoint>,
+    /// Outputs that pay someone else, the change comes after them
+    recipients: usize,
+    /// Address the change goes back to
+    change: Vtxo,
+    ark_tx: Psbt,
+    checkpoint_txs: Vec<Psbt>,
+    vtxo_inputs: Vec<send::VtxoInput>,
+}
 
 impl ArkClient {
     /// Spend confirmed and pre-confimed VTXOs in an Ark transaction sending the given `amount` to
@@ -26,31 +73,110 @@ impl ArkClient {
     ///
     /// The [`Txid`] of the generated Ark transaction.
     pub async fn send_vtxo(&self, address: ArkAddress, amount: Amount) -> Result<Txid> {
+        let signed = self.sign_send(address, amount).await?;
+        let inputs = signed.inputs.clone();
+
+        let result = self.submit_send(signed).await;
+        if result.is_err() {
+            self.release_vtxos(&inputs).await;
+        }
+        result
+    }
+
+    /// Build and sign the Ark transaction of [`ArkClient::send_vtxo`] without sending it, so that
+    /// its [`Txid`] can be recorded before the Ark server learns about it
+    pub async fn sign_send(&self, address: ArkAddress, amount: Amount) -> Result<SignedSend> {
+        self.sign_send_many(&[(address, amount)], &HashSet::new(), Funds::House)
+            .await
+    }
+
+    /// Like [`ArkClient::sign_send`], paying every `(address, amount)` in a single Ark
+    /// transaction, see [`ArkClient::prepare_send_many`]
+    pub async fn sign_send_many(
+        &self,
+        outputs: &[(ArkAddress, Amount)],
+        excluded: &HashSet<OutPoint>,
+        funds: Funds,
+    ) -> Result<SignedSend> {
+        let prepared = self.prepare_send_many(outputs, excluded, funds).await?;
+        let inputs = prepared.inputs.clone();
+
+        let result = self.sign_prepared_send(prepared);
+        if result.is_err() {
+            self.release_vtxos(&inputs).await;
+        }
+        result
+    }
+
+    /// Select the VTXOs of an Ark transaction paying every `(address, amount)` and build it
+    /// without signing it, so that its inputs can be reserved elsewhere first. Output `i` of the
+    /// transaction pays `outputs[i]`, the change comes after them.
+    ///
+    /// Coin selection only spends `funds`, skipping the VTXOs in `excluded` and those reserved by
+    /// an earlier send, and reserves the VTXOs it selects until they are released with
+    /// [`ArkClient::release_vtxos`] or the transaction is submitted. The change goes back to the
+    /// address of `funds`.
+    pub async fn prepare_send_many(
+        &self,
+        outputs: &[(ArkAddress, Amount)],
+        excluded: &HashSet<OutPoint>,
+        funds: Funds,
+    ) -> Result<PreparedSend> {
+        let amount = outputs.iter().map(|(_, amount)| *amount).sum::<Amount>();
+
+        // Held until the selected VTXOs are reserved, so that no other send can select them
+        let mut reserved_vtxos = self.reserved_vtxos.write().await;
+
         // Use cached spendable VTXOs instead of fetching
         let spendable_vtxos = self
             .get_cached_spendable_vtxos()
             .await
             .context("failed to get cached spendable VTXOs")?;
 
-        // Run coin selection algorithm on candidate spendable VTXOs.
-        let spendable_virtual_tx_outpoints = spendable_vtxos
-            .iter()
-            .flat_map(|(_, vtxos)| vtxos.clone())
-            .map(|vtxo| ark_core::coin_select::VirtualTxOutPoint {
-                outpoint: vtxo.outpoint,
-                expire_at: vtxo.expires_at,
-                amount: vtxo.amount,
-            })
-            .collect::<Vec<_>>();
+        // Run coin selection algorithm on the candidate spendable VTXOs of the addresses `spends`
+        // accepts.
+        let select = |spends: &dyn Fn(&Vtxo) -> bool| {
+            let spendable_virtual_tx_outpoints = spendable_vtxos
+                .iter()
+                .filter(|(vtxo, _)| spends(vtxo))
+                .flat_map(|(_, vtxos)| vtxos.clone())
+                .filter(|vtxo| {
+                    !reserved_vtxos.contains(&vtxo.outpoint) && !excluded.contains(&vtxo.outpoint)
+                })
+                .map(|vtxo| ark_core::coin_select::VirtualTxOutPoint {
+                    outpoint: vtxo.outpoint,
+                    expire_at: vtxo.expires_at,
+                    amount: vtxo.amount,
+                })
+                .collect::<Vec<_>>();
 
-        let selected_coins = select_vtxos(
-            spendable_virtual_tx_outpoints,
-            amount,
-            self.server_info.dust,
-            true,
-        )
+            select_vtxos(
+                spendable_virtual_tx_outpoints,
+                amount,
+                self.server_info.dust,
+                true,
+            )
+        };
+
+        let change = self.funds_address(funds).clone();
+        let is_pool = |vtxo: &Vtxo| {
+            self.pool_addresses()
+                .iter()
+                .any(|(pool_vtxo, _)| pool_vtxo == vtxo)
+        };
+        let selected_coins = match funds {
+            Funds::House => select(&|vtxo| !is_pool(vtxo)),
+            // Settling moves the pool funds to the main address, the house covers what is missing
+            Funds::Lottery | Funds::Duel => select(&|vtxo| *vtxo == change)
+                .or_else(|_| select(&|vtxo| *vtxo == change || !is_pool(vtxo))),
+        }
         .context("failed to select coins")?;
 
+        let inputs = selected_coins
+            .iter()
+            .map(|virtual_tx_outpoint| virtual_tx_outpoint.outpoint)
+            .collect();
+
         let vtxo_inputs = selected_coins
             .into_iter()
             .map(|virtual_tx_outpoint| {
@@ -81,51 +207,85 @@ impl ArkClient {
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let (main_address, _) = &self.main_address;
-        let change_address = main_address.to_ark_address();
+        let change_address = change.to_ark_address();
 
         let OffchainTransactions {
-            mut ark_tx,
+            ark_tx,
             checkpoint_txs,
         } = build_offchain_transactions(
-            &[(&address, amount)],
+            &outputs
+                .iter()
+                .map(|(address, amount)| (address, *amount))
+                .collect::<Vec<_>>(),
             Some(&change_address),
             &vtxo_inputs,
             &self.server_info,
         )
         .context("failed to build offchain transactions")?;
 
-        let mut all_keys = vec![self.main_address.clone()];
-        for game_address in &self.game_addresses {
-            all_keys.push((game_address.vtxo.clone(), game_address.secret_key));
-        }
+        let ark_txid = ark_tx.unsigned_tx.compute_txid();
+        reserved_vtxos.extend(inputs.iter().copied());
 
-        let sign_fn = |_psbt: &mut psbt::Input,
-                       msg: secp256k1::Message,
-                       index: usize|
-         -> Result<(schnorr::Signature, XOnlyPublicKey), ark_core::Error> {
-            let input = vtxo_inputs.get(index).expect("input");
-            let kp = all_keys.iter().find_map(|(v, sk)| {
-                if input.script_pubkey() == v.script_pubkey() {
-                    Some(sk.keypair(&self.secp))
-                } else {
-                    None
-                }
-            });
-            let kp = kp
-                .context("Key not found for vtxo")
-                .map_err(ark_core::Error::ad_hoc)?;
+        Ok(PreparedSend {
+            ark_txid,
+            inputs,
+            recipients: outputs.len(),
+            change,
+            ark_tx,
+            checkpoint_txs,
+            vtxo_inputs,
+        })
+    }
 
-            let sig = self.secp.sign_schnorr_no_aux_rand(&msg, &kp);
-            let pk = kp.x_only_public_key().0;
-            Ok((sig, pk))
-        };
+    /// Sign an Ark transaction built with [`ArkClient::prepare_send_many`]. Its inputs stay
+    /// reserved if signing fails.
+    pub fn sign_prepared_send(&self, prepared: PreparedSend) -> Result<SignedSend> {
+        let PreparedSend {
+            ark_txid,
+            inputs,
+            recipients,
+            change,
+            mut ark_tx,
+            checkpoint_txs,
+            vtxo_inputs,
+        } = prepared;
 
+        let all_keys = self.signing_keys();
         for i in 0..checkpoint_txs.len() {
-            sign_ark_transaction(|a, b| sign_fn(a, b, i), &mut ark_tx, i)?;
+            sign_ark_transaction(
+                |_, msg| self.sign_input(&all_keys, &vtxo_inputs, i, msg),
+                &mut ark_tx,
+                i,
+            )?;
         }
 
-        let ark_txid = ark_tx.unsigned_tx.compute_txid();
+        Ok(SignedSend {
+            ark_txid,
+            inputs,
+            recipients,
+            change,
+            ark_tx,
+            checkpoint_txs,
+            vtxo_inputs,
+        })
+    }
+
+    /// Submit an Ark transaction signed with [`ArkClient::sign_send`] and finalize it. Once it is
+    /// finalized, its inputs are released and replaced by its change in the VTXO cache.
+    ///
+    /// The inputs stay reserved if it fails, as they may have been spent anyway.
+    pub async fn submit_send(&self, signed: SignedSend) -> Result<Txid> {
+        let SignedSend {
+            ark_txid,
+            inputs,
+            recipients,
+            change,
+            ark_tx,
+            checkpoint_txs,
+            vtxo_inputs,
+        } = signed;
+        let all_keys = self.signing_keys();
+        let tx_outputs = ark_tx.unsigned_tx.output.clone();
 
         let mut res = self
             .grpc_client
@@ -134,7 +294,10 @@ impl ArkClient {
             .context("failed to submit offchain transaction request")?;
 
         for checkpoint_psbt in res.signed_checkpoint_txs.iter_mut() {
-            sign_checkpoint_transaction(|a, b| sign_fn(a, b, 0), checkpoint_psbt)?;
+            sign_checkpoint_transaction(
+                |_, msg| self.sign_input(&all_keys, &vtxo_inputs, 0, msg),
+                checkpoint_psbt,
+            )?;
         }
 
         self.grpc_client
@@ -142,6 +305,125 @@ impl ArkClient {
             .await
             .context("failed to finalize offchain transaction")?;
 
+        self.apply_send(ark_txid, &tx_outputs, recipients, &change, &inputs)
+            .await;
+
         Ok(ark_txid)
     }
+
+    /// Release VTXOs reserved by [`ArkClient::sign_send_many`], so that coin selection may pick
+    /// them again
+    pub async fn release_vtxos(&self, outpoints: &[OutPoint]) {
+        let mut reserved_vtxos = self.reserved_vtxos.write().await;
+        for outpoint in outpoints {
+            reserved_vtxos.remove(outpoint);
+        }
+    }
+
+    /// Drop the inputs of a finalized Ark transaction from the VTXO cache and add its change to
+    /// the cache of `change_vtxo`, so that the next send does not need a full sync
+    async fn apply_send(
+        &self,
+        ark_txid: Txid,
+        tx_outputs: &[TxOut],
+        recipients: usize,
+        change_vtxo: &Vtxo,
+        inputs: &[OutPoint],
+    ) {
+        let change_address = change_vtxo.to_ark_address();
+        let mut cache = self.cached_spendable_vtxos.write().await;
+
+        let mut spent = Vec::new();
+        for virtual_tx_outpoints in cache.values_mut() {
+            virtual_tx_outpoints.retain(|virtual_tx_outpoint| {
+                let is_input = inputs.contains(&virtual_tx_outpoint.outpoint);
+                if is_input {
+                    spent.push(virtual_tx_outpoint.clone());
+                }
+                !is_input
+            });
+        }
+
+        let change = tx_outputs
+            .iter()
+            .enumerate()
+            .skip(recipients)
+            .find(|(_, output)| {
+                output.script_pubkey == change_address.to_p2tr_script_pubkey()
+                    || output.script_pubkey == change_address.to_sub_dust_script_pubkey()
+            });
+        // The change expires with the earliest VTXO it was made of
+        let template = spent
+            .iter()
+            .min_by_key(|virtual_tx_outpoint| virtual_tx_outpoint.expires_at);
+        if let (Some((vout, output)), Some(template)) = (change, template) {
+            let change = ark_core::server::VirtualTxOutPoint {
+                outpoint: OutPoint::new(ark_txid, vout as u32),
+                amount: output.value,
+                script: output.script_pubkey.clone(),
+                ..template.clone()
+            };
+            cache.entry(change_vtxo.clone()).or_default().push(change);
+        }
+        drop(cache);
+
+        self.release_vtxos(inputs).await;
+    }
+
+    /// Whether the Ark server knows the Ark transaction `ark_txid`, i.e. it was submitted
+    pub async fn is_ark_transaction_known(&self, ark_txid: Txid) -> Result<bool> {
+        let response = self
+            .grpc_client
+            .get_virtual_txs(vec![ark_txid.to_string()], None)
+            .await
+            .context("failed to get virtual transactions")?;
+
+        Ok(response
+            .txs
+            .iter()
+            .any(|tx| tx.unsigned_tx.compute_txid() == ark_txid))
+    }
+
+    /// The address that holds `funds` and takes the change of sends paid from them
+    fn funds_address(&self, funds: Funds) -> &Vtxo {
+        match funds {
+            Funds::House => &self.main_address.0,
+            Funds::Lottery => &self.lottery_address.0,
+            Funds::Duel => &self.duel_address.0,
+        }
+    }
+
+    /// Every key our VTXOs can be spent with
+    fn signing_keys(&self) -> Vec<(Vtxo, SecretKey)> {
+        let mut all_keys = vec![self.main_address.clone()];
+        all_keys.extend(self.pool_addresses().into_iter().cloned());
+        for game_address in self.all_game_addresses() {
+            all_keys.push((game_address.vtxo.clone(), game_address.secret_key));
+        }
+        all_keys
+    }
+
+    fn sign_input(
+        &self,
+        all_keys: &[(Vtxo, SecretKey)],
+        vtxo_inputs: &[send::VtxoInput],
+        index: usize,
+        msg: secp256k1::Message,
+    ) -> Result<(schnorr::Signature, XOnlyPublicKey), ark_core::Error> {
+        let input = vtxo_inputs.get(index).expect("input");
+        let kp = all_keys.iter().find_map(|(v, sk)| {
+            if input.script_pubkey() == v.script_pubkey() {
+                Some(sk.keypair(&self.secp))
+            } else {
+                None
+            }
+        });
+        let kp = kp
+            .context("Key not found for vtxo")
+            .map_err(ark_core::Error::ad_hoc)?;
+
+        let sig = self.secp.sign_schnorr_no_aux_rand(&msg, &kp);
+        let pk = kp.x_only_public_key().0;
+        Ok((sig, pk))
+    }
 }
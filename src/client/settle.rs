@@ -215,11 +215,15 @@ impl ArkClient {
 
         let (main_address, main_sk) = &self.main_address;
         let main_signing_kp = Keypair::from_secret_key(&self.secp, main_sk);
-        let mut signing_kps = self
-            .game_addresses
+        let game_addresses = self.all_game_addresses();
+        let mut signing_kps = game_addresses
             .iter()
             .map(|game_ark_address| game_ark_address.secret_key.keypair(&self.secp))
             .collect::<Vec<_>>();
+        let pool_signing_kps = self
+            .pool_addresses()
+            .map(|(vtxo, sk)| (vtxo.to_ark_address().encode(), sk.keypair(&self.secp)));
+        signing_kps.extend(pool_signing_kps.iter().map(|(_, kp)| *kp));
         signing_kps.push(main_signing_kp);
 
         let sign_for_onchain_pk_fn = |xonly_public_key: &XOnlyPublicKey,
@@ -591,9 +595,14 @@ impl ArkClient {
                                         == main_address.to_ark_address().encode()
                                     {
                                         main_signing_kp
+                                    } else if let Some((_, kp)) = pool_signing_kps
+                                        .iter()
+                                        .find(|(address, _)| *address == ark_address)
+                                    {
+                                        *kp
                                     } else {
                                         let maybe_kp =
-                                            self.game_addresses.iter().find_map(|game_address| {
+                                            game_addresses.iter().find_map(|game_address| {
                                                 if game_address.vtxo.to_ark_address().encode()
                                                     == ark_address
                                                 {
@@ -1,3 +1,19 @@
+use crate::duel::DuelSettings;
+use crate::games::coin_flip::CoinFlipGame;
+use crate::games::crash::CrashGame;
+use crate::games::high_low::HighLowGame;
+use crate::games::over_under::OverUnderGame;
+use crate::games::registry::GameRegistry;
+use crate::games::satoshis_number::RollVersion;
+use crate::games::GameSettings;
+use crate::games::GameType;
+use crate::jackpot::JackpotSettings;
+use crate::lottery::LotterySettings;
+use crate::multiplier::Multiplier;
+use crate::multiplier::MultiplierError;
+use crate::multiplier::MultiplierTable;
+use crate::nonce_service::NonceMode;
+use anyhow::Context;
 use serde::Deserialize;
 
 #[derive(Deserialize, Clone)]
@@ -12,6 +28,140 @@ pub struct Config {
     pub max_payout_sats: u64,
     #[serde(default = "default_vtxo_sync_interval")]
     pub vtxo_sync_interval_seconds: u64,
+    #[serde(default)]
+    pub nonce_mode: NonceMode,
+    /// Number of nonces derived from each seed in `hash_chain` mode
+    #[serde(default = "default_hash_chain_length")]
+    pub hash_chain_length: u32,
+    /// House edge of the coin flip in basis points, e.g. 300 for 3%
+    #[serde(default = "default_coin_flip_house_edge_bps")]
+    pub coin_flip_house_edge_bps: u16,
+    /// House edge of high/low in basis points, taken off the payout of both directions
+    #[serde(default = "default_high_low_house_edge_bps")]
+    pub high_low_house_edge_bps: u16,
+    /// House edge of over/under in basis points
+    #[serde(default = "default_over_under_house_edge_bps")]
+    pub over_under_house_edge_bps: u16,
+    /// House edge of crash in basis points, built into the distribution of the crash point
+    #[serde(default = "default_crash_house_edge_bps")]
+    pub crash_house_edge_bps: u16,
+    /// How long a bet intent address waits for its bet, later bets to it are refunded
+    #[serde(default = "default_bet_intent_ttl_minutes")]
+    pub bet_intent_ttl_minutes: u64,
+    /// Multipliers of Satoshi's Number, the historical ones if not set
+    #[serde(default = "default_multipliers")]
+    pub multipliers: Vec<MultiplierConfig>,
+    /// RTP of multipliers that set neither a threshold nor their own RTP or house edge, e.g. 97.0
+    pub multiplier_rtp_percent: Option<f64>,
+    /// Games that take no bets, by name, e.g. `["coin-flip"]`. Bets sent to them are refunded.
+    #[serde(default)]
+    pub disabled_games: Vec<String>,
+    /// Share of every losing bet that goes into the jackpot in basis points, 0 disables the
+    /// jackpot
+    #[serde(default)]
+    pub jackpot_contribution_bps: u16,
+    /// A bet wins the jackpot with a chance of 1 in `jackpot_odds`
+    #[serde(default = "default_jackpot_odds")]
+    pub jackpot_odds: u32,
+    /// Length of a lottery round in minutes, 0 disables the lottery
+    #[serde(default)]
+    pub lottery_round_minutes: u64,
+    /// Sats a single lottery ticket costs
+    #[serde(default = "default_lottery_ticket_price_sats")]
+    pub lottery_ticket_price_sats: u64,
+    /// Share of every lottery pot kept by the house in basis points
+    #[serde(default = "default_lottery_rake_bps")]
+    pub lottery_rake_bps: u16,
+    /// Minutes a duel challenge waits for an opponent before it is refunded, 0 disables duels
+    #[serde(default)]
+    pub duel_timeout_minutes: u64,
+    /// Share of both duel stakes kept by the house in basis points
+    #[serde(default = "default_duel_rake_bps")]
+    pub duel_rake_bps: u16,
+    /// Validated form of `multipliers`, built when the config is loaded
+    #[serde(skip)]
+    multiplier_table: MultiplierTable,
+    /// Parsed form of `disabled_games`, built when the config is loaded
+    #[serde(skip)]
+    disabled_game_types: Vec<GameType>,
+}
+
+/// A multiplier of Satoshi's Number. Its threshold is either given explicitly or computed from a
+/// target RTP (return to player) or house edge, falling back to `multiplier_rtp_percent`.
+///
+/// Thresholds computed from an RTP are set at the resolution of 32-bit rolls, explicit thresholds
+/// are 16-bit rolls and give the same odds under every roll version.
+///
+/// ```toml
+/// [[multipliers]]
+/// value = 400 # 4.00x
+/// index = 11
+/// rtp_percent = 97.0
+/// ```
+#[derive(Deserialize, Clone, Debug)]
+pub struct MultiplierConfig {
+    /// Payout, e.g. 105 for 1.05x
+    pub value: u64,
+    /// Derivation index of the address. Never give an index that received bets different odds.
+    pub index: u32,
+    /// The bet wins if the 16-bit roll is below this threshold
+    pub threshold: Option<u32>,
+    /// Share of every bet returned to the player, e.g. 97.0 for 97%
+    pub rtp_percent: Option<f64>,
+    /// House edge the threshold is computed from, e.g. 3.0 for 3%
+    pub house_edge_percent: Option<f64>,
+}
+
+impl MultiplierConfig {
+    fn to_multiplier(&self, default_rtp_percent: Option<f64>) -> anyhow::Result<Multiplier> {
+        let multiplier = match (self.threshold, self.rtp_percent, self.house_edge_percent) {
+            (Some(threshold), None, None) => Multiplier::new(
+                self.value,
+                self.index,
+                u16::try_from(threshold).map_err(|_| MultiplierError::ThresholdOutOfRange {
+                    value: self.value,
+                    threshold: threshold as u64,
+                    roll_version: RollVersion::V1,
+                })?,
+            ),
+            (None, Some(rtp_percent), None) => self.from_rtp(rtp_percent)?,
+            (None, None, Some(house_edge_percent)) => self.from_rtp(100.0 - house_edge_percent)?,
+            (None, None, None) => match default_rtp_percent {
+                Some(rtp_percent) => self.from_rtp(rtp_percent)?,
+                None => anyhow::bail!(
+                    "multiplier {} needs a threshold, rtp_percent or house_edge_percent, or a \
+                     global multiplier_rtp_percent",
+                    self.value
+                ),
+            },
+            _ => anyhow::bail!(
+                "multiplier {} needs only one of threshold, rtp_percent and house_edge_percent",
+                self.value
+            ),
+        };
+
+        Ok(multiplier)
+    }
+
+    fn from_rtp(&self, rtp_percent: f64) -> anyhow::Result<Multiplier> {
+        let rtp_bps = (rtp_percent * 100.0).round();
+        if !(0.0..=10_000.0).contains(&rtp_bps) {
+            anyhow::bail!(
+                "RTP of multiplier {} must be between 0% and 100%, got {}%",
+                self.value,
+                rtp_percent
+            );
+        }
+        let rtp_bps = rtp_bps as u16;
+
+        Ok(Multiplier::from_rtp(self.value, self.index, rtp_bps).ok_or(
+            MultiplierError::InvalidRtp {
+                value: self.value,
+                rtp_bps,
+                roll_version: RollVersion::LATEST,
+            },
+        )?)
+    }
 }
 
 fn default_transaction_check_interval() -> u64 {
@@ -26,15 +176,237 @@ fn default_vtxo_sync_interval() -> u64 {
     300 // 5 minutes
 }
 
+fn default_hash_chain_length() -> u32 {
+    8760 // one year of hourly nonces
+}
+
+fn default_coin_flip_house_edge_bps() -> u16 {
+    CoinFlipGame::DEFAULT_HOUSE_EDGE_BPS
+}
+
+fn default_high_low_house_edge_bps() -> u16 {
+    HighLowGame::DEFAULT_HOUSE_EDGE_BPS
+}
+
+fn default_over_under_house_edge_bps() -> u16 {
+    OverUnderGame::DEFAULT_HOUSE_EDGE_BPS
+}
+
+fn default_crash_house_edge_bps() -> u16 {
+    CrashGame::DEFAULT_HOUSE_EDGE_BPS
+}
+
+fn default_bet_intent_ttl_minutes() -> u64 {
+    30
+}
+
+fn default_jackpot_odds() -> u32 {
+    1_000_000
+}
+
+fn default_lottery_ticket_price_sats() -> u64 {
+    1_000
+}
+
+fn default_lottery_rake_bps() -> u16 {
+    500
+}
+
+fn default_duel_rake_bps() -> u16 {
+    250
+}
+
+fn default_multipliers() -> Vec<MultiplierConfig> {
+    Multiplier::HISTORICAL
+        .iter()
+        .map(|multiplier| MultiplierConfig {
+            value: multiplier.multiplier(),
+            index: multiplier.index(),
+            threshold: Some(multiplier.get_lower_than() as u32),
+            rtp_percent: None,
+            house_edge_percent: None,
+        })
+        .collect()
+}
+
 impl Config {
     pub fn from_file(path: &str) -> anyhow::Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+        Self::from_toml(&content)
+    }
+
+    fn from_toml(content: &str) -> anyhow::Result<Self> {
+        let mut config: Config = toml::from_str(content)?;
+        config.multiplier_table = config.build_multiplier_table()?;
+        config.disabled_game_types = config
+            .disabled_games
+            .iter()
+            .map(|name| {
+                name.parse::<GameType>()
+                    .map_err(|_| anyhow::anyhow!("Unknown game in disabled_games: {name}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
         Ok(config)
     }
 
+    fn build_multiplier_table(&self) -> anyhow::Result<MultiplierTable> {
+        let multipliers = self
+            .multipliers
+            .iter()
+            .map(|multiplier| multiplier.to_multiplier(self.multiplier_rtp_percent))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .context("Invalid multiplier")?;
+
+        MultiplierTable::new(multipliers).context("Invalid multipliers")
+    }
+
+    pub fn game_settings(&self) -> GameSettings {
+        GameSettings {
+            multipliers: self.multiplier_table.clone(),
+            coin_flip_house_edge_bps: self.coin_flip_house_edge_bps,
+            high_low_house_edge_bps: self.high_low_house_edge_bps,
+            over_under_house_edge_bps: self.over_under_house_edge_bps,
+            crash_house_edge_bps: self.crash_house_edge_bps,
+        }
+    }
+
+    pub fn jackpot_settings(&self) -> JackpotSettings {
+        JackpotSettings {
+            contribution_bps: self.jackpot_contribution_bps,
+            odds: self.jackpot_odds,
+        }
+    }
+
+    /// Settings of the lottery, `None` if it is disabled
+    pub fn lottery_settings(&self) -> Option<LotterySettings> {
+        (self.lottery_round_minutes > 0).then_some(LotterySettings {
+            ticket_price_sats: self.lottery_ticket_price_sats,
+            rake_bps: self.lottery_rake_bps,
+        })
+    }
+
+    /// Settings of duels, `None` if they are disabled
+    pub fn duel_settings(&self) -> Option<DuelSettings> {
+        (self.duel_timeout_minutes > 0).then_some(DuelSettings {
+            rake_bps: self.duel_rake_bps,
+            timeout_minutes: self.duel_timeout_minutes,
+        })
+    }
+
+    /// Every game with the configured settings, see [`Config::disabled_games`]
+    pub fn game_registry(&self) -> GameRegistry {
+        GameRegistry::new(self.game_settings(), &self.disabled_game_types)
+    }
+
     /// Get telegram bot token from environment variable
     pub fn telegram_bot_token() -> Option<String> {
         std::env::var("TELEGRAM_BOT_KEY").ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> anyhow::Result<Config> {
+        Config::from_toml(&format!(
+            "ark_server_url = \"\"\nesplora_url = \"\"\nmaster_seed_file = \"\"\ndatabase = \"\"\n{toml}"
+        ))
+    }
+
+    #[test]
+    fn test_default_multipliers_are_historical() {
+        let config = parse("").unwrap();
+        assert_eq!(
+            config.game_settings().multipliers,
+            MultiplierTable::default()
+        );
+    }
+
+    #[test]
+    fn test_multiplier_thresholds_from_rtp() {
+        let config = parse(
+            r#"
+            multiplier_rtp_percent = 99.0
+
+            [[multipliers]]
+            value = 200
+            index = 4
+            threshold = 31784
+
+            [[multipliers]]
+            value = 400
+            index = 11
+            house_edge_percent = 3.0
+
+            [[multipliers]]
+            value = 500
+            index = 12
+            rtp_percent = 98.0
+
+            [[multipliers]]
+            value = 300
+            index = 13
+            "#,
+        )
+        .unwrap();
+
+        let multipliers = config.game_settings().multipliers;
+        assert_eq!(multipliers.all().len(), 4);
+        assert_eq!(
+            multipliers.from_index(4),
+            Some(Multiplier::new(200, 4, 31_784))
+        );
+        assert_eq!(
+            multipliers.from_index(11),
+            Multiplier::from_rtp(400, 11, 9_700)
+        );
+        assert_eq!(
+            multipliers.from_index(12),
+            Multiplier::from_rtp(500, 12, 9_800)
+        );
+        // Global RTP
+        assert_eq!(
+            multipliers.from_index(13),
+            Multiplier::from_rtp(300, 13, 9_900)
+        );
+
+        // Games rolled with 16 bits keep the thresholds they were played with
+        let thresholds = [11, 12, 13].map(|index| {
+            multipliers
+                .from_index(index)
+                .map(|multiplier| multiplier.get_lower_than())
+        });
+        assert_eq!(thresholds, [Some(15_892), Some(12_845), Some(21_626)]);
+    }
+
+    #[test]
+    fn test_disabled_games() {
+        let games = parse(r#"disabled_games = ["coin-flip"]"#)
+            .unwrap()
+            .game_registry();
+        assert!(!games.is_enabled(GameType::CoinFlip));
+        assert!(games.is_enabled(GameType::SatoshisNumber));
+
+        assert!(parse(r#"disabled_games = ["blackjack"]"#).is_err());
+    }
+
+    #[test]
+    fn test_invalid_multipliers_are_rejected() {
+        for toml in [
+            // Threshold out of range
+            "[[multipliers]]\nvalue = 400\nindex = 11\nthreshold = 65536",
+            // No threshold and no RTP
+            "[[multipliers]]\nvalue = 400\nindex = 11",
+            // Threshold and RTP
+            "[[multipliers]]\nvalue = 400\nindex = 11\nthreshold = 100\nrtp_percent = 97.0",
+            // Historical value with other odds
+            "[[multipliers]]\nvalue = 200\nindex = 4\nhouse_edge_percent = 1.0",
+            // Duplicate index
+            "[[multipliers]]\nvalue = 400\nindex = 11\nthreshold = 100\n\
+             [[multipliers]]\nvalue = 500\nindex = 11\nthreshold = 100",
+        ] {
+            assert!(parse(toml).is_err(), "{toml}");
+        }
+    }
+}
@@ -9,15 +9,46 @@ pub struct Nonce {
     pub nonce_hash: String,
     pub created_at: OffsetDateTime,
     pub expires_at: OffsetDateTime,
+    pub chain_position: Option<i64>,
+    /// When games started being evaluated against this nonce
+    pub activated_at: Option<OffsetDateTime>,
+    /// When the nonce was replaced, only retired nonces may be revealed
+    pub retired_at: Option<OffsetDateTime>,
+    /// When the nonce was made public, [`crate::nonce_service::NonceService`] reveals nonces once
+    /// their reveal delay has passed
+    pub revealed_at: Option<OffsetDateTime>,
 }
 
+/// A stored nonce together with the number of games played under it
+#[derive(Debug, sqlx::FromRow)]
+pub struct NonceHistoryEntry {
+    pub id: i64,
+    pub nonce: String,
+    pub nonce_hash: String,
+    pub created_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+    pub chain_position: Option<i64>,
+    pub activated_at: Option<OffsetDateTime>,
+    pub retired_at: Option<OffsetDateTime>,
+    pub revealed_at: Option<OffsetDateTime>,
+    pub game_count: i64,
+}
+
+/// `rolled_number` of a bet that was refunded instead of played. Donations are stored as -1.
+pub const REFUNDED_ROLL: i64 = -2;
+
 #[derive(Debug, sqlx::FromRow)]
 pub struct GameResult {
     pub id: i64,
     pub nonce: String,
     pub rolled_number: i64,
     pub input_tx_id: String,
+    /// Output of `input_tx_id` that was played, missing for games stored before bets were tracked
+    /// per output
+    pub input_vout: Option<i64>,
     pub output_tx_id: Option<String>,
+    /// Output of `output_tx_id` that paid the player, several players can share a transaction
+    pub output_vout: Option<i64>,
     pub bet_amount: i64,
     pub winning_amount: Option<i64>,
     pub player_address: String,
@@ -25,6 +56,182 @@ pub struct GameResult {
     pub payment_successful: bool,
     pub timestamp: OffsetDateTime,
     pub multiplier: i64,
+    pub client_seed: Option<String>,
+    pub chain_position: Option<i64>,
+    /// Discriminant of the [`crate::games::GameType`] that was played
+    pub game_type: i64,
+    /// Index of the [`crate::games::GameOption`] that was bet on, missing for games stored before
+    /// options were recorded
+    pub game_option: Option<i64>,
+    /// Part of `winning_amount` that returned the bet without a win
+    pub refund_amount: Option<i64>,
+    /// Short name of the outcome, e.g. "win", missing for games stored before outcomes were
+    /// labelled
+    pub outcome_label: Option<String>,
+    /// Game specific details of the outcome as a JSON object
+    pub outcome_detail: Option<String>,
+    /// [`crate::games::satoshis_number::RollVersion`] the game was played with
+    pub roll_version: i64,
+    /// House edge in basis points the game was played with, missing for games without one
+    pub house_edge_bps: Option<i64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct NonceChain {
+    pub id: i64,
+    pub seed: String,
+    pub terminal_hash: String,
+    pub length: i64,
+    pub position: i64,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ClientSeed {
+    pub address: String,
+    pub client_seed: String,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+/// Outcomes of the Satoshi's Number games stored with one multiplier under one roll version
+#[derive(Debug, sqlx::FromRow)]
+pub struct StoredMultiplierGames {
+    pub game_option: Option<i64>,
+    pub multiplier: i64,
+    pub roll_version: i64,
+    pub highest_win: Option<i64>,
+    pub lowest_loss: Option<i64>,
+}
+
+/// One-time address handed out for an over/under bet on a target picked by the player
+#[derive(Debug, sqlx::FromRow)]
+pub struct BetIntent {
+    pub id: i64,
+    /// Index of the address key below the over/under derivation path
+    pub derivation_index: i64,
+    pub address: String,
+    /// Discriminant of the [`crate::games::over_under::Side`]
+    pub side: i64,
+    pub target: i64,
+    /// Payout offered for the target, e.g. 200 for 2.00x
+    pub payout_multiplier: i64,
+    pub created_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+    /// When the first bet to the address was played
+    pub paid_at: Option<OffsetDateTime>,
+    /// When the intent was given up on without a bet, its address stays watched and bets that
+    /// reach it later are refunded
+    pub expired_at: Option<OffsetDateTime>,
+}
+
+/// `entry_type` of the share of a losing bet that went into the jackpot
+pub const JACKPOT_CONTRIBUTION: &str = "contribution";
+/// `entry_type` of a bet that won the jackpot, its amount is negative
+pub const JACKPOT_WIN: &str = "win";
+
+/// A change to the jackpot, see [`JACKPOT_CONTRIBUTION`] and [`JACKPOT_WIN`]
+#[derive(Debug, sqlx::FromRow)]
+pub struct JackpotEntry {
+    pub id: i64,
+    pub entry_type: String,
+    pub amount: i64,
+    pub input_tx_id: String,
+    pub input_vout: i64,
+    pub player_address: Option<String>,
+    pub payout_tx_id: Option<String>,
+    pub created_at: OffsetDateTime,
+}
+
+/// A round of the lottery, see [`crate::lottery_service`]
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LotteryRound {
+    pub id: i64,
+    /// Only published once the round is drawn
+    pub nonce: String,
+    pub nonce_hash: String,
+    pub ticket_price: i64,
+    pub rake_bps: i64,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: OffsetDateTime,
+    /// When the round stopped taking tickets
+    pub closed_at: Option<OffsetDateTime>,
+    pub drawn_at: Option<OffsetDateTime>,
+    pub tickets_hash: Option<String>,
+    pub winning_ticket: Option<i64>,
+    /// Purchase holding the winning ticket, missing if no ticket was bought
+    pub winner_ticket_id: Option<i64>,
+    pub pot_amount: Option<i64>,
+    pub rake_amount: Option<i64>,
+    pub payout_amount: Option<i64>,
+    pub payout_tx_id: Option<String>,
+    pub created_at: OffsetDateTime,
+}
+
+/// A duel between two players, see [`crate::duel`]
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Duel {
+    pub id: i64,
+    /// "open", "matched", "expired", "settled" or "refunded"
+    pub status: String,
+    pub stake_amount: i64,
+    pub rake_bps: i64,
+    pub challenger_address: String,
+    pub challenger_tx_id: String,
+    pub challenger_vout: i64,
+    pub expires_at: OffsetDateTime,
+    pub opponent_address: Option<String>,
+    pub opponent_tx_id: Option<String>,
+    pub opponent_vout: Option<i64>,
+    /// Only published once the nonce is revealed
+    pub nonce: Option<String>,
+    pub nonce_hash: Option<String>,
+    pub winner_address: Option<String>,
+    pub rake_amount: Option<i64>,
+    pub payout_amount: Option<i64>,
+    pub matched_at: Option<OffsetDateTime>,
+    pub payout_tx_id: Option<String>,
+    pub settled_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+/// A payment we send, see [`crate::payouts`]
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Payout {
+    pub id: i64,
+    pub source: String,
+    pub source_id: i64,
+    pub transaction_type: String,
+    pub address: String,
+    pub amount: i64,
+    /// "planned", "signed", "submitted", "finalized" or "failed"
+    pub status: String,
+    /// Shared by every payout of a batch
+    pub ark_txid: Option<String>,
+    /// Output of `ark_txid` that pays this payout
+    pub vout: Option<i64>,
+    /// The VTXOs spent by the Ark transaction, comma separated `txid:vout`
+    pub inputs: Option<String>,
+    pub error: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+/// A payment to the lottery address
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LotteryTicket {
+    pub id: i64,
+    /// Missing for a payment that came in after its round was closed, it is refunded
+    pub round_id: Option<i64>,
+    pub input_tx_id: String,
+    pub input_vout: i64,
+    pub player_address: String,
+    pub amount: i64,
+    /// Number of the first ticket of the purchase within its round, counted from 0
+    pub first_ticket: i64,
+    pub ticket_count: i64,
+    pub refund_tx_id: Option<String>,
+    pub created_at: OffsetDateTime,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -40,15 +247,19 @@ pub async fn insert_nonce(
     nonce: &str,
     nonce_hash: &str,
     expires_at: OffsetDateTime,
+    chain_position: Option<i64>,
+    activated_at: OffsetDateTime,
 ) -> Result<i64, sqlx::Error> {
     let result = sqlx::query!(
         r#"
-        INSERT INTO nonces (nonce, nonce_hash, expires_at)
-        VALUES (?, ?, ?)
+        INSERT INTO nonces (nonce, nonce_hash, expires_at, chain_position, activated_at)
+        VALUES (?, ?, ?, ?, ?)
         "#,
         nonce,
         nonce_hash,
-        expires_at
+        expires_at,
+        chain_position,
+        activated_at
     )
     .execute(pool)
     .await?;
@@ -60,7 +271,8 @@ pub async fn get_nonce(pool: &Pool<Sqlite>, nonce: &str) -> Result<Option<Nonce>
     let nonce = sqlx::query_as!(
         Nonce,
         r#"
-        SELECT id, nonce, nonce_hash, created_at, expires_at
+        SELECT id, nonce, nonce_hash, created_at, expires_at, chain_position,
+               activated_at, retired_at, revealed_at
         FROM nonces
         WHERE nonce = ?
         "#,
@@ -72,6 +284,133 @@ pub async fn get_nonce(pool: &Pool<Sqlite>, nonce: &str) -> Result<Option<Nonce>
     Ok(nonce)
 }
 
+pub async fn get_nonces_paginated(
+    pool: &Pool<Sqlite>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<NonceHistoryEntry>, sqlx::Error> {
+    let offset = (page - 1) * page_size;
+
+    let nonces = sqlx::query_as!(
+        NonceHistoryEntry,
+        r#"
+        SELECT n.id, n.nonce, n.nonce_hash, n.created_at, n.expires_at, n.chain_position,
+               n.activated_at, n.retired_at, n.revealed_at,
+               COUNT(g.id) as game_count
+        FROM nonces n
+        LEFT JOIN game_results g ON g.nonce = n.nonce AND g.rolled_number >= 0
+        GROUP BY n.id
+        ORDER BY n.id DESC
+        LIMIT ? OFFSET ?
+        "#,
+        page_size,
+        offset
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(nonces)
+}
+
+pub async fn get_total_nonce_count(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as count
+        FROM nonces
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.count)
+}
+
+/// The newest nonce that is neither retired nor expired
+pub async fn get_active_nonce(pool: &Pool<Sqlite>) -> Result<Option<Nonce>, sqlx::Error> {
+    let nonce = sqlx::query_as!(
+        Nonce,
+        r#"
+        SELECT id, nonce, nonce_hash, created_at, expires_at, chain_position,
+               activated_at, retired_at, revealed_at
+        FROM nonces
+        WHERE retired_at IS NULL AND expires_at > datetime('now')
+        ORDER BY id DESC
+        LIMIT 1
+        "#
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(nonce)
+}
+
+/// The nonce whose active window contains `time`
+pub async fn get_nonce_active_at(
+    pool: &Pool<Sqlite>,
+    time: OffsetDateTime,
+) -> Result<Option<Nonce>, sqlx::Error> {
+    let nonce = sqlx::query_as!(
+        Nonce,
+        r#"
+        SELECT id, nonce, nonce_hash, created_at, expires_at, chain_position,
+               activated_at, retired_at, revealed_at
+        FROM nonces
+        WHERE julianday(activated_at) <= julianday(?)
+          AND (retired_at IS NULL OR julianday(retired_at) > julianday(?))
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+        time,
+        time
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(nonce)
+}
+
+/// Retire every nonce that is still marked as active
+pub async fn retire_active_nonces(
+    pool: &Pool<Sqlite>,
+    retired_at: OffsetDateTime,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE nonces
+        SET retired_at = ?
+        WHERE retired_at IS NULL
+        "#,
+        retired_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Reveal every nonce that was retired at or before `retired_before` and is not revealed yet
+pub async fn reveal_retired_nonces(
+    pool: &Pool<Sqlite>,
+    retired_before: OffsetDateTime,
+    revealed_at: OffsetDateTime,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE nonces
+        SET revealed_at = ?
+        WHERE retired_at IS NOT NULL
+          AND revealed_at IS NULL
+          AND julianday(retired_at) <= julianday(?)
+        "#,
+        revealed_at,
+        retired_before
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 pub async fn is_nonce_valid(pool: &Pool<Sqlite>, nonce: &str) -> Result<bool, sqlx::Error> {
     let result = sqlx::query!(
         r#"
@@ -87,12 +426,88 @@ pub async fn is_nonce_valid(pool: &Pool<Sqlite>, nonce: &str) -> Result<bool, sq
     Ok(result.count > 0)
 }
 
+pub async fn get_active_nonce_chain(
+    pool: &Pool<Sqlite>,
+) -> Result<Option<NonceChain>, sqlx::Error> {
+    let chain = sqlx::query_as!(
+        NonceChain,
+        r#"
+        SELECT id, seed, terminal_hash, length, position, created_at
+        FROM nonce_chains
+        WHERE position < length
+        ORDER BY id DESC
+        LIMIT 1
+        "#
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(chain)
+}
+
+pub async fn get_nonce_chains(pool: &Pool<Sqlite>) -> Result<Vec<NonceChain>, sqlx::Error> {
+    let chains = sqlx::query_as!(
+        NonceChain,
+        r#"
+        SELECT id, seed, terminal_hash, length, position, created_at
+        FROM nonce_chains
+        ORDER BY id DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(chains)
+}
+
+pub async fn insert_nonce_chain(
+    pool: &Pool<Sqlite>,
+    seed: &str,
+    terminal_hash: &str,
+    length: i64,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO nonce_chains (seed, terminal_hash, length)
+        VALUES (?, ?, ?)
+        "#,
+        seed,
+        terminal_hash,
+        length
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn advance_nonce_chain(
+    pool: &Pool<Sqlite>,
+    chain_id: i64,
+    position: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE nonce_chains
+        SET position = ?
+        WHERE id = ?
+        "#,
+        position,
+        chain_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn insert_game_result(
     pool: &Pool<Sqlite>,
     nonce: &str,
     rolled_number: i64,
     input_tx_id: &str,
+    input_vout: i64,
     output_tx_id: Option<&str>,
     bet_amount: i64,
     winning_amount: Option<i64>,
@@ -100,26 +515,47 @@ pub async fn insert_game_result(
     is_winner: bool,
     payment_successful: bool,
     multiplier: i64,
+    client_seed: Option<&str>,
+    chain_position: Option<i64>,
+    game_type: i64,
+    game_option: i64,
+    refund_amount: Option<i64>,
+    outcome_label: Option<&str>,
+    outcome_detail: Option<&str>,
+    roll_version: i64,
+    house_edge_bps: Option<i64>,
 ) -> Result<i64, sqlx::Error> {
     let result = sqlx::query!(
         r#"
         INSERT INTO game_results (
-            nonce, rolled_number, input_tx_id, output_tx_id,
+            nonce, rolled_number, input_tx_id, input_vout, output_tx_id,
             bet_amount, winning_amount, player_address,
-            is_winner, payment_successful, multiplier
+            is_winner, payment_successful, multiplier, client_seed,
+            chain_position, game_type, game_option,
+            refund_amount, outcome_label, outcome_detail, roll_version, house_edge_bps
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         nonce,
         rolled_number,
         input_tx_id,
+        input_vout,
         output_tx_id,
         bet_amount,
         winning_amount,
         player_address,
         is_winner,
         payment_successful,
-        multiplier
+        multiplier,
+        client_seed,
+        chain_position,
+        game_type,
+        game_option,
+        refund_amount,
+        outcome_label,
+        outcome_detail,
+        roll_version,
+        house_edge_bps
     )
     .execute(pool)
     .await?;
@@ -127,17 +563,21 @@ pub async fn insert_game_result(
     Ok(result.last_insert_rowid())
 }
 
-pub async fn is_transaction_processed(
+/// Whether the bet at `input_tx_id:input_vout` has already been recorded. Games stored before bets
+/// were tracked per output cover every output of their transaction.
+pub async fn is_outpoint_processed(
     pool: &Pool<Sqlite>,
     input_tx_id: &str,
+    input_vout: i64,
 ) -> Result<bool, sqlx::Error> {
     let result = sqlx::query!(
         r#"
         SELECT COUNT(*) as count
         FROM game_results
-        WHERE input_tx_id = ?
+        WHERE input_tx_id = ? AND (input_vout = ? OR input_vout IS NULL)
         "#,
-        input_tx_id
+        input_tx_id,
+        input_vout
     )
     .fetch_one(pool)
     .await?;
@@ -189,9 +629,12 @@ pub async fn get_game_results_paginated(
     let results = sqlx::query_as!(
         GameResult,
         r#"
-        SELECT id, nonce, rolled_number, input_tx_id, output_tx_id,
+        SELECT id, nonce, rolled_number, input_tx_id, input_vout, output_tx_id,
                bet_amount, winning_amount, player_address, is_winner,
-               payment_successful, timestamp, multiplier
+               payment_successful, timestamp, multiplier, client_seed,
+               chain_position, game_type, game_option,
+               refund_amount, outcome_label, outcome_detail, roll_version, output_vout,
+               house_edge_bps
         FROM game_results
         ORDER BY timestamp DESC
         LIMIT ? OFFSET ?
@@ -205,6 +648,78 @@ pub async fn get_game_results_paginated(
     Ok(results)
 }
 
+pub async fn get_all_game_results(pool: &Pool<Sqlite>) -> Result<Vec<GameResult>, sqlx::Error> {
+    let results = sqlx::query_as!(
+        GameResult,
+        r#"
+        SELECT id, nonce, rolled_number, input_tx_id, input_vout, output_tx_id,
+               bet_amount, winning_amount, player_address, is_winner,
+               payment_successful, timestamp, multiplier, client_seed,
+               chain_position, game_type, game_option,
+               refund_amount, outcome_label, outcome_detail, roll_version, output_vout,
+               house_edge_bps
+        FROM game_results
+        ORDER BY id ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(results)
+}
+
+/// Every game a transaction paid for, by output
+pub async fn get_game_results_by_input_tx_id(
+    pool: &Pool<Sqlite>,
+    input_tx_id: &str,
+) -> Result<Vec<GameResult>, sqlx::Error> {
+    let results = sqlx::query_as!(
+        GameResult,
+        r#"
+        SELECT id, nonce, rolled_number, input_tx_id, input_vout, output_tx_id,
+               bet_amount, winning_amount, player_address, is_winner,
+               payment_successful, timestamp, multiplier, client_seed,
+               chain_position, game_type, game_option,
+               refund_amount, outcome_label, outcome_detail, roll_version, output_vout,
+               house_edge_bps
+        FROM game_results
+        WHERE input_tx_id = ?
+        ORDER BY input_vout
+        "#,
+        input_tx_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(results)
+}
+
+pub async fn get_game_result_by_outpoint(
+    pool: &Pool<Sqlite>,
+    input_tx_id: &str,
+    input_vout: i64,
+) -> Result<Option<GameResult>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        GameResult,
+        r#"
+        SELECT id, nonce, rolled_number, input_tx_id, input_vout, output_tx_id,
+               bet_amount, winning_amount, player_address, is_winner,
+               payment_successful, timestamp, multiplier, client_seed,
+               chain_position, game_type, game_option,
+               refund_amount, outcome_label, outcome_detail, roll_version, output_vout,
+               house_edge_bps
+        FROM game_results
+        WHERE input_tx_id = ? AND input_vout = ?
+        "#,
+        input_tx_id,
+        input_vout
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(result)
+}
+
 pub async fn get_total_game_count(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
     let result = sqlx::query!(
         r#"
@@ -218,15 +733,19 @@ pub async fn get_total_game_count(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Erro
     Ok(result.count)
 }
 
+/// Winners and refunds that still have to be paid out
 pub async fn get_unpaid_winners(pool: &Pool<Sqlite>) -> Result<Vec<GameResult>, sqlx::Error> {
     let results = sqlx::query_as!(
         GameResult,
         r#"
-        SELECT id, nonce, rolled_number, input_tx_id, output_tx_id,
+        SELECT id, nonce, rolled_number, input_tx_id, input_vout, output_tx_id,
                bet_amount, winning_amount, player_address, is_winner,
-               payment_successful, timestamp, multiplier
+               payment_successful, timestamp, multiplier, client_seed,
+               chain_position, game_type, game_option,
+               refund_amount, outcome_label, outcome_detail, roll_version, output_vout,
+               house_edge_bps
         FROM game_results
-        WHERE is_winner = TRUE AND payment_successful = FALSE
+        WHERE winning_amount > 0 AND payment_successful = FALSE
         ORDER BY timestamp ASC
         "#
     )
@@ -244,11 +763,14 @@ pub async fn get_unpaid_winners_within_hours(
     let results = sqlx::query_as!(
         GameResult,
         r#"
-        SELECT id, nonce, rolled_number, input_tx_id, output_tx_id,
+        SELECT id, nonce, rolled_number, input_tx_id, input_vout, output_tx_id,
                bet_amount, winning_amount, player_address, is_winner,
-               payment_successful, timestamp, multiplier
+               payment_successful, timestamp, multiplier, client_seed,
+               chain_position, game_type, game_option,
+               refund_amount, outcome_label, outcome_detail, roll_version, output_vout,
+               house_edge_bps
         FROM game_results
-        WHERE is_winner = TRUE
+        WHERE winning_amount > 0
           AND payment_successful = FALSE
           AND timestamp >= datetime('now', '-' || ? || ' hours')
         ORDER BY timestamp ASC
@@ -265,14 +787,16 @@ pub async fn mark_payment_successful(
     pool: &Pool<Sqlite>,
     game_id: i64,
     output_tx_id: &str,
+    output_vout: i64,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         UPDATE game_results
-        SET payment_successful = TRUE, output_tx_id = ?
+        SET payment_successful = TRUE, output_tx_id = ?, output_vout = ?
         WHERE id = ?
         "#,
         output_tx_id,
+        output_vout,
         game_id
     )
     .execute(pool)
@@ -281,50 +805,174 @@ pub async fn mark_payment_successful(
     Ok(())
 }
 
-pub async fn register_telegram_chat(pool: &Pool<Sqlite>, chat_id: &str) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        r#"
-        INSERT OR IGNORE INTO telegram_registrations (chat_id)
-        VALUES (?)
-        "#,
-        chat_id
-    )
-    .execute(pool)
-    .await?;
-
-    Ok(())
-}
-
-pub async fn unregister_telegram_chat(
+/// Record `house_edge_bps` as the edge of the `game_type` games stored before edges were recorded
+pub async fn backfill_house_edge(
     pool: &Pool<Sqlite>,
-    chat_id: &str,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+    game_type: i64,
+    house_edge_bps: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
         r#"
-        DELETE FROM telegram_registrations
-        WHERE chat_id = ?
+        UPDATE game_results
+        SET house_edge_bps = ?
+        WHERE game_type = ? AND house_edge_bps IS NULL
         "#,
-        chat_id
+        house_edge_bps,
+        game_type
     )
     .execute(pool)
     .await?;
 
-    Ok(())
+    Ok(result.rows_affected())
 }
 
-pub async fn get_registered_telegram_chats(
+/// The distinct multipliers Satoshi's Number games were stored with, by option index and roll
+/// version. Games from before options were stored are left out.
+pub async fn get_stored_multiplier_games(
     pool: &Pool<Sqlite>,
-) -> Result<Vec<String>, sqlx::Error> {
-    let records = sqlx::query!(
+) -> Result<Vec<StoredMultiplierGames>, sqlx::Error> {
+    let games = sqlx::query_as!(
+        StoredMultiplierGames,
         r#"
-        SELECT chat_id
-        FROM telegram_registrations
+        SELECT game_option, multiplier, roll_version,
+               MAX(CASE WHEN is_winner THEN rolled_number END) as highest_win,
+               MIN(CASE WHEN NOT is_winner THEN rolled_number END) as lowest_loss
+        FROM game_results
+        WHERE game_type = 0 AND game_option IS NOT NULL AND rolled_number >= 0
+        GROUP BY game_option, multiplier, roll_version
         "#
     )
     .fetch_all(pool)
     .await?;
 
-    Ok(records.into_iter().map(|r| r.chat_id).collect())
+    Ok(games)
+}
+
+/// Register `client_seed` as the seed of `address` from `registered_at` on, keeping the seeds it
+/// registered before so that earlier bets are still evaluated with theirs
+pub async fn upsert_client_seed(
+    pool: &Pool<Sqlite>,
+    address: &str,
+    client_seed: &str,
+    registered_at: OffsetDateTime,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO client_seed_history (address, client_seed, created_at)
+        VALUES (?, ?, ?)
+        "#,
+        address,
+        client_seed,
+        registered_at
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO client_seeds (address, client_seed, created_at, updated_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT (address) DO UPDATE
+        SET client_seed = excluded.client_seed, updated_at = excluded.updated_at
+        "#,
+        address,
+        client_seed,
+        registered_at,
+        registered_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_client_seed(
+    pool: &Pool<Sqlite>,
+    address: &str,
+) -> Result<Option<ClientSeed>, sqlx::Error> {
+    let client_seed = sqlx::query_as!(
+        ClientSeed,
+        r#"
+        SELECT address, client_seed, created_at, updated_at
+        FROM client_seeds
+        WHERE address = ?
+        "#,
+        address
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(client_seed)
+}
+
+/// The seed `address` had registered at `time`, the one a bet created at that time is played with
+pub async fn get_client_seed_at(
+    pool: &Pool<Sqlite>,
+    address: &str,
+    time: OffsetDateTime,
+) -> Result<Option<String>, sqlx::Error> {
+    let client_seed = sqlx::query_scalar!(
+        r#"
+        SELECT client_seed
+        FROM client_seed_history
+        WHERE address = ?
+          AND julianday(created_at) <= julianday(?)
+        ORDER BY julianday(created_at) DESC, id DESC
+        LIMIT 1
+        "#,
+        address,
+        time
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(client_seed)
+}
+
+pub async fn register_telegram_chat(pool: &Pool<Sqlite>, chat_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO telegram_registrations (chat_id)
+        VALUES (?)
+        "#,
+        chat_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn unregister_telegram_chat(
+    pool: &Pool<Sqlite>,
+    chat_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM telegram_registrations
+        WHERE chat_id = ?
+        "#,
+        chat_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_registered_telegram_chats(
+    pool: &Pool<Sqlite>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let records = sqlx::query!(
+        r#"
+        SELECT chat_id
+        FROM telegram_registrations
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records.into_iter().map(|r| r.chat_id).collect())
 }
 
 pub async fn is_telegram_chat_registered(
@@ -358,6 +1006,7 @@ pub struct DatabaseStats {
 
 #[derive(Debug)]
 pub struct MultiplierStats {
+    pub game_type: i64,
     pub multiplier: i64,
     pub total_games: i64,
     pub total_winners: i64,
@@ -371,7 +1020,7 @@ pub async fn get_database_stats(pool: &Pool<Sqlite>) -> Result<DatabaseStats, sq
         r#"
         SELECT COUNT(*) as count
         FROM game_results
-        WHERE rolled_number != -1
+        WHERE rolled_number >= 0
         "#
     )
     .fetch_one(pool)
@@ -393,7 +1042,7 @@ pub async fn get_database_stats(pool: &Pool<Sqlite>) -> Result<DatabaseStats, sq
         r#"
         SELECT COUNT(*) as count
         FROM game_results
-        WHERE is_winner = FALSE AND rolled_number != -1
+        WHERE is_winner = FALSE AND rolled_number >= 0
         "#
     )
     .fetch_one(pool)
@@ -417,7 +1066,7 @@ pub async fn get_database_stats(pool: &Pool<Sqlite>) -> Result<DatabaseStats, sq
             COALESCE(SUM(bet_amount), 0) as total_bet,
             COALESCE(SUM(CASE WHEN winning_amount IS NOT NULL THEN winning_amount ELSE 0 END), 0) as total_payout
         FROM game_results
-        WHERE rolled_number != -1
+        WHERE rolled_number >= 0
         "#
     )
     .fetch_one(pool)
@@ -444,6 +1093,7 @@ pub async fn get_stats_by_multiplier(
     let stats = sqlx::query!(
         r#"
         SELECT
+            game_type,
             multiplier,
             COUNT(*) as total_games,
             SUM(CASE WHEN is_winner = TRUE THEN 1 ELSE 0 END) as total_winners,
@@ -451,9 +1101,9 @@ pub async fn get_stats_by_multiplier(
             SUM(bet_amount) as total_bet,
             COALESCE(SUM(CASE WHEN winning_amount IS NOT NULL THEN winning_amount ELSE 0 END), 0) as total_payout
         FROM game_results
-        WHERE rolled_number != -1
-        GROUP BY multiplier
-        ORDER BY multiplier ASC
+        WHERE rolled_number >= 0
+        GROUP BY game_type, multiplier
+        ORDER BY game_type ASC, multiplier ASC
         "#
     )
     .fetch_all(pool)
@@ -462,6 +1112,7 @@ pub async fn get_stats_by_multiplier(
     Ok(stats
         .into_iter()
         .map(|s| MultiplierStats {
+            game_type: s.game_type,
             multiplier: s.multiplier,
             total_games: s.total_games,
             total_winners: s.total_winners,
@@ -471,3 +1122,1238 @@ pub async fn get_stats_by_multiplier(
         })
         .collect())
 }
+
+/// The derivation index for the next bet intent, indices of expired intents are never reused
+pub async fn next_bet_intent_index(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COALESCE(MAX(derivation_index) + 1, 0) as next_index
+        FROM bet_intents
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.next_index)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_bet_intent(
+    pool: &Pool<Sqlite>,
+    derivation_index: i64,
+    address: &str,
+    side: i64,
+    target: i64,
+    payout_multiplier: i64,
+    expires_at: OffsetDateTime,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO bet_intents (
+            derivation_index, address, side, target, payout_multiplier, expires_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        derivation_index,
+        address,
+        side,
+        target,
+        payout_multiplier,
+        expires_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn get_bet_intent_by_address(
+    pool: &Pool<Sqlite>,
+    address: &str,
+) -> Result<Option<BetIntent>, sqlx::Error> {
+    let intent = sqlx::query_as!(
+        BetIntent,
+        r#"
+        SELECT id, derivation_index, address, side, target, payout_multiplier,
+               created_at, expires_at, paid_at, expired_at
+        FROM bet_intents
+        WHERE address = ?
+        "#,
+        address
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(intent)
+}
+
+/// Every intent, their addresses are watched and spent from for good so that late bets are refunded
+pub async fn get_bet_intents(pool: &Pool<Sqlite>) -> Result<Vec<BetIntent>, sqlx::Error> {
+    let intents = sqlx::query_as!(
+        BetIntent,
+        r#"
+        SELECT id, derivation_index, address, side, target, payout_multiplier,
+               created_at, expires_at, paid_at, expired_at
+        FROM bet_intents
+        ORDER BY derivation_index ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(intents)
+}
+
+/// Intents that were never paid and ran out of time before `now`
+pub async fn get_unpaid_bet_intents_expired_before(
+    pool: &Pool<Sqlite>,
+    now: OffsetDateTime,
+) -> Result<Vec<BetIntent>, sqlx::Error> {
+    let intents = sqlx::query_as!(
+        BetIntent,
+        r#"
+        SELECT id, derivation_index, address, side, target, payout_multiplier,
+               created_at, expires_at, paid_at, expired_at
+        FROM bet_intents
+        WHERE expired_at IS NULL AND paid_at IS NULL AND julianday(expires_at) <= julianday(?)
+        "#,
+        now
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(intents)
+}
+
+/// Record the first bet to an intent's address
+pub async fn mark_bet_intent_paid(pool: &Pool<Sqlite>, address: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE bet_intents
+        SET paid_at = CURRENT_TIMESTAMP
+        WHERE address = ? AND paid_at IS NULL
+        "#,
+        address
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Give up on an unpaid intent. Returns whether it was still unpaid.
+pub async fn mark_bet_intent_expired(pool: &Pool<Sqlite>, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE bet_intents
+        SET expired_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND paid_at IS NULL AND expired_at IS NULL
+        "#,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Add the share of a losing bet to the jackpot
+pub async fn insert_jackpot_contribution(
+    pool: &Pool<Sqlite>,
+    amount: i64,
+    input_tx_id: &str,
+    input_vout: i64,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO jackpot_ledger (entry_type, amount, input_tx_id, input_vout)
+        VALUES ('contribution', ?, ?, ?)
+        "#,
+        amount,
+        input_tx_id,
+        input_vout
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// The sats currently in the jackpot
+pub async fn get_jackpot_balance(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COALESCE(SUM(amount), 0) as balance
+        FROM jackpot_ledger
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.balance)
+}
+
+/// Take the whole jackpot out for the bet at `input_tx_id:input_vout`, in a single statement so
+/// that the pot cannot be paid twice. Returns `None` if the pot holds less than `min_amount`.
+pub async fn claim_jackpot(
+    pool: &Pool<Sqlite>,
+    input_tx_id: &str,
+    input_vout: i64,
+    player_address: &str,
+    min_amount: i64,
+) -> Result<Option<JackpotEntry>, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO jackpot_ledger (entry_type, amount, input_tx_id, input_vout, player_address)
+        SELECT 'win', -balance, ?, ?, ?
+        FROM (SELECT COALESCE(SUM(amount), 0) as balance FROM jackpot_ledger)
+        WHERE balance >= ? AND balance > 0
+        "#,
+        input_tx_id,
+        input_vout,
+        player_address,
+        min_amount
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    let entry = sqlx::query_as!(
+        JackpotEntry,
+        r#"
+        SELECT id, entry_type, amount, input_tx_id, input_vout, player_address, payout_tx_id,
+               created_at
+        FROM jackpot_ledger
+        WHERE id = ?
+        "#,
+        result.last_insert_rowid()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(entry)
+}
+
+/// Jackpot wins whose payout has not gone through yet
+pub async fn get_unpaid_jackpot_wins(
+    pool: &Pool<Sqlite>,
+) -> Result<Vec<JackpotEntry>, sqlx::Error> {
+    let entries = sqlx::query_as!(
+        JackpotEntry,
+        r#"
+        SELECT id, entry_type, amount, input_tx_id, input_vout, player_address, payout_tx_id,
+               created_at
+        FROM jackpot_ledger
+        WHERE entry_type = 'win' AND payout_tx_id IS NULL
+        ORDER BY id ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+pub async fn mark_jackpot_paid(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    payout_tx_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE jackpot_ledger
+        SET payout_tx_id = ?
+        WHERE id = ?
+        "#,
+        payout_tx_id,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_lottery_round(
+    pool: &Pool<Sqlite>,
+    nonce: &str,
+    nonce_hash: &str,
+    ticket_price: i64,
+    rake_bps: i64,
+    starts_at: OffsetDateTime,
+    ends_at: OffsetDateTime,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO lottery_rounds (nonce, nonce_hash, ticket_price, rake_bps, starts_at, ends_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        nonce,
+        nonce_hash,
+        ticket_price,
+        rake_bps,
+        starts_at,
+        ends_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn get_lottery_round(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<Option<LotteryRound>, sqlx::Error> {
+    let round = sqlx::query_as!(
+        LotteryRound,
+        r#"
+        SELECT id, nonce, nonce_hash, ticket_price, rake_bps, starts_at, ends_at, closed_at,
+               drawn_at, tickets_hash, winning_ticket, winner_ticket_id, pot_amount, rake_amount,
+               payout_amount, payout_tx_id, created_at
+        FROM lottery_rounds
+        WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(round)
+}
+
+/// The most recently opened round
+pub async fn get_latest_lottery_round(
+    pool: &Pool<Sqlite>,
+) -> Result<Option<LotteryRound>, sqlx::Error> {
+    let round = sqlx::query_as!(
+        LotteryRound,
+        r#"
+        SELECT id, nonce, nonce_hash, ticket_price, rake_bps, starts_at, ends_at, closed_at,
+               drawn_at, tickets_hash, winning_ticket, winner_ticket_id, pot_amount, rake_amount,
+               payout_amount, payout_tx_id, created_at
+        FROM lottery_rounds
+        ORDER BY id DESC
+        LIMIT 1
+        "#
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(round)
+}
+
+/// The round whose ticket window contains `time`
+pub async fn get_lottery_round_at(
+    pool: &Pool<Sqlite>,
+    time: OffsetDateTime,
+) -> Result<Option<LotteryRound>, sqlx::Error> {
+    let round = sqlx::query_as!(
+        LotteryRound,
+        r#"
+        SELECT id, nonce, nonce_hash, ticket_price, rake_bps, starts_at, ends_at, closed_at,
+               drawn_at, tickets_hash, winning_ticket, winner_ticket_id, pot_amount, rake_amount,
+               payout_amount, payout_tx_id, created_at
+        FROM lottery_rounds
+        WHERE julianday(starts_at) <= julianday(?) AND julianday(ends_at) > julianday(?)
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+        time,
+        time
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(round)
+}
+
+/// Rounds that are not drawn yet and whose ticket window ended before `time`
+pub async fn get_lottery_rounds_ended_before(
+    pool: &Pool<Sqlite>,
+    time: OffsetDateTime,
+) -> Result<Vec<LotteryRound>, sqlx::Error> {
+    let rounds = sqlx::query_as!(
+        LotteryRound,
+        r#"
+        SELECT id, nonce, nonce_hash, ticket_price, rake_bps, starts_at, ends_at, closed_at,
+               drawn_at, tickets_hash, winning_ticket, winner_ticket_id, pot_amount, rake_amount,
+               payout_amount, payout_tx_id, created_at
+        FROM lottery_rounds
+        WHERE drawn_at IS NULL AND julianday(ends_at) <= julianday(?)
+        ORDER BY id ASC
+        "#,
+        time
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rounds)
+}
+
+/// Drawn rounds, newest first
+pub async fn get_drawn_lottery_rounds_paginated(
+    pool: &Pool<Sqlite>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<LotteryRound>, sqlx::Error> {
+    let offset = (page - 1) * page_size;
+
+    let rounds = sqlx::query_as!(
+        LotteryRound,
+        r#"
+        SELECT id, nonce, nonce_hash, ticket_price, rake_bps, starts_at, ends_at, closed_at,
+               drawn_at, tickets_hash, winning_ticket, winner_ticket_id, pot_amount, rake_amount,
+               payout_amount, payout_tx_id, created_at
+        FROM lottery_rounds
+        WHERE drawn_at IS NOT NULL
+        ORDER BY id DESC
+        LIMIT ? OFFSET ?
+        "#,
+        page_size,
+        offset
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rounds)
+}
+
+pub async fn get_total_drawn_lottery_round_count(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as count
+        FROM lottery_rounds
+        WHERE drawn_at IS NOT NULL
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.count)
+}
+
+/// Drawn rounds with a winner whose payout has not gone through yet
+pub async fn get_unpaid_lottery_rounds(
+    pool: &Pool<Sqlite>,
+) -> Result<Vec<LotteryRound>, sqlx::Error> {
+    let rounds = sqlx::query_as!(
+        LotteryRound,
+        r#"
+        SELECT id, nonce, nonce_hash, ticket_price, rake_bps, starts_at, ends_at, closed_at,
+               drawn_at, tickets_hash, winning_ticket, winner_ticket_id, pot_amount, rake_amount,
+               payout_amount, payout_tx_id, created_at
+        FROM lottery_rounds
+        WHERE drawn_at IS NOT NULL AND payout_amount > 0 AND payout_tx_id IS NULL
+        ORDER BY id ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rounds)
+}
+
+/// Stop a round from taking more tickets, so that its tickets are final when it is drawn
+pub async fn close_lottery_round(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE lottery_rounds
+        SET closed_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND closed_at IS NULL
+        "#,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Store the outcome of a closed round. Returns `false` if the round was already drawn.
+#[allow(clippy::too_many_arguments)]
+pub async fn mark_lottery_round_drawn(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    tickets_hash: &str,
+    winning_ticket: Option<i64>,
+    winner_ticket_id: Option<i64>,
+    pot_amount: i64,
+    rake_amount: i64,
+    payout_amount: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE lottery_rounds
+        SET drawn_at = CURRENT_TIMESTAMP, tickets_hash = ?, winning_ticket = ?,
+            winner_ticket_id = ?, pot_amount = ?, rake_amount = ?, payout_amount = ?
+        WHERE id = ? AND closed_at IS NOT NULL AND drawn_at IS NULL
+        "#,
+        tickets_hash,
+        winning_ticket,
+        winner_ticket_id,
+        pot_amount,
+        rake_amount,
+        payout_amount,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn mark_lottery_round_paid(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    payout_tx_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE lottery_rounds
+        SET payout_tx_id = ?
+        WHERE id = ?
+        "#,
+        payout_tx_id,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Add a purchase of `ticket_count` tickets to a round, numbering its tickets after those bought
+/// before. Returns `None` if the round was closed in the meantime.
+pub async fn insert_lottery_ticket(
+    pool: &Pool<Sqlite>,
+    round_id: i64,
+    input_tx_id: &str,
+    input_vout: i64,
+    player_address: &str,
+    amount: i64,
+    ticket_count: i64,
+) -> Result<Option<LotteryTicket>, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO lottery_tickets (
+            round_id, input_tx_id, input_vout, player_address, amount, first_ticket, ticket_count
+        )
+        SELECT r.id, ?, ?, ?, ?,
+               (SELECT COALESCE(SUM(t.ticket_count), 0) FROM lottery_tickets t
+                WHERE t.round_id = r.id),
+               ?
+        FROM lottery_rounds r
+        WHERE r.id = ? AND r.closed_at IS NULL
+        "#,
+        input_tx_id,
+        input_vout,
+        player_address,
+        amount,
+        ticket_count,
+        round_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    let ticket = sqlx::query_as!(
+        LotteryTicket,
+        r#"
+        SELECT id, round_id, input_tx_id, input_vout, player_address, amount, first_ticket,
+               ticket_count, refund_tx_id, created_at
+        FROM lottery_tickets
+        WHERE id = ?
+        "#,
+        result.last_insert_rowid()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(ticket)
+}
+
+/// Record a payment that came in without an open round, it is refunded
+pub async fn insert_lottery_refund(
+    pool: &Pool<Sqlite>,
+    input_tx_id: &str,
+    input_vout: i64,
+    player_address: &str,
+    amount: i64,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO lottery_tickets (
+            round_id, input_tx_id, input_vout, player_address, amount, first_ticket, ticket_count
+        )
+        VALUES (NULL, ?, ?, ?, ?, 0, 0)
+        "#,
+        input_tx_id,
+        input_vout,
+        player_address,
+        amount
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn mark_lottery_refund_paid(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    refund_tx_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE lottery_tickets
+        SET refund_tx_id = ?
+        WHERE id = ?
+        "#,
+        refund_tx_id,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Payments without a round whose refund has not gone through yet
+pub async fn get_unpaid_lottery_refunds(
+    pool: &Pool<Sqlite>,
+) -> Result<Vec<LotteryTicket>, sqlx::Error> {
+    let tickets = sqlx::query_as!(
+        LotteryTicket,
+        r#"
+        SELECT id, round_id, input_tx_id, input_vout, player_address, amount, first_ticket,
+               ticket_count, refund_tx_id, created_at
+        FROM lottery_tickets
+        WHERE round_id IS NULL AND refund_tx_id IS NULL
+        ORDER BY id ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tickets)
+}
+
+/// Every purchase of a round in the order they were bought
+pub async fn get_lottery_tickets(
+    pool: &Pool<Sqlite>,
+    round_id: i64,
+) -> Result<Vec<LotteryTicket>, sqlx::Error> {
+    let tickets = sqlx::query_as!(
+        LotteryTicket,
+        r#"
+        SELECT id, round_id, input_tx_id, input_vout, player_address, amount, first_ticket,
+               ticket_count, refund_tx_id, created_at
+        FROM lottery_tickets
+        WHERE round_id = ?
+        ORDER BY id ASC
+        "#,
+        round_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tickets)
+}
+
+pub async fn is_lottery_outpoint_processed(
+    pool: &Pool<Sqlite>,
+    input_tx_id: &str,
+    input_vout: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as count
+        FROM lottery_tickets
+        WHERE input_tx_id = ? AND input_vout = ?
+        "#,
+        input_tx_id,
+        input_vout
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.count > 0)
+}
+
+/// Open a challenge that waits for an opponent until `expires_at`
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_duel(
+    pool: &Pool<Sqlite>,
+    stake_amount: i64,
+    rake_bps: i64,
+    challenger_address: &str,
+    challenger_tx_id: &str,
+    challenger_vout: i64,
+    expires_at: OffsetDateTime,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO duels (
+            stake_amount, rake_bps, challenger_address, challenger_tx_id, challenger_vout,
+            expires_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        stake_amount,
+        rake_bps,
+        challenger_address,
+        challenger_tx_id,
+        challenger_vout,
+        expires_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Store a stake that cannot open a challenge as an expired duel, so that it is refunded like a
+/// challenge nobody matched
+pub async fn insert_expired_duel(
+    pool: &Pool<Sqlite>,
+    stake_amount: i64,
+    rake_bps: i64,
+    challenger_address: &str,
+    challenger_tx_id: &str,
+    challenger_vout: i64,
+    expires_at: OffsetDateTime,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO duels (
+            status, stake_amount, rake_bps, challenger_address, challenger_tx_id, challenger_vout,
+            expires_at
+        )
+        VALUES ('expired', ?, ?, ?, ?, ?, ?)
+        "#,
+        stake_amount,
+        rake_bps,
+        challenger_address,
+        challenger_tx_id,
+        challenger_vout,
+        expires_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn get_duel(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Duel>, sqlx::Error> {
+    let duel = sqlx::query_as!(
+        Duel,
+        r#"
+        SELECT id, status, stake_amount, rake_bps, challenger_address, challenger_tx_id,
+               challenger_vout, expires_at, opponent_address, opponent_tx_id, opponent_vout, nonce,
+               nonce_hash, winner_address, rake_amount, payout_amount, matched_at, payout_tx_id,
+               settled_at, created_at
+        FROM duels
+        WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(duel)
+}
+
+/// The oldest open challenge over `stake_amount` that was still open at `time` and that
+/// `player_address` did not open themselves
+pub async fn find_open_duel(
+    pool: &Pool<Sqlite>,
+    stake_amount: i64,
+    player_address: &str,
+    time: OffsetDateTime,
+) -> Result<Option<Duel>, sqlx::Error> {
+    let duel = sqlx::query_as!(
+        Duel,
+        r#"
+        SELECT id, status, stake_amount, rake_bps, challenger_address, challenger_tx_id,
+               challenger_vout, expires_at, opponent_address, opponent_tx_id, opponent_vout, nonce,
+               nonce_hash, winner_address, rake_amount, payout_amount, matched_at, payout_tx_id,
+               settled_at, created_at
+        FROM duels
+        WHERE status = 'open' AND stake_amount = ? AND challenger_address != ?
+          AND julianday(expires_at) > julianday(?)
+        ORDER BY id ASC
+        LIMIT 1
+        "#,
+        stake_amount,
+        player_address,
+        time
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(duel)
+}
+
+/// Match an open challenge against the opponent's stake and store its winner. Returns `false` if
+/// the challenge is no longer open.
+#[allow(clippy::too_many_arguments)]
+pub async fn match_duel(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    opponent_address: &str,
+    opponent_tx_id: &str,
+    opponent_vout: i64,
+    nonce: &str,
+    nonce_hash: &str,
+    winner_address: &str,
+    rake_amount: i64,
+    payout_amount: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE duels
+        SET status = 'matched', opponent_address = ?, opponent_tx_id = ?, opponent_vout = ?,
+            nonce = ?, nonce_hash = ?, winner_address = ?, rake_amount = ?, payout_amount = ?,
+            matched_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND status = 'open'
+        "#,
+        opponent_address,
+        opponent_tx_id,
+        opponent_vout,
+        nonce,
+        nonce_hash,
+        winner_address,
+        rake_amount,
+        payout_amount,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Expire every challenge that stopped taking opponents before `time`, returns how many expired
+pub async fn expire_duels(pool: &Pool<Sqlite>, time: OffsetDateTime) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE duels
+        SET status = 'expired'
+        WHERE status = 'open' AND julianday(expires_at) <= julianday(?)
+        "#,
+        time
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Matched duels whose winner was not paid and expired ones that were not refunded yet
+pub async fn get_unsettled_duels(pool: &Pool<Sqlite>) -> Result<Vec<Duel>, sqlx::Error> {
+    let duels = sqlx::query_as!(
+        Duel,
+        r#"
+        SELECT id, status, stake_amount, rake_bps, challenger_address, challenger_tx_id,
+               challenger_vout, expires_at, opponent_address, opponent_tx_id, opponent_vout, nonce,
+               nonce_hash, winner_address, rake_amount, payout_amount, matched_at, payout_tx_id,
+               settled_at, created_at
+        FROM duels
+        WHERE status IN ('matched', 'expired')
+        ORDER BY id ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(duels)
+}
+
+/// Settle a matched or expired duel once its payout or refund went through. Returns `false` if it
+/// was already settled.
+pub async fn mark_duel_settled(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    payout_tx_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE duels
+        SET status = CASE status WHEN 'matched' THEN 'settled' ELSE 'refunded' END,
+            payout_tx_id = ?, settled_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND status IN ('matched', 'expired')
+        "#,
+        payout_tx_id,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Challenges waiting for an opponent, oldest first
+pub async fn get_open_duels(pool: &Pool<Sqlite>) -> Result<Vec<Duel>, sqlx::Error> {
+    let duels = sqlx::query_as!(
+        Duel,
+        r#"
+        SELECT id, status, stake_amount, rake_bps, challenger_address, challenger_tx_id,
+               challenger_vout, expires_at, opponent_address, opponent_tx_id, opponent_vout, nonce,
+               nonce_hash, winner_address, rake_amount, payout_amount, matched_at, payout_tx_id,
+               settled_at, created_at
+        FROM duels
+        WHERE status = 'open'
+        ORDER BY id ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(duels)
+}
+
+/// Every duel, newest first
+pub async fn get_duels_paginated(
+    pool: &Pool<Sqlite>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<Duel>, sqlx::Error> {
+    let offset = (page - 1) * page_size;
+
+    let duels = sqlx::query_as!(
+        Duel,
+        r#"
+        SELECT id, status, stake_amount, rake_bps, challenger_address, challenger_tx_id,
+               challenger_vout, expires_at, opponent_address, opponent_tx_id, opponent_vout, nonce,
+               nonce_hash, winner_address, rake_amount, payout_amount, matched_at, payout_tx_id,
+               settled_at, created_at
+        FROM duels
+        ORDER BY id DESC
+        LIMIT ? OFFSET ?
+        "#,
+        page_size,
+        offset
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(duels)
+}
+
+pub async fn get_total_duel_count(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as count
+        FROM duels
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.count)
+}
+
+/// Whether a payment to the duel address was already taken as a challenger's or opponent's stake
+pub async fn is_duel_outpoint_processed(
+    pool: &Pool<Sqlite>,
+    input_tx_id: &str,
+    input_vout: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as count
+        FROM duels
+        WHERE (challenger_tx_id = ? AND challenger_vout = ?)
+           OR (opponent_tx_id = ? AND opponent_vout = ?)
+        "#,
+        input_tx_id,
+        input_vout,
+        input_tx_id,
+        input_vout
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.count > 0)
+}
+
+/// Plan a payout for a source unless it already has one that did not fail, returns the payout the
+/// source has now
+pub async fn plan_payout(
+    pool: &Pool<Sqlite>,
+    source: &str,
+    source_id: i64,
+    transaction_type: &str,
+    address: &str,
+    amount: i64,
+) -> Result<Payout, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO payouts (source, source_id, transaction_type, address, amount)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+        source,
+        source_id,
+        transaction_type,
+        address,
+        amount
+    )
+    .execute(pool)
+    .await?;
+
+    let payout = sqlx::query_as!(
+        Payout,
+        r#"
+        SELECT id, source, source_id, transaction_type, address, amount, status, ark_txid, vout,
+               inputs, error, created_at, updated_at
+        FROM payouts
+        WHERE source = ? AND source_id = ? AND status != 'failed'
+        "#,
+        source,
+        source_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(payout)
+}
+
+/// Record the Ark transaction of a planned payout and the output that pays it, returns false if it
+/// was not planned anymore
+pub async fn mark_payout_signed(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    ark_txid: &str,
+    vout: i64,
+    inputs: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE payouts
+        SET status = 'signed', ark_txid = ?, vout = ?, inputs = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND status = 'planned'
+        "#,
+        ark_txid,
+        vout,
+        inputs,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Mark every signed payout of an Ark transaction submitted, returns how many were
+pub async fn mark_payouts_submitted(
+    pool: &Pool<Sqlite>,
+    ark_txid: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE payouts
+        SET status = 'submitted', updated_at = CURRENT_TIMESTAMP
+        WHERE ark_txid = ? AND status = 'signed'
+        "#,
+        ark_txid
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Mark every submitted payout of an Ark transaction finalized, returns how many were
+pub async fn mark_payouts_finalized(
+    pool: &Pool<Sqlite>,
+    ark_txid: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE payouts
+        SET status = 'finalized', updated_at = CURRENT_TIMESTAMP
+        WHERE ark_txid = ? AND status = 'submitted'
+        "#,
+        ark_txid
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Give up on a planned payout, its source may be paid again afterwards
+pub async fn mark_payout_failed(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    error: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE payouts
+        SET status = 'failed', error = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND status = 'planned'
+        "#,
+        error,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Give up on every payout of an Ark transaction that was never finalized, their sources may be
+/// paid again afterwards. Returns how many were given up on.
+pub async fn mark_payouts_failed(
+    pool: &Pool<Sqlite>,
+    ark_txid: &str,
+    error: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE payouts
+        SET status = 'failed', error = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE ark_txid = ? AND status IN ('signed', 'submitted')
+        "#,
+        error,
+        ark_txid
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Payouts that were signed or submitted but not finalized, oldest first
+pub async fn get_in_flight_payouts(pool: &Pool<Sqlite>) -> Result<Vec<Payout>, sqlx::Error> {
+    let payouts = sqlx::query_as!(
+        Payout,
+        r#"
+        SELECT id, source, source_id, transaction_type, address, amount, status, ark_txid, vout,
+               inputs, error, created_at, updated_at
+        FROM payouts
+        WHERE status IN ('signed', 'submitted')
+        ORDER BY id ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(payouts)
+}
+
+/// Every payout paid by the Ark transaction `ark_txid`, in output order
+pub async fn get_payouts_by_ark_txid(
+    pool: &Pool<Sqlite>,
+    ark_txid: &str,
+) -> Result<Vec<Payout>, sqlx::Error> {
+    let payouts = sqlx::query_as!(
+        Payout,
+        r#"
+        SELECT id, source, source_id, transaction_type, address, amount, status, ark_txid, vout,
+               inputs, error, created_at, updated_at
+        FROM payouts
+        WHERE ark_txid = ?
+        ORDER BY vout ASC
+        "#,
+        ark_txid
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(payouts)
+}
+
+/// Reserve `outpoints` for the payout transaction `ark_txid`. Returns false and reserves nothing
+/// if any of them is reserved by another transaction already.
+pub async fn reserve_vtxos(
+    pool: &Pool<Sqlite>,
+    ark_txid: &str,
+    outpoints: &[String],
+) -> Result<bool, sqlx::Error> {
+    for outpoint in outpoints {
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO vtxo_reservations (outpoint, ark_txid)
+            VALUES (?, ?)
+            "#,
+            outpoint,
+            ark_txid
+        )
+        .execute(pool)
+        .await?;
+
+        let reserved_by = sqlx::query_scalar!(
+            r#"
+            SELECT ark_txid FROM vtxo_reservations WHERE outpoint = ?
+            "#,
+            outpoint
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if reserved_by != ark_txid {
+            release_vtxos(pool, ark_txid).await?;
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Release the VTXOs reserved for the payout transaction `ark_txid`
+pub async fn release_vtxos(pool: &Pool<Sqlite>, ark_txid: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM vtxo_reservations WHERE ark_txid = ?
+        "#,
+        ark_txid
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Release the reservations made before `created_before` for a transaction that no payout was
+/// signed with, left behind by a crash between reserving VTXOs and signing. Returns how many were
+/// released.
+pub async fn release_orphaned_vtxos(
+    pool: &Pool<Sqlite>,
+    created_before: OffsetDateTime,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM vtxo_reservations
+        WHERE julianday(created_at) <= julianday(?)
+          AND ark_txid NOT IN (SELECT ark_txid FROM payouts WHERE ark_txid IS NOT NULL)
+        "#,
+        created_before
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Outpoints of every VTXO reserved for a payout transaction
+pub async fn get_reserved_vtxos(pool: &Pool<Sqlite>) -> Result<Vec<String>, sqlx::Error> {
+    let outpoints = sqlx::query_scalar!(
+        r#"
+        SELECT outpoint FROM vtxo_reservations ORDER BY created_at ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(outpoints)
+}
@@ -1,11 +1,14 @@
+pub mod audit;
+pub mod bet_intents;
 pub mod client;
 pub mod config;
 pub mod db;
 pub mod esplora;
-pub mod games;
 pub mod key_derivation;
 pub mod logger;
+pub mod lottery_service;
 pub mod nonce_service;
+pub mod payouts;
 pub mod recovery;
 pub mod server;
 pub mod telegram;
@@ -15,3 +18,8 @@ pub mod websocket;
 pub use client::ArkClient;
 pub use config::Config;
 pub use esplora::EsploraClient;
+pub use satoshi_dice_core::duel;
+pub use satoshi_dice_core::games;
+pub use satoshi_dice_core::jackpot;
+pub use satoshi_dice_core::lottery;
+pub use satoshi_dice_core::multiplier;
@@ -0,0 +1,453 @@
+use crate::db;
+use crate::lottery;
+use crate::lottery::LotterySettings;
+use crate::nonce_service::hash_nonce;
+use crate::payouts::PayoutExecutor;
+use crate::payouts::PayoutSource;
+use crate::server::LotteryRoundItem;
+use crate::server::LotteryTicketItem;
+use crate::websocket::SharedBroadcaster;
+use crate::ArkClient;
+use anyhow::Context;
+use anyhow::Result;
+use ark_core::ArkAddress;
+use bitcoin::Amount;
+use bitcoin::OutPoint;
+use rand::random;
+use sqlx::Pool;
+use sqlx::Sqlite;
+use std::sync::Arc;
+use time::Duration as TimeDuration;
+use time::OffsetDateTime;
+use tokio::time::interval;
+use tokio::time::Duration;
+
+/// How often rounds are opened, drawn and paid out
+const TICK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long a round keeps taking tickets after its window ended, so that a VTXO the Ark server
+/// timestamped just before the end still gets its tickets
+const DRAW_DELAY: TimeDuration = TimeDuration::minutes(1);
+
+/// Runs the scheduled lottery: opens a round with a committed nonce, sells tickets for payments
+/// to the lottery address while it is open, then draws the winner and pays out the pot less the
+/// rake.
+///
+/// Payouts and refunds are only sent from the round timer, so a failed one is simply retried on
+/// the next tick and nothing is ever sent twice at the same time.
+#[derive(Clone)]
+pub struct LotteryService {
+    db_pool: Pool<Sqlite>,
+    ark_client: Arc<ArkClient>,
+    payouts: PayoutExecutor,
+    broadcaster: SharedBroadcaster,
+    settings: LotterySettings,
+    round_duration: TimeDuration,
+}
+
+impl LotteryService {
+    pub fn new(
+        db_pool: Pool<Sqlite>,
+        ark_client: Arc<ArkClient>,
+        payouts: PayoutExecutor,
+        broadcaster: SharedBroadcaster,
+        settings: LotterySettings,
+        round_minutes: u64,
+    ) -> Self {
+        Self {
+            db_pool,
+            ark_client,
+            payouts,
+            broadcaster,
+            settings,
+            round_duration: TimeDuration::minutes(round_minutes as i64),
+        }
+    }
+
+    pub fn address(&self) -> ArkAddress {
+        self.ark_client.get_lottery_address()
+    }
+
+    /// Buy tickets with a payment of `amount` sats whose VTXO was created at `created_at`. A
+    /// payment without an open round is recorded for a refund.
+    pub async fn buy_tickets(
+        &self,
+        outpoint: OutPoint,
+        player: ArkAddress,
+        amount: u64,
+        created_at: OffsetDateTime,
+    ) -> Result<()> {
+        let input_tx_id = outpoint.txid.to_string();
+        let player = player.encode();
+
+        let ticket = match db::get_lottery_round_at(&self.db_pool, created_at).await? {
+            Some(round) => {
+                let ticket_count = round_settings(&round).tickets(amount);
+                db::insert_lottery_ticket(
+                    &self.db_pool,
+                    round.id,
+                    &input_tx_id,
+                    outpoint.vout as i64,
+                    &player,
+                    amount as i64,
+                    ticket_count as i64,
+                )
+                .await?
+            }
+            None => None,
+        };
+
+        let Some(ticket) = ticket else {
+            tracing::warn!(
+                %outpoint,
+                %created_at,
+                player,
+                amount,
+                "⚠️ Lottery payment without an open round, refunding it"
+            );
+            db::insert_lottery_refund(
+                &self.db_pool,
+                &input_tx_id,
+                outpoint.vout as i64,
+                &player,
+                amount as i64,
+            )
+            .await
+            .context("Failed to store lottery refund")?;
+            return Ok(());
+        };
+
+        tracing::info!(
+            %outpoint,
+            round_id = ticket.round_id,
+            player,
+            amount,
+            ticket_count = ticket.ticket_count,
+            "🎟️ Lottery tickets bought"
+        );
+
+        let broadcaster = self.broadcaster.read().await;
+        if let Err(e) = broadcaster.broadcast_lottery_ticket(ticket_item(&ticket)) {
+            tracing::error!("Failed to broadcast lottery ticket: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// A round as shown to players, its nonce is only included once it was drawn
+    pub fn round_item(
+        &self,
+        round: &db::LotteryRound,
+        tickets: &[db::LotteryTicket],
+    ) -> LotteryRoundItem {
+        let status = match (round.drawn_at, round.closed_at) {
+            (Some(_), _) => "drawn",
+            (None, Some(_)) => "closed",
+            (None, None) if round.ends_at <= OffsetDateTime::now_utc() => "closed",
+            (None, None) => "open",
+        };
+        let pot = round
+            .pot_amount
+            .unwrap_or_else(|| tickets.iter().map(|ticket| ticket.amount).sum());
+
+        LotteryRoundItem {
+            id: round.id,
+            status,
+            address: self.address().encode(),
+            nonce_hash: round.nonce_hash.clone(),
+            nonce: round.drawn_at.map(|_| round.nonce.clone()),
+            ticket_price: Amount::from_sat(round.ticket_price as u64),
+            rake_bps: round.rake_bps,
+            starts_at: round.starts_at,
+            ends_at: round.ends_at,
+            drawn_at: round.drawn_at,
+            pot: Amount::from_sat(pot.max(0) as u64),
+            ticket_count: tickets.iter().map(|ticket| ticket.ticket_count).sum(),
+            purchase_count: tickets.len(),
+            tickets_hash: round.tickets_hash.clone(),
+            winning_ticket: round.winning_ticket,
+            winner: round
+                .winner_ticket_id
+                .and_then(|id| tickets.iter().find(|ticket| ticket.id == id))
+                .map(ticket_item),
+            payout: round
+                .payout_amount
+                .map(|payout| Amount::from_sat(payout.max(0) as u64)),
+            payout_tx_id: round.payout_tx_id.clone(),
+        }
+    }
+
+    /// Open the next round if the last one ended. Rounds follow each other without a gap unless
+    /// the service was down for longer than a round.
+    async fn open_round(&self, now: OffsetDateTime) -> Result<()> {
+        let starts_at = match db::get_latest_lottery_round(&self.db_pool).await? {
+            Some(round) if round.ends_at > now => return Ok(()),
+            Some(round) if round.ends_at + self.round_duration > now => round.ends_at,
+            _ => now,
+        };
+        let ends_at = starts_at + self.round_duration;
+
+        let nonce = hex::encode(random::<[u8; 32]>());
+        let nonce_hash = hash_nonce(&nonce);
+        let round_id = db::insert_lottery_round(
+            &self.db_pool,
+            &nonce,
+            &nonce_hash,
+            self.settings.ticket_price_sats as i64,
+            self.settings.rake_bps as i64,
+            starts_at,
+            ends_at,
+        )
+        .await
+        .context("Failed to store lottery round")?;
+
+        tracing::info!(
+            round_id,
+            nonce_hash,
+            %starts_at,
+            %ends_at,
+            "🎟️ Opened lottery round"
+        );
+
+        Ok(())
+    }
+
+    /// Close a round, draw its winner from the committed nonce and the hash of its tickets, and
+    /// pay the winner
+    async fn draw_round(&self, round: db::LotteryRound) -> Result<()> {
+        db::close_lottery_round(&self.db_pool, round.id).await?;
+        let tickets = db::get_lottery_tickets(&self.db_pool, round.id).await?;
+
+        let tickets_hash = lottery::tickets_hash(
+            tickets
+                .iter()
+                .map(|ticket| (ticket.input_tx_id.as_str(), ticket.input_vout as u32)),
+        );
+        let total_tickets: u64 = tickets
+            .iter()
+            .map(|ticket| ticket.ticket_count as u64)
+            .sum();
+        let pot: u64 = tickets.iter().map(|ticket| ticket.amount as u64).sum();
+
+        let draw = lottery::draw(&round.nonce, &tickets_hash, total_tickets);
+        let winner = draw.as_ref().and_then(|draw| {
+            let index = lottery::winning_purchase(
+                tickets.iter().map(|ticket| ticket.ticket_count as u64),
+                draw.winning_ticket,
+            )?;
+            tickets.get(index)
+        });
+        // Without a single ticket there is no one to pay, payments below the ticket price stay
+        // with the house
+        let payout = match winner {
+            Some(_) => round_settings(&round).payout(pot),
+            None => 0,
+        };
+
+        let drawn = db::mark_lottery_round_drawn(
+            &self.db_pool,
+            round.id,
+            &tickets_hash,
+            draw.as_ref().map(|draw| draw.winning_ticket as i64),
+            winner.map(|ticket| ticket.id),
+            pot as i64,
+            (pot - payout) as i64,
+            payout as i64,
+        )
+        .await?;
+        if !drawn {
+            return Ok(());
+        }
+
+        tracing::info!(
+            round_id = round.id,
+            tickets_hash,
+            total_tickets,
+            pot,
+            payout,
+            winner = winner.map(|ticket| ticket.player_address.as_str()),
+            "🎉 Lottery round drawn"
+        );
+
+        let round = db::get_lottery_round(&self.db_pool, round.id)
+            .await?
+            .context("Lottery round missing after draw")?;
+        let round = match winner {
+            Some(winner) => self.pay_round(round, winner).await?,
+            None => round,
+        };
+
+        let broadcaster = self.broadcaster.read().await;
+        if let Err(e) = broadcaster.broadcast_lottery_draw(self.round_item(&round, &tickets)) {
+            tracing::error!("Failed to broadcast lottery draw: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Pay the pot of a drawn round to its winner, returns the round with the payout recorded if
+    /// it went through
+    async fn pay_round(
+        &self,
+        round: db::LotteryRound,
+        winner: &db::LotteryTicket,
+    ) -> Result<db::LotteryRound> {
+        let amount = Amount::from_sat(round.payout_amount.unwrap_or_default().max(0) as u64);
+        let address = ArkAddress::decode(&winner.player_address)?;
+
+        let payout_tx_id = match self
+            .send(
+                PayoutSource::LotteryRound(round.id),
+                address,
+                amount,
+                "lottery_payout",
+            )
+            .await
+        {
+            Ok(payout_tx_id) => payout_tx_id,
+            Err(e) => {
+                tracing::error!(
+                    round_id = round.id,
+                    payout = amount.to_sat(),
+                    "🚨 Failed to pay lottery winner, retrying on the next tick {e:#}"
+                );
+                return Ok(round);
+            }
+        };
+
+        db::mark_lottery_round_paid(&self.db_pool, round.id, &payout_tx_id).await?;
+
+        tracing::info!(
+            round_id = round.id,
+            payout = amount.to_sat(),
+            winner = winner.player_address,
+            payout_tx_id,
+            "💸 Lottery payout sent"
+        );
+
+        Ok(db::LotteryRound {
+            payout_tx_id: Some(payout_tx_id),
+            ..round
+        })
+    }
+
+    /// Retry payouts and refunds that did not go through
+    async fn retry_unpaid(&self) -> Result<()> {
+        for round in db::get_unpaid_lottery_rounds(&self.db_pool).await? {
+            let tickets = db::get_lottery_tickets(&self.db_pool, round.id).await?;
+            let Some(winner) = round
+                .winner_ticket_id
+                .and_then(|id| tickets.iter().find(|ticket| ticket.id == id))
+            else {
+                tracing::error!(round_id = round.id, "Winner of lottery round is missing");
+                continue;
+            };
+
+            self.pay_round(round.clone(), winner).await?;
+        }
+
+        for refund in db::get_unpaid_lottery_refunds(&self.db_pool).await? {
+            let address = ArkAddress::decode(&refund.player_address)?;
+            let amount = Amount::from_sat(refund.amount.max(0) as u64);
+
+            match self
+                .send(
+                    PayoutSource::LotteryRefund(refund.id),
+                    address,
+                    amount,
+                    "refund",
+                )
+                .await
+            {
+                Ok(refund_tx_id) => {
+                    db::mark_lottery_refund_paid(&self.db_pool, refund.id, &refund_tx_id).await?;
+                    tracing::info!(
+                        amount = amount.to_sat(),
+                        refund_tx_id,
+                        "↩️ Lottery payment refunded"
+                    );
+                }
+                Err(e) => {
+                    tracing::error!(
+                        amount = amount.to_sat(),
+                        "🚨 Failed to refund lottery payment, retrying on the next tick {e:#}"
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send `amount` to `address` for `source` through the payout outbox
+    async fn send(
+        &self,
+        source: PayoutSource,
+        address: ArkAddress,
+        amount: Amount,
+        transaction_type: &str,
+    ) -> Result<String> {
+        self.payouts
+            .pay(source, address, amount, transaction_type)
+            .await
+            .map(|paid| paid.txid)
+    }
+
+    async fn tick(&self) {
+        let now = OffsetDateTime::now_utc();
+
+        match db::get_lottery_rounds_ended_before(&self.db_pool, now - DRAW_DELAY).await {
+            Ok(rounds) => {
+                for round in rounds {
+                    let round_id = round.id;
+                    if let Err(e) = self.draw_round(round).await {
+                        tracing::error!(round_id, "Failed to draw lottery round: {:#}", e);
+                    }
+                }
+            }
+            Err(e) => tracing::error!("Failed to load lottery rounds to draw: {}", e),
+        }
+
+        if let Err(e) = self.open_round(now).await {
+            tracing::error!("Failed to open lottery round: {:#}", e);
+        }
+
+        if let Err(e) = self.retry_unpaid().await {
+            tracing::error!("Failed to retry lottery payouts: {:#}", e);
+        }
+    }
+
+    pub fn start(&self) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut timer = interval(TICK_INTERVAL);
+
+            loop {
+                timer.tick().await;
+                service.tick().await;
+            }
+        });
+    }
+}
+
+/// The settings a round was opened with, later config changes only apply to new rounds
+fn round_settings(round: &db::LotteryRound) -> LotterySettings {
+    LotterySettings {
+        ticket_price_sats: round.ticket_price.max(0) as u64,
+        rake_bps: u16::try_from(round.rake_bps).unwrap_or(10_000),
+    }
+}
+
+pub fn ticket_item(ticket: &db::LotteryTicket) -> LotteryTicketItem {
+    LotteryTicketItem {
+        round_id: ticket.round_id,
+        player: ticket.player_address.clone(),
+        input_tx_id: ticket.input_tx_id.clone(),
+        input_vout: ticket.input_vout,
+        amount: Amount::from_sat(ticket.amount.max(0) as u64),
+        first_ticket: ticket.first_ticket,
+        ticket_count: ticket.ticket_count,
+        timestamp: ticket.created_at,
+    }
+}
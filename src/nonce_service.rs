@@ -1,101 +1,311 @@
 use crate::db;
+use anyhow::Context;
+use anyhow::Result;
 use rand::random;
 use rand::Rng;
-use sha2::Digest;
-use sha2::Sha256;
+use serde::Deserialize;
 use sqlx::Pool;
 use sqlx::Sqlite;
 use std::sync::Arc;
 use time::Duration as TimeDuration;
 use time::OffsetDateTime;
 use tokio::sync::RwLock;
-use tokio::time::interval;
+use tokio::time::interval_at;
+use tokio::time::sleep;
 use tokio::time::Duration;
+use tokio::time::Instant;
+
+pub use satoshi_dice_core::nonce::chain_nonce;
+pub use satoshi_dice_core::nonce::hash_nonce;
+
+/// How new nonces are generated
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NonceMode {
+    /// Every nonce is an independent random number
+    #[default]
+    Random,
+    /// Nonces are taken in reverse order from a pre-committed SHA256 hash chain
+    HashChain,
+}
+
+/// The nonce that new games are currently evaluated against
+#[derive(Debug, Clone)]
+pub struct ActiveNonce {
+    pub nonce: String,
+    /// Position in the hash chain, if the nonce was taken from one
+    pub chain_position: Option<i64>,
+    /// When games started being evaluated against this nonce
+    pub activated_at: OffsetDateTime,
+}
+
+impl From<db::Nonce> for ActiveNonce {
+    fn from(nonce: db::Nonce) -> Self {
+        Self {
+            nonce: nonce.nonce,
+            chain_position: nonce.chain_position,
+            activated_at: nonce.activated_at.unwrap_or(nonce.created_at),
+        }
+    }
+}
+
+/// How long a retired nonce is held back before it is revealed, so that a VTXO the Ark server
+/// timestamped just before a rotation can still be played. Bets bound to the nonce that are not
+/// processed by then are refunded.
+const REVEAL_DELAY: TimeDuration = TimeDuration::minutes(1);
+
+/// Whether `nonce` was retired at least [`REVEAL_DELAY`] before `now`. From then on it is revealed,
+/// and bets bound to it are no longer played.
+fn is_revealable(nonce: &db::Nonce, now: OffsetDateTime) -> bool {
+    nonce
+        .retired_at
+        .is_some_and(|retired_at| now >= retired_at + REVEAL_DELAY)
+}
 
 #[derive(Clone)]
 pub struct NonceService {
-    current_nonce: Arc<RwLock<u64>>,
+    current_nonce: Arc<RwLock<ActiveNonce>>,
     db_pool: Pool<Sqlite>,
+    /// How often the active nonce is rotated
+    interval_hours: u64,
     validity_hours: u64,
+    mode: NonceMode,
+    chain_length: u32,
 }
 
 impl NonceService {
-    pub fn new(db_pool: Pool<Sqlite>, validity_hours: u64) -> Self {
-        let initial_nonce = rand::thread_rng().r#gen::<u64>();
-        Self {
-            current_nonce: Arc::new(RwLock::new(initial_nonce)),
+    pub async fn new(
+        db_pool: Pool<Sqlite>,
+        interval_hours: u64,
+        validity_hours: u64,
+        mode: NonceMode,
+        chain_length: u32,
+    ) -> Result<Self> {
+        let service = Self {
+            current_nonce: Arc::new(RwLock::new(ActiveNonce {
+                nonce: String::new(),
+                chain_position: None,
+                activated_at: OffsetDateTime::now_utc(),
+            })),
             db_pool,
+            interval_hours,
             validity_hours,
+            mode,
+            chain_length,
+        };
+
+        // Pick up where we left off, so games in flight across a restart keep their nonce
+        match db::get_active_nonce(&service.db_pool).await? {
+            Some(nonce) => {
+                tracing::info!(
+                    chain_position = nonce.chain_position,
+                    "♻️ Resuming nonce: {} (expires at {})",
+                    nonce.nonce,
+                    nonce.expires_at
+                );
+
+                *service.current_nonce.write().await = nonce.into();
+            }
+            None => service.rotate().await?,
         }
+
+        // Nonces whose reveal delay passed while we were down
+        service.reveal_due_nonces().await?;
+
+        Ok(service)
     }
 
-    pub async fn get_current_nonce(&self) -> u64 {
-        *self.current_nonce.read().await
+    pub async fn get_current_nonce(&self) -> ActiveNonce {
+        self.current_nonce.read().await.clone()
     }
 
     pub async fn get_current_nonce_hash(&self) -> String {
         let nonce = self.get_current_nonce().await;
-        let mut hasher = Sha256::new();
-        hasher.update(nonce.to_string());
-        format!("{:x}", hasher.finalize())
+        hash_nonce(&nonce.nonce)
+    }
+
+    /// The nonce that was active at `time`, e.g. when the Ark server created a VTXO.
+    ///
+    /// Returns `None` if no nonce was active, or if that nonce may already have been revealed: a
+    /// bet only processed by then, e.g. during catch-up, could have been placed knowing its
+    /// outcome and has to be refunded instead.
+    pub async fn get_nonce_at(&self, time: OffsetDateTime) -> Result<Option<ActiveNonce>> {
+        // Wait for a rotation in progress so its new nonce is already stored
+        let _current_nonce = self.current_nonce.read().await;
+
+        let Some(nonce) = db::get_nonce_active_at(&self.db_pool, time).await? else {
+            return Ok(None);
+        };
+
+        if is_revealable(&nonce, OffsetDateTime::now_utc()) {
+            tracing::warn!(
+                %time,
+                nonce_hash = nonce.nonce_hash,
+                "Nonce bound to a bet has already been revealed"
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(nonce.into()))
     }
 
     pub async fn verify_nonce(&self, nonce: &str) -> Result<bool, sqlx::Error> {
         db::is_nonce_valid(&self.db_pool, nonce).await
     }
 
-    // Returns the actual nonce if it's safe to reveal (not the current one), otherwise returns None
+    /// The nonce itself if it has been revealed, otherwise `None`
     pub async fn get_revealable_nonce(&self, nonce_str: &str) -> Option<String> {
-        let current_nonce = self.get_current_nonce().await;
-        let nonce_u64 = nonce_str.parse::<u64>().ok()?;
-
-        // Only reveal if it's not the current nonce
-        if nonce_u64 != current_nonce {
-            Some(nonce_str.to_string())
-        } else {
-            None
+        let nonce = match db::get_nonce(&self.db_pool, nonce_str).await {
+            Ok(nonce) => nonce?,
+            Err(e) => {
+                tracing::error!("Failed to look up nonce for reveal: {}", e);
+                return None;
+            }
+        };
+
+        nonce.revealed_at.map(|_| nonce.nonce)
+    }
+
+    /// When `nonce` is going to be revealed, [`REVEAL_DELAY`] after it is rotated out. The active
+    /// nonce is rotated one interval after it was activated.
+    pub fn expected_reveal_at(&self, nonce: &db::Nonce) -> OffsetDateTime {
+        let retired_at = nonce.retired_at.unwrap_or_else(|| {
+            let activated_at = nonce.activated_at.unwrap_or(nonce.created_at);
+            activated_at + TimeDuration::hours(self.interval_hours as i64)
+        });
+
+        retired_at + REVEAL_DELAY
+    }
+
+    /// Reveal every retired nonce whose [`REVEAL_DELAY`] has passed
+    async fn reveal_due_nonces(&self) -> Result<()> {
+        let now = OffsetDateTime::now_utc();
+        let revealed = db::reveal_retired_nonces(&self.db_pool, now - REVEAL_DELAY, now)
+            .await
+            .context("Failed to reveal retired nonces")?;
+
+        if revealed > 0 {
+            tracing::info!(revealed, "🔓 Revealed retired nonces");
+        }
+
+        Ok(())
+    }
+
+    /// Draw the next nonce according to the configured [`NonceMode`]
+    async fn next_nonce(&self) -> Result<ActiveNonce> {
+        match self.mode {
+            NonceMode::Random => Ok(ActiveNonce {
+                nonce: rand::thread_rng().r#gen::<u64>().to_string(),
+                chain_position: None,
+                activated_at: OffsetDateTime::now_utc(),
+            }),
+            NonceMode::HashChain => self.next_chain_nonce().await,
         }
     }
 
-    pub async fn start_periodic_generation(&self, interval_hours: u64) {
-        let nonce_arc = self.current_nonce.clone();
-        let db_pool = self.db_pool.clone();
-        let validity_hours = self.validity_hours;
+    /// Take the next unused nonce of the active hash chain, starting a new chain if the active one
+    /// is used up
+    async fn next_chain_nonce(&self) -> Result<ActiveNonce> {
+        let chain = match db::get_active_nonce_chain(&self.db_pool).await? {
+            Some(chain) => chain,
+            None => {
+                let seed = hex::encode(random::<[u8; 32]>());
+                let terminal_hash = chain_nonce(&seed, self.chain_length, 0);
+                let length = self.chain_length as i64;
+                db::insert_nonce_chain(&self.db_pool, &seed, &terminal_hash, length)
+                    .await
+                    .context("Failed to store new nonce chain")?;
+
+                tracing::info!(terminal_hash, length, "⛓️ Started new nonce hash chain");
+
+                db::get_active_nonce_chain(&self.db_pool)
+                    .await?
+                    .context("Nonce chain missing after insert")?
+            }
+        };
+
+        let position = chain.position + 1;
+        db::advance_nonce_chain(&self.db_pool, chain.id, position).await?;
+
+        Ok(ActiveNonce {
+            nonce: chain_nonce(&chain.seed, chain.length as u32, position as u32),
+            chain_position: Some(position),
+            activated_at: OffsetDateTime::now_utc(),
+        })
+    }
+
+    /// Store a nonce together with its commitment
+    async fn store_nonce(&self, nonce: &ActiveNonce) -> Result<OffsetDateTime> {
+        let nonce_hash = hash_nonce(&nonce.nonce);
+        let expires_at =
+            OffsetDateTime::now_utc() + TimeDuration::hours(self.validity_hours as i64);
+        db::insert_nonce(
+            &self.db_pool,
+            &nonce.nonce,
+            &nonce_hash,
+            expires_at,
+            nonce.chain_position,
+            nonce.activated_at,
+        )
+        .await?;
+
+        Ok(expires_at)
+    }
+
+    /// Retire the active nonce and replace it with a freshly drawn one
+    async fn rotate(&self) -> Result<()> {
+        // Hold the lock for the whole swap so no game is evaluated against a retired nonce
+        let mut current_nonce = self.current_nonce.write().await;
+
+        let new_nonce = self.next_nonce().await?;
+        let retired = db::retire_active_nonces(&self.db_pool, new_nonce.activated_at)
+            .await
+            .context("Failed to retire previous nonce")?;
+        let expires_at = self
+            .store_nonce(&new_nonce)
+            .await
+            .context("Failed to store nonce in database")?;
+
+        tracing::info!(
+            chain_position = new_nonce.chain_position,
+            retired,
+            "🎲 Generated new nonce: {} (expires at {})",
+            new_nonce.nonce,
+            expires_at
+        );
+
+        *current_nonce = new_nonce;
+
+        // Reveal the retired nonce once bets placed just before the rotation had their chance to
+        // be played
+        let service = self.clone();
+        tokio::spawn(async move {
+            sleep(REVEAL_DELAY.unsigned_abs()).await;
+
+            if let Err(e) = service.reveal_due_nonces().await {
+                tracing::error!("Failed to reveal nonces: {:#}", e);
+            }
+        });
+
+        Ok(())
+    }
+
+    pub async fn start_periodic_generation(&self) {
+        let service = self.clone();
+        let period = Duration::from_secs(self.interval_hours * 3600);
+
+        // A resumed nonce only lives out the rest of its interval
+        let active_for = OffsetDateTime::now_utc() - self.get_current_nonce().await.activated_at;
+        let first_rotation = period.saturating_sub(active_for.try_into().unwrap_or_default());
 
         tokio::spawn(async move {
-            let mut timer = interval(Duration::from_secs(interval_hours * 3600));
-            timer.tick().await; // Skip first immediate tick
+            let mut timer = interval_at(Instant::now() + first_rotation, period);
 
             loop {
                 timer.tick().await;
 
-                let new_nonce = random::<u64>();
-                let nonce_str = new_nonce.to_string();
-
-                // Calculate hash
-                let mut hasher = Sha256::new();
-                hasher.update(&nonce_str);
-                let nonce_hash = format!("{:x}", hasher.finalize());
-
-                // Store in database
-                let expires_at =
-                    OffsetDateTime::now_utc() + TimeDuration::hours(validity_hours as i64);
-                match db::insert_nonce(&db_pool, &nonce_str, &nonce_hash, expires_at).await {
-                    Ok(_) => {
-                        tracing::info!(
-                            "🎲 Generated new nonce: {} (expires at {})",
-                            new_nonce,
-                            expires_at
-                        );
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to store nonce in database: {}", e);
-                    }
-                }
-
-                {
-                    let mut nonce = nonce_arc.write().await;
-                    *nonce = new_nonce;
+                if let Err(e) = service.rotate().await {
+                    tracing::error!("Failed to rotate nonce: {:#}", e);
                 }
             }
         });
@@ -106,39 +316,20 @@ pub async fn spawn_nonce_service(
     db_pool: Pool<Sqlite>,
     interval_hours: u64,
     validity_hours: u64,
-) -> NonceService {
-    let service = NonceService::new(db_pool, validity_hours);
-
+    mode: NonceMode,
+    chain_length: u32,
+) -> Result<NonceService> {
     tracing::info!(
+        ?mode,
         "🎯 Starting nonce service (generating new nonce every {} hours, valid for {} hours)",
         interval_hours,
         validity_hours
     );
 
-    let initial_nonce = service.get_current_nonce().await;
-    let nonce_str = initial_nonce.to_string();
-
-    // Calculate hash
-    let mut hasher = Sha256::new();
-    hasher.update(&nonce_str);
-    let nonce_hash = format!("{:x}", hasher.finalize());
-
-    // Store initial nonce in database
-    let expires_at = OffsetDateTime::now_utc() + TimeDuration::hours(validity_hours as i64);
-    match db::insert_nonce(&service.db_pool, &nonce_str, &nonce_hash, expires_at).await {
-        Ok(_) => {
-            tracing::info!(
-                "🎲 Initial nonce: {} (expires at {})",
-                initial_nonce,
-                expires_at
-            );
-        }
-        Err(e) => {
-            tracing::error!("Failed to store initial nonce in database: {}", e);
-        }
-    }
+    let service =
+        NonceService::new(db_pool, interval_hours, validity_hours, mode, chain_length).await?;
 
-    service.start_periodic_generation(interval_hours).await;
+    service.start_periodic_generation().await;
 
-    service
+    Ok(service)
 }
@@ -0,0 +1,816 @@
+use crate::client::Funds;
+use crate::client::SignedSend;
+use crate::db;
+use crate::ArkClient;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use ark_core::ArkAddress;
+use bitcoin::Amount;
+use bitcoin::OutPoint;
+use bitcoin::Txid;
+use sqlx::Pool;
+use sqlx::Sqlite;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use time::Duration as TimeDuration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+
+/// How long a submitted payout is left alone before it is reconciled by anyone but the task that
+/// submitted it, so that a payout is never given up on while the Ark server is still processing it
+pub const IN_FLIGHT_GRACE: TimeDuration = TimeDuration::minutes(2);
+
+/// How often VTXOs are selected for a payout before giving up, when other payouts keep reserving
+/// the selected ones first
+const RESERVATION_ATTEMPTS: usize = 3;
+
+/// What a payout pays for, every source is paid at most once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutSource {
+    /// The winnings or the refund of a game result
+    GameResult(i64),
+    /// A jackpot ledger entry
+    Jackpot(i64),
+    /// The winner of a matched duel or the challenger of an expired one
+    Duel(i64),
+    LotteryRound(i64),
+    /// A lottery payment without an open round
+    LotteryRefund(i64),
+}
+
+impl PayoutSource {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PayoutSource::GameResult(_) => "game_result",
+            PayoutSource::Jackpot(_) => "jackpot",
+            PayoutSource::Duel(_) => "duel",
+            PayoutSource::LotteryRound(_) => "lottery_round",
+            PayoutSource::LotteryRefund(_) => "lottery_refund",
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        match self {
+            PayoutSource::GameResult(id)
+            | PayoutSource::Jackpot(id)
+            | PayoutSource::Duel(id)
+            | PayoutSource::LotteryRound(id)
+            | PayoutSource::LotteryRefund(id) => *id,
+        }
+    }
+
+    pub fn from_stored(kind: &str, id: i64) -> Option<Self> {
+        match kind {
+            "game_result" => Some(PayoutSource::GameResult(id)),
+            "jackpot" => Some(PayoutSource::Jackpot(id)),
+            "duel" => Some(PayoutSource::Duel(id)),
+            "lottery_round" => Some(PayoutSource::LotteryRound(id)),
+            "lottery_refund" => Some(PayoutSource::LotteryRefund(id)),
+            _ => None,
+        }
+    }
+
+    /// The funds the source is paid from, the lottery and duel pools pay only their own
+    fn funds(&self) -> Funds {
+        match self {
+            PayoutSource::GameResult(_) | PayoutSource::Jackpot(_) => Funds::House,
+            PayoutSource::Duel(_) => Funds::Duel,
+            PayoutSource::LotteryRound(_) | PayoutSource::LotteryRefund(_) => Funds::Lottery,
+        }
+    }
+
+    /// Record the payout on the source itself
+    async fn mark_paid(&self, pool: &Pool<Sqlite>, paid: &Paid) -> Result<()> {
+        let txid = paid.txid.as_str();
+        match *self {
+            PayoutSource::GameResult(id) => {
+                db::mark_payment_successful(pool, id, txid, paid.vout as i64).await?
+            }
+            PayoutSource::Jackpot(id) => db::mark_jackpot_paid(pool, id, txid).await?,
+            PayoutSource::Duel(id) => {
+                db::mark_duel_settled(pool, id, txid).await?;
+            }
+            PayoutSource::LotteryRound(id) => db::mark_lottery_round_paid(pool, id, txid).await?,
+            PayoutSource::LotteryRefund(id) => db::mark_lottery_refund_paid(pool, id, txid).await?,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PayoutSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind(), self.id())
+    }
+}
+
+/// A payout that went through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paid {
+    /// The Ark transaction, shared by every payout of a batch
+    pub txid: String,
+    /// Output of the Ark transaction that pays the payout
+    pub vout: u32,
+}
+
+impl Paid {
+    fn of(payout: &db::Payout) -> Result<Self> {
+        Ok(Self {
+            txid: payout
+                .ark_txid
+                .clone()
+                .context("Sent payout without an Ark txid")?,
+            // Payouts sent before batching always used the first output
+            vout: payout.vout.unwrap_or_default() as u32,
+        })
+    }
+}
+
+/// Where an in-flight payout ended up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reconciled {
+    Finalized,
+    /// The payout never reached the Ark server, its source may be paid again
+    Failed,
+    InFlight,
+}
+
+/// A source that is either paid already or has a planned payout
+enum Prepared {
+    Paid(Paid),
+    Planned(db::Payout),
+}
+
+/// Sends the payouts of this process one Ark transaction at a time, in the order they are
+/// queued, so that no two payouts select the same VTXOs
+#[derive(Clone)]
+pub struct PayoutExecutor {
+    jobs: mpsc::UnboundedSender<PayoutJob>,
+}
+
+struct PayoutJob {
+    requests: Vec<(PayoutSource, ArkAddress, Amount)>,
+    transaction_type: String,
+    reply: oneshot::Sender<Vec<Result<Paid>>>,
+}
+
+impl PayoutExecutor {
+    /// Spawn the task that sends every queued payout
+    pub fn start(pool: Pool<Sqlite>, ark_client: Arc<ArkClient>) -> Self {
+        let (jobs, mut queue) = mpsc::unbounded_channel::<PayoutJob>();
+
+        tokio::spawn(async move {
+            while let Some(job) = queue.recv().await {
+                let results = match job.requests.as_slice() {
+                    [(source, address, amount)] => vec![
+                        pay(
+                            &pool,
+                            &ark_client,
+                            *source,
+                            *address,
+                            *amount,
+                            &job.transaction_type,
+                        )
+                        .await,
+                    ],
+                    requests => {
+                        pay_batch(&pool, &ark_client, requests, &job.transaction_type).await
+                    }
+                };
+
+                // The caller may have given up waiting, the outcome is in the outbox either way
+                let _ = job.reply.send(results);
+            }
+            tracing::info!("Payout executor stopped");
+        });
+
+        Self { jobs }
+    }
+
+    /// Queue a payout like [`pay`] and wait until it was sent
+    pub async fn pay(
+        &self,
+        source: PayoutSource,
+        address: ArkAddress,
+        amount: Amount,
+        transaction_type: &str,
+    ) -> Result<Paid> {
+        let mut results = self
+            .queue(vec![(source, address, amount)], transaction_type)
+            .await?;
+        results.remove(0)
+    }
+
+    /// Queue a batch of payouts like [`pay_batch`] and wait until it was sent
+    pub async fn pay_batch(
+        &self,
+        requests: &[(PayoutSource, ArkAddress, Amount)],
+        transaction_type: &str,
+    ) -> Vec<Result<Paid>> {
+        match self.queue(requests.to_vec(), transaction_type).await {
+            Ok(results) => results,
+            Err(e) => requests
+                .iter()
+                .map(|_| Err(anyhow!("Failed to queue payout batch: {e:#}")))
+                .collect(),
+        }
+    }
+
+    async fn queue(
+        &self,
+        requests: Vec<(PayoutSource, ArkAddress, Amount)>,
+        transaction_type: &str,
+    ) -> Result<Vec<Result<Paid>>> {
+        let (reply, results) = oneshot::channel();
+        self.jobs
+            .send(PayoutJob {
+                requests,
+                transaction_type: transaction_type.to_string(),
+                reply,
+            })
+            .map_err(|_| anyhow!("Payout executor is not running"))?;
+
+        results.await.context("Payout executor dropped the payout")
+    }
+}
+
+/// Send `amount` to `address` for `source` through the payout outbox.
+///
+/// The payout is planned before anything is signed and its Ark txid is stored before the Ark
+/// server sees it, so a source that was already paid returns the earlier payment instead of being
+/// paid twice. The caller still records the payment on the source.
+///
+/// Within the server payouts go through the [`PayoutExecutor`] instead, VTXOs reserved by another
+/// process are skipped either way.
+pub async fn pay(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    source: PayoutSource,
+    address: ArkAddress,
+    amount: Amount,
+    transaction_type: &str,
+) -> Result<Paid> {
+    match prepare(pool, ark_client, source, address, amount, transaction_type).await? {
+        Prepared::Paid(paid) => Ok(paid),
+        Prepared::Planned(payout) => {
+            let mut paid = send(
+                pool,
+                ark_client,
+                &[payout],
+                &[(address, amount)],
+                transaction_type,
+            )
+            .await?;
+            Ok(paid.remove(0))
+        }
+    }
+}
+
+/// Like [`pay`] for every `(source, address, amount)` of `requests`, sending a single Ark
+/// transaction with an output per payout. Returns the result of every request in order.
+///
+/// If the batch does not go through, every payout of it fails and can be sent on its own with
+/// [`pay`], so that one recipient cannot hold up the rest.
+pub async fn pay_batch(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    requests: &[(PayoutSource, ArkAddress, Amount)],
+    transaction_type: &str,
+) -> Vec<Result<Paid>> {
+    let mut results: Vec<Option<Result<Paid>>> = requests.iter().map(|_| None).collect();
+    let mut planned = Vec::new();
+
+    for (index, (source, address, amount)) in requests.iter().enumerate() {
+        match prepare(
+            pool,
+            ark_client,
+            *source,
+            *address,
+            *amount,
+            transaction_type,
+        )
+        .await
+        {
+            Ok(Prepared::Planned(payout)) => planned.push((index, payout)),
+            Ok(Prepared::Paid(paid)) => results[index] = Some(Ok(paid)),
+            Err(e) => results[index] = Some(Err(e)),
+        }
+    }
+
+    if !planned.is_empty() {
+        let payouts = planned
+            .iter()
+            .map(|(_, payout)| payout.clone())
+            .collect::<Vec<_>>();
+        let outputs = planned
+            .iter()
+            .map(|(index, _)| (requests[*index].1, requests[*index].2))
+            .collect::<Vec<_>>();
+
+        match send(pool, ark_client, &payouts, &outputs, transaction_type).await {
+            Ok(paid) => {
+                for ((index, _), paid) in planned.iter().zip(paid) {
+                    results[*index] = Some(Ok(paid));
+                }
+            }
+            Err(e) => {
+                for (index, _) in &planned {
+                    results[*index] = Some(Err(anyhow!("Failed to send payout batch: {e:#}")));
+                }
+            }
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every request has a result"))
+        .collect()
+}
+
+/// Settle every payout a crash left signed or submitted, and record the finalized ones on their
+/// source. Payouts that cannot be settled stay in flight, their source is not paid again.
+///
+/// Submitted payouts updated within `grace` are skipped, as they may still be sent by someone
+/// else.
+pub async fn reconcile_in_flight(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    grace: TimeDuration,
+) -> Result<()> {
+    let orphaned = db::release_orphaned_vtxos(pool, OffsetDateTime::now_utc() - grace)
+        .await
+        .context("Failed to release orphaned VTXO reservations")?;
+    if orphaned > 0 {
+        tracing::info!(
+            orphaned,
+            "🔓 Released VTXOs reserved for never signed payouts"
+        );
+    }
+
+    let payouts = db::get_in_flight_payouts(pool)
+        .await
+        .context("Failed to load in-flight payouts")?;
+
+    if payouts.is_empty() {
+        return Ok(());
+    }
+
+    tracing::info!(count = payouts.len(), "🔄 Reconciling in-flight payouts");
+
+    // Every payout of a batch shares the Ark transaction, which is only reconciled once
+    let mut reconciled = HashMap::new();
+
+    for payout in payouts {
+        let Some(source) = PayoutSource::from_stored(&payout.source, payout.source_id) else {
+            tracing::error!(
+                payout_id = payout.id,
+                source = payout.source,
+                "Payout of an unknown source"
+            );
+            continue;
+        };
+
+        let ark_txid = payout.ark_txid.clone().unwrap_or_default();
+        let result = match reconciled.get(&ark_txid) {
+            Some(result) => Ok(*result),
+            None => reconcile(pool, ark_client, &payout, grace).await,
+        };
+        if let Ok(result) = result {
+            reconciled.insert(ark_txid, result);
+        }
+
+        match result {
+            Ok(Reconciled::Finalized) => {
+                let paid = match Paid::of(&payout) {
+                    Ok(paid) => paid,
+                    Err(e) => {
+                        tracing::error!(payout_id = payout.id, %source, "Invalid payout {e:#}");
+                        continue;
+                    }
+                };
+                if let Err(e) = source.mark_paid(pool, &paid).await {
+                    tracing::error!(%source, txid = paid.txid, "Failed to record payout: {e:#}");
+                }
+            }
+            Ok(Reconciled::Failed) => {
+                tracing::info!(
+                    payout_id = payout.id,
+                    %source,
+                    "↩️ Payout never reached the Ark server, it will be paid again"
+                );
+            }
+            Ok(Reconciled::InFlight) => {
+                tracing::error!(
+                    payout_id = payout.id,
+                    %source,
+                    ark_txid = payout.ark_txid.as_deref(),
+                    "🚨 Payout is still in flight and needs a manual check"
+                );
+            }
+            Err(e) => {
+                tracing::error!(payout_id = payout.id, %source, "Failed to reconcile payout {e:#}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Plan a payout for `source`, settling the one it has in flight first
+async fn prepare(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    source: PayoutSource,
+    address: ArkAddress,
+    amount: Amount,
+    transaction_type: &str,
+) -> Result<Prepared> {
+    let mut payout = plan(pool, source, address, amount, transaction_type).await?;
+
+    if matches!(payout.status.as_str(), "signed" | "submitted") {
+        match reconcile(pool, ark_client, &payout, IN_FLIGHT_GRACE).await? {
+            Reconciled::Finalized => return Ok(Prepared::Paid(Paid::of(&payout)?)),
+            Reconciled::Failed => {
+                payout = plan(pool, source, address, amount, transaction_type).await?;
+            }
+            Reconciled::InFlight => bail!("Payout {} of {source} is still in flight", payout.id),
+        }
+    }
+
+    match payout.status.as_str() {
+        "finalized" => {
+            tracing::info!(payout_id = payout.id, %source, "💸 Payout was already sent");
+            Ok(Prepared::Paid(Paid::of(&payout)?))
+        }
+        "planned" => Ok(Prepared::Planned(payout)),
+        status => bail!("Payout {} of {source} is {status}", payout.id),
+    }
+}
+
+/// Sign, record and submit a single Ark transaction paying `outputs[i]` for `payouts[i]`.
+///
+/// The VTXOs it spends are reserved until it is finalized or failed, so that neither this nor
+/// another process selects them in the meantime.
+async fn send(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    payouts: &[db::Payout],
+    outputs: &[(ArkAddress, Amount)],
+    transaction_type: &str,
+) -> Result<Vec<Paid>> {
+    let signed = match funds_of(payouts) {
+        Ok(funds) => sign_reserved(pool, ark_client, outputs, funds).await,
+        Err(e) => Err(e),
+    };
+    let signed = match signed {
+        Ok(signed) => signed,
+        Err(e) => {
+            for payout in payouts {
+                db::mark_payout_failed(pool, payout.id, &format!("{e:#}")).await?;
+            }
+            return Err(e);
+        }
+    };
+
+    let ark_txid = signed.ark_txid.to_string();
+    if let Err(e) = record_signed(pool, payouts, &ark_txid, &signed.inputs).await {
+        // Nothing was submitted yet, so the batch can be given up on
+        fail(
+            pool,
+            ark_client,
+            &ark_txid,
+            &signed.inputs,
+            "Never submitted",
+        )
+        .await?;
+        return Err(e);
+    }
+
+    if let Err(e) = ark_client.submit_send(signed).await {
+        let submitted = match db::get_payouts_by_ark_txid(pool, &ark_txid).await {
+            Ok(payouts) => payouts.into_iter().next(),
+            Err(load_error) => {
+                tracing::error!(ark_txid, "Failed to load submitted payouts {load_error:#}");
+                None
+            }
+        };
+        let Some(submitted) = submitted else {
+            return Err(e.context(format!("Payouts of {ark_txid} are left in flight")));
+        };
+
+        // We submitted it ourselves, so there is no need to wait for anyone else
+        match reconcile(pool, ark_client, &submitted, TimeDuration::ZERO).await {
+            Ok(Reconciled::Finalized) => {}
+            Ok(Reconciled::Failed) => return Err(e),
+            Ok(Reconciled::InFlight) => {
+                return Err(e.context(format!("Payouts of {ark_txid} are left in flight")));
+            }
+            Err(reconcile_error) => {
+                tracing::error!(ark_txid, "Failed to reconcile payouts {reconcile_error:#}");
+                return Err(e);
+            }
+        }
+    } else {
+        // The client already swapped the spent VTXOs for the change in its cache
+        finalize(pool, ark_client, &ark_txid, &[], transaction_type).await?;
+    }
+
+    Ok((0..payouts.len())
+        .map(|vout| Paid {
+            txid: ark_txid.clone(),
+            vout: vout as u32,
+        })
+        .collect())
+}
+
+/// Select the VTXOs of `funds` for an Ark transaction paying `outputs`, reserve them for every
+/// process and sign it.
+///
+/// The VTXOs are reserved in the database before the transaction is signed. If another process
+/// reserved one of them since they were selected, they are selected again without it.
+async fn sign_reserved(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    outputs: &[(ArkAddress, Amount)],
+    funds: Funds,
+) -> Result<SignedSend> {
+    for _ in 0..RESERVATION_ATTEMPTS {
+        let reserved = reserved_vtxos(pool).await?;
+        let prepared = ark_client
+            .prepare_send_many(outputs, &reserved, funds)
+            .await?;
+        let ark_txid = prepared.ark_txid.to_string();
+        let inputs = prepared.inputs.clone();
+
+        let outpoints = inputs
+            .iter()
+            .map(|input| input.to_string())
+            .collect::<Vec<_>>();
+        match db::reserve_vtxos(pool, &ark_txid, &outpoints).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::debug!(ark_txid, "Selected VTXOs were reserved by another payout");
+                ark_client.release_vtxos(&inputs).await;
+                continue;
+            }
+            Err(e) => {
+                ark_client.release_vtxos(&inputs).await;
+                return Err(e).context("Failed to reserve VTXOs");
+            }
+        }
+
+        return match ark_client.sign_prepared_send(prepared) {
+            Ok(signed) => Ok(signed),
+            Err(e) => {
+                db::release_vtxos(pool, &ark_txid).await?;
+                ark_client.release_vtxos(&inputs).await;
+                Err(e)
+            }
+        };
+    }
+
+    bail!("Selected VTXOs kept being reserved by other payouts")
+}
+
+/// Store the Ark transaction on every payout it pays, right before it is submitted
+async fn record_signed(
+    pool: &Pool<Sqlite>,
+    payouts: &[db::Payout],
+    ark_txid: &str,
+    inputs: &[OutPoint],
+) -> Result<()> {
+    let encoded = encode_inputs(inputs);
+    for (vout, payout) in payouts.iter().enumerate() {
+        if !db::mark_payout_signed(pool, payout.id, ark_txid, vout as i64, &encoded).await? {
+            bail!("Payout {} is not planned anymore", payout.id);
+        }
+    }
+
+    if db::mark_payouts_submitted(pool, ark_txid).await? != payouts.len() as u64 {
+        bail!("Payouts of {ark_txid} were given up on before they were sent");
+    }
+
+    Ok(())
+}
+
+async fn plan(
+    pool: &Pool<Sqlite>,
+    source: PayoutSource,
+    address: ArkAddress,
+    amount: Amount,
+    transaction_type: &str,
+) -> Result<db::Payout> {
+    let payout = db::plan_payout(
+        pool,
+        source.kind(),
+        source.id(),
+        transaction_type,
+        &address.encode(),
+        amount.to_sat() as i64,
+    )
+    .await
+    .context("Failed to plan payout")?;
+
+    Ok(payout)
+}
+
+/// Find out whether the Ark transaction of an in-flight payout reached the Ark server, settling
+/// every payout it pays.
+///
+/// A signed transaction was never submitted and fails right away. A submitted one is finalized if
+/// the Ark server knows it, and fails only if the Ark server does not know it and every VTXO it
+/// spends is still spendable. It stays in flight while the Ark server cannot be asked.
+async fn reconcile(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    payout: &db::Payout,
+    grace: TimeDuration,
+) -> Result<Reconciled> {
+    let ark_txid = payout
+        .ark_txid
+        .as_deref()
+        .context("In-flight payout without an Ark txid")?;
+
+    let inputs = decode_inputs(payout.inputs.as_deref().unwrap_or_default())?;
+
+    if payout.status == "signed" {
+        fail(pool, ark_client, ark_txid, &inputs, "Never submitted").await?;
+        return Ok(Reconciled::Failed);
+    }
+    if payout.updated_at > OffsetDateTime::now_utc() - grace {
+        return Ok(Reconciled::InFlight);
+    }
+
+    let txid = Txid::from_str(ark_txid).context("Invalid Ark txid")?;
+    match ark_client.is_ark_transaction_known(txid).await {
+        Ok(true) => {
+            // The cache may still hold the VTXOs it spent
+            if let Err(e) = ark_client.sync_spendable_vtxos().await {
+                tracing::error!("Failed syncing after a payout was reconciled {e:#}");
+            }
+            finalize(
+                pool,
+                ark_client,
+                ark_txid,
+                &inputs,
+                &payout.transaction_type,
+            )
+            .await?;
+            return Ok(Reconciled::Finalized);
+        }
+        Ok(false) => {}
+        Err(e) => {
+            // Failing it on the cache alone could pay its sources twice
+            tracing::warn!(ark_txid, "Failed to look up payout transaction {e:#}");
+            return Ok(Reconciled::InFlight);
+        }
+    }
+
+    ark_client.sync_spendable_vtxos().await?;
+    let spendable = ark_client.get_cached_spendable_vtxos().await?;
+    let all_spendable = inputs.iter().all(|input| {
+        spendable
+            .values()
+            .flatten()
+            .any(|vtxo| vtxo.outpoint == *input)
+    });
+
+    if inputs.is_empty() || !all_spendable {
+        return Ok(Reconciled::InFlight);
+    }
+
+    fail(
+        pool,
+        ark_client,
+        ark_txid,
+        &inputs,
+        "Not known to the Ark server",
+    )
+    .await?;
+    Ok(Reconciled::Failed)
+}
+
+/// Finalize every payout of `ark_txid` and release the VTXOs it spent
+async fn finalize(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    ark_txid: &str,
+    inputs: &[OutPoint],
+    transaction_type: &str,
+) -> Result<()> {
+    db::mark_payouts_finalized(pool, ark_txid).await?;
+    db::release_vtxos(pool, ark_txid).await?;
+    ark_client.release_vtxos(inputs).await;
+
+    if let Err(e) = db::insert_own_transaction(pool, ark_txid, transaction_type).await {
+        tracing::error!("Failed to store {transaction_type} transaction: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Fail every payout of `ark_txid` that never reached the Ark server and release the VTXOs it
+/// would have spent
+async fn fail(
+    pool: &Pool<Sqlite>,
+    ark_client: &ArkClient,
+    ark_txid: &str,
+    inputs: &[OutPoint],
+    error: &str,
+) -> Result<()> {
+    db::mark_payouts_failed(pool, ark_txid, error).await?;
+    db::release_vtxos(pool, ark_txid).await?;
+    ark_client.release_vtxos(inputs).await;
+
+    Ok(())
+}
+
+/// VTXOs reserved by payouts of any process that are not settled yet
+async fn reserved_vtxos(pool: &Pool<Sqlite>) -> Result<HashSet<OutPoint>> {
+    db::get_reserved_vtxos(pool)
+        .await
+        .context("Failed to load reserved VTXOs")?
+        .iter()
+        .map(|outpoint| OutPoint::from_str(outpoint).context("Invalid reserved VTXO"))
+        .collect()
+}
+
+/// The funds that pay every payout of one Ark transaction, which have to share them
+fn funds_of(payouts: &[db::Payout]) -> Result<Funds> {
+    let mut funds = payouts.iter().map(|payout| {
+        PayoutSource::from_stored(&payout.source, payout.source_id)
+            .map(|source| source.funds())
+            .with_context(|| format!("Unknown payout source {}", payout.source))
+    });
+
+    let first = funds.next().context("No payouts to send")??;
+    for other in funds {
+        if other? != first {
+            bail!("Payouts of one transaction are paid from different funds");
+        }
+    }
+
+    Ok(first)
+}
+
+fn encode_inputs(inputs: &[OutPoint]) -> String {
+    inputs
+        .iter()
+        .map(|input| input.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn decode_inputs(inputs: &str) -> Result<Vec<OutPoint>> {
+    inputs
+        .split(',')
+        .filter(|input| !input.is_empty())
+        .map(|input| OutPoint::from_str(input).context("Invalid payout input"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_and_inputs_round_trip() {
+        for source in [
+            PayoutSource::GameResult(1),
+            PayoutSource::Jackpot(2),
+            PayoutSource::Duel(3),
+            PayoutSource::LotteryRound(4),
+            PayoutSource::LotteryRefund(5),
+        ] {
+            assert_eq!(
+                PayoutSource::from_stored(source.kind(), source.id()),
+                Some(source)
+            );
+        }
+        assert_eq!(PayoutSource::from_stored("unknown", 1), None);
+
+        let txid = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+        let inputs = vec![
+            OutPoint::from_str(&format!("{txid}:0")).expect("valid outpoint"),
+            OutPoint::from_str(&format!("{txid}:3")).expect("valid outpoint"),
+        ];
+        let encoded = encode_inputs(&inputs);
+        assert_eq!(encoded, format!("{txid}:0,{txid}:3"));
+        assert_eq!(decode_inputs(&encoded).expect("valid inputs"), inputs);
+        assert!(decode_inputs("").expect("no inputs").is_empty());
+    }
+
+    #[test]
+    fn test_pools_pay_only_their_own_sources() {
+        assert_eq!(PayoutSource::GameResult(1).funds(), Funds::House);
+        assert_eq!(PayoutSource::Jackpot(2).funds(), Funds::House);
+        assert_eq!(PayoutSource::Duel(3).funds(), Funds::Duel);
+        assert_eq!(PayoutSource::LotteryRound(4).funds(), Funds::Lottery);
+        assert_eq!(PayoutSource::LotteryRefund(5).funds(), Funds::Lottery);
+    }
+}
@@ -1,6 +1,15 @@
+use crate::bet_intents::claim_bet_intent;
+use crate::bet_intents::is_late_bet;
 use crate::db;
-use crate::games::get_game;
+use crate::games::registry::GameRegistry;
+use crate::games::satoshis_number::RollVersion;
+use crate::games::GameInput;
+use crate::games::GameType;
+use crate::jackpot::JackpotSettings;
+use crate::nonce_service::hash_nonce;
 use crate::nonce_service::NonceService;
+use crate::payouts;
+use crate::payouts::PayoutSource;
 use crate::ArkClient;
 use anyhow::Context;
 use anyhow::Result;
@@ -22,6 +31,17 @@ pub async fn process_missed_payouts(
     let mut total_payout_amount = 0u64;
     let mut retry_payouts = 0;
 
+    // Payouts a crash left in flight are settled first, so that none of them is paid again
+    if dry_run {
+        let in_flight = db::get_in_flight_payouts(pool).await?;
+        tracing::info!(
+            "🎰 [DRY RUN] Would reconcile {} in-flight payouts",
+            in_flight.len()
+        );
+    } else {
+        payouts::reconcile_in_flight(pool, &ark_client, payouts::IN_FLIGHT_GRACE).await?;
+    }
+
     let unpaid_winners = match hours {
         Some(h) => db::get_unpaid_winners_within_hours(pool, h).await?,
         None => db::get_unpaid_winners(pool).await?,
@@ -72,30 +92,33 @@ pub async fn process_missed_payouts(
                 while retry_count < MAX_RETRIES {
                     ark_client.sync_spendable_vtxos().await?;
 
-                    match ark_client
-                        .send_vtxo(player_address, Amount::from_sat(payout_sats))
-                        .await
+                    match payouts::pay(
+                        pool,
+                        &ark_client,
+                        PayoutSource::GameResult(winner.id),
+                        player_address,
+                        Amount::from_sat(payout_sats),
+                        "retry_payout",
+                    )
+                    .await
                     {
-                        Ok(txid) => {
-                            let output_txid = txid.to_string();
+                        Ok(paid) => {
                             tracing::info!(
                                 "✅ Retry payout sent: game_id={}, payout_txid={}, amount={} sats",
                                 winner.id,
-                                txid,
+                                paid.txid,
                                 payout_sats
                             );
                             payout_sent = true;
 
-                            // Store as our own transaction
-                            if let Err(e) =
-                                db::insert_own_transaction(pool, &output_txid, "retry_payout").await
-                            {
-                                tracing::error!("Failed to store own transaction: {}", e);
-                            }
-
                             // Mark as paid in database
-                            if let Err(e) =
-                                db::mark_payment_successful(pool, winner.id, &output_txid).await
+                            if let Err(e) = db::mark_payment_successful(
+                                pool,
+                                winner.id,
+                                &paid.txid,
+                                paid.vout as i64,
+                            )
+                            .await
                             {
                                 tracing::error!("Failed to mark payment as successful: {}", e);
                             }
@@ -133,6 +156,75 @@ pub async fn process_missed_payouts(
         tracing::info!("✅ No unpaid winners found in database");
     }
 
+    for jackpot in db::get_unpaid_jackpot_wins(pool).await? {
+        retry_payouts += 1;
+        let payout_sats = jackpot.amount.unsigned_abs();
+        total_payout_amount += payout_sats;
+        let player = jackpot.player_address.unwrap_or_default();
+
+        if dry_run {
+            tracing::info!(
+                "🎰 [DRY RUN] Would retry jackpot payout: entry_id={}, player={}, payout={} sats",
+                jackpot.id,
+                player,
+                payout_sats
+            );
+            successful_payouts += 1;
+            continue;
+        }
+
+        tracing::info!(
+            "🎰 Retrying jackpot payout: entry_id={}, player={}, payout={} sats",
+            jackpot.id,
+            player,
+            payout_sats
+        );
+
+        let player_address = match ark_core::ArkAddress::decode(&player) {
+            Ok(addr) => addr,
+            Err(e) => {
+                tracing::error!("Failed to decode jackpot winner address {}: {}", player, e);
+                failed_payouts += 1;
+                continue;
+            }
+        };
+
+        ark_client.sync_spendable_vtxos().await?;
+        match payouts::pay(
+            pool,
+            &ark_client,
+            PayoutSource::Jackpot(jackpot.id),
+            player_address,
+            Amount::from_sat(payout_sats),
+            "jackpot_payout",
+        )
+        .await
+        {
+            Ok(paid) => {
+                tracing::info!(
+                    "✅ Jackpot payout sent: entry_id={}, payout_txid={}, amount={} sats",
+                    jackpot.id,
+                    paid.txid,
+                    payout_sats
+                );
+
+                if let Err(e) = db::mark_jackpot_paid(pool, jackpot.id, &paid.txid).await {
+                    tracing::error!("Failed to mark jackpot paid: {}", e);
+                }
+
+                successful_payouts += 1;
+            }
+            Err(e) => {
+                tracing::error!(
+                    "❌ Failed to send jackpot payout for entry_id={}: {:#}",
+                    jackpot.id,
+                    e
+                );
+                failed_payouts += 1;
+            }
+        }
+    }
+
     if failed_payouts > 0 {
         tracing::error!(
             "⚠️  Recovery completed: {} retry payouts sent, {} FAILED",
@@ -164,6 +256,8 @@ pub async fn process_missed_games(
     pool: &Pool<Sqlite>,
     nonce_service: &NonceService,
     max_payout_sats: u64,
+    games: &GameRegistry,
+    jackpot: JackpotSettings,
     dry_run: bool,
 ) -> Result<()> {
     if dry_run {
@@ -175,8 +269,9 @@ pub async fn process_missed_games(
     }
     ark_client.sync_spendable_vtxos().await?;
 
-    // Get all game addresses
-    let game_addresses = ark_client.get_game_addresses();
+    // Get all game and bet intent addresses
+    let mut game_addresses = ark_client.get_game_addresses();
+    game_addresses.extend(ark_client.get_bet_intent_addresses());
     let addresses: Vec<_> = game_addresses
         .iter()
         .map(|(_, _, address)| *address)
@@ -202,6 +297,7 @@ pub async fn process_missed_games(
     let failed_payouts = 0;
     let mut total_payout_amount = 0u64;
     let mut donation_count = 0;
+    let mut refund_count = 0;
     let retry_payouts = 0;
 
     // First, handle unpaid winners from database
@@ -209,9 +305,10 @@ pub async fn process_missed_games(
 
     for vtxo in vtxos {
         let tx_id = vtxo.outpoint.txid.to_string();
+        let vout = vtxo.outpoint.vout as i64;
 
         // Skip if already processed
-        if db::is_transaction_processed(pool, &tx_id).await? {
+        if db::is_outpoint_processed(pool, &tx_id, vout).await? {
             already_processed += 1;
             continue;
         }
@@ -225,19 +322,19 @@ pub async fn process_missed_games(
         // This is a new game we haven't seen!
         new_games += 1;
         tracing::info!(
-            "🎲 Found unprocessed game: txid={}, amount={} sats",
-            tx_id,
+            "🎲 Found unprocessed game: outpoint={}, amount={} sats",
+            vtxo.outpoint,
             vtxo.amount.to_sat()
         );
 
         // Find which game this VTXO belongs to
-        let (game_type, multiplier) = match game_addresses
+        let (game_type, option, game_address) = match game_addresses
             .iter()
             .find(|(_, _, addr)| {
                 vtxo.script == addr.to_p2tr_script_pubkey()
                     || vtxo.script == addr.to_sub_dust_script_pubkey()
             })
-            .map(|(gt, m, _)| (*gt, *m))
+            .copied()
         {
             Some(game_info) => game_info,
             None => {
@@ -267,10 +364,95 @@ pub async fn process_missed_games(
         };
 
         let input_amount = vtxo.amount.to_sat();
-        let current_nonce = nonce_service.get_current_nonce().await;
+        // Evaluate against the nonce and client seed that were in effect when the bet was made,
+        // not the current ones. Bets on disabled games and late bets to expired intents are
+        // refunded like bets without a nonce.
+        let created_at = time::OffsetDateTime::from_unix_timestamp(vtxo.created_at)?;
+
+        let late_bet = match (game_type, dry_run) {
+            (GameType::OverUnder, false) => {
+                claim_bet_intent(pool, &game_address.encode(), created_at).await?
+            }
+            (GameType::OverUnder, true) => {
+                db::get_bet_intent_by_address(pool, &game_address.encode())
+                    .await?
+                    .is_some_and(|intent| is_late_bet(&intent, created_at))
+            }
+            _ => false,
+        };
+        let client_seed =
+            db::get_client_seed_at(pool, &sender_address.encode(), created_at).await?;
+        let game = games.get(game_type);
+
+        let (current_nonce, refund_reason) = if !games.is_enabled(game_type) {
+            (None, "game is disabled")
+        } else if late_bet {
+            (None, "bet intent expired")
+        } else {
+            (
+                nonce_service.get_nonce_at(created_at).await?,
+                "no unrevealed nonce was active",
+            )
+        };
+        let Some(current_nonce) = current_nonce else {
+            refund_count += 1;
+            if dry_run {
+                tracing::info!(
+                    "↩️ [DRY RUN] Would record refund: txid={}, amount={} sats, created_at={} ({})",
+                    tx_id,
+                    input_amount,
+                    created_at,
+                    refund_reason
+                );
+            } else {
+                tracing::warn!(
+                    "↩️ Recording refund for missed game: txid={}, amount={} sats, created_at={} ({})",
+                    tx_id,
+                    input_amount,
+                    created_at,
+                    refund_reason
+                );
+
+                // Stored as unpaid so it gets sent with the unpaid winners
+                if let Err(e) = db::insert_game_result(
+                    pool,
+                    "", // Not bound to a nonce
+                    db::REFUNDED_ROLL,
+                    &tx_id,
+                    vout,
+                    None,
+                    input_amount as i64,
+                    Some(input_amount as i64),
+                    &sender_address.encode(),
+                    false,
+                    false,
+                    game.payout_multiplier("", &option) as i64,
+                    client_seed.as_deref(),
+                    None,
+                    game_type as i64,
+                    option.index() as i64,
+                    None,
+                    None,
+                    None,
+                    RollVersion::LATEST as i64,
+                    games.settings().house_edge_bps(game_type).map(i64::from),
+                )
+                .await
+                {
+                    tracing::error!("Failed to store missed refund: {:#}", e);
+                }
+            }
+            continue;
+        };
+
+        let nonce_hash = hash_nonce(&current_nonce.nonce);
+        let payout_multiplier = game.payout_multiplier(&nonce_hash, &option);
+        let target = game.target(&nonce_hash, &option);
 
         // Check donation threshold
-        let donation_threshold = (max_payout_sats * 100) / multiplier.multiplier();
+        let donation_threshold = (max_payout_sats * 100)
+            .checked_div(payout_multiplier)
+            .unwrap_or(0);
         if input_amount > donation_threshold {
             donation_count += 1;
             if dry_run {
@@ -291,16 +473,26 @@ pub async fn process_missed_games(
                 // Store as donation
                 if let Err(e) = db::insert_game_result(
                     pool,
-                    &current_nonce.to_string(),
+                    &current_nonce.nonce,
                     -1, // Special value for donations
                     &tx_id,
+                    vout,
                     None,
                     input_amount as i64,
                     None,
                     &sender_address.encode(),
                     false,
                     false,
-                    multiplier.multiplier() as i64,
+                    payout_multiplier as i64,
+                    client_seed.as_deref(),
+                    current_nonce.chain_position,
+                    game_type as i64,
+                    option.index() as i64,
+                    None,
+                    None,
+                    None,
+                    RollVersion::LATEST as i64,
+                    games.settings().house_edge_bps(game_type).map(i64::from),
                 )
                 .await
                 {
@@ -311,56 +503,70 @@ pub async fn process_missed_games(
         }
 
         // Evaluate the game
-        let game = get_game(game_type);
-        let evaluation = game.evaluate(current_nonce, &tx_id, &multiplier);
-
-        let (is_win, payout_amount) = if evaluation.is_win {
-            let payout = (input_amount as f64
-                * evaluation
-                    .payout_multiplier
-                    .expect("to have payout multiplier")) as u64;
-            (true, Some(payout))
-        } else {
-            (false, None)
+        let input = GameInput {
+            nonce: &current_nonce.nonce,
+            txid: &tx_id,
+            vout: Some(out_point.vout),
+            client_seed: client_seed.as_deref(),
+            roll_version: RollVersion::LATEST,
         };
+        let evaluation = game.evaluate(&input, &option, input_amount);
+        let payout_sats = evaluation.returned_sats();
 
-        if is_win {
-            let payout_sats = payout_amount.unwrap();
+        // Partial refunds are paid like wins
+        if payout_sats > 0 {
             total_payout_amount += payout_sats;
+            let kind = if evaluation.is_win {
+                "WINNER"
+            } else {
+                "partial refund"
+            };
 
             if dry_run {
                 tracing::info!(
-                    "🎰 [DRY RUN] Would record WINNER! txid={}, amount={} sats, payout={} sats, rolled={}, target={}",
+                    "🎰 [DRY RUN] Would record {}! txid={}, amount={} sats, payout={} sats, rolled={}, target={}",
+                    kind,
                     tx_id,
                     input_amount,
                     payout_sats,
                     evaluation.rolled_value,
-                    multiplier.get_lower_than()
+                    target
                 );
                 successful_payouts += 1;
             } else {
                 tracing::info!(
-                    "🎰 Missed WINNER found! Recording in DB (not paying out yet): txid={}, amount={} sats, payout={} sats, rolled={}, target={}",
+                    "🎰 Missed {} found! Recording in DB (not paying out yet): txid={}, amount={} sats, payout={} sats, rolled={}, target={}",
+                    kind,
                     tx_id,
                     input_amount,
                     payout_sats,
                     evaluation.rolled_value,
-                    multiplier.get_lower_than()
+                    target
                 );
 
                 // Store game result in database as unpaid winner
                 if let Err(e) = db::insert_game_result(
                     pool,
-                    &current_nonce.to_string(),
+                    &current_nonce.nonce,
                     evaluation.rolled_value,
                     &tx_id,
+                    vout,
                     None, // No output tx yet
                     input_amount as i64,
-                    payout_amount.map(|p| p as i64),
+                    Some(payout_sats as i64),
                     &sender_address.encode(),
-                    true,  // is_winner
+                    evaluation.is_win,
                     false, // payment_successful = false (will be paid later)
-                    multiplier.multiplier() as i64,
+                    payout_multiplier as i64,
+                    client_seed.as_deref(),
+                    current_nonce.chain_position,
+                    game_type as i64,
+                    option.index() as i64,
+                    evaluation.refund_sats.map(|r| r as i64),
+                    Some(evaluation.label),
+                    Some(&evaluation.detail),
+                    RollVersion::LATEST as i64,
+                    games.settings().house_edge_bps(game_type).map(i64::from),
                 )
                 .await
                 {
@@ -375,33 +581,81 @@ pub async fn process_missed_games(
                     "[DRY RUN] Would record loser: txid={}, rolled={}, target={}",
                     tx_id,
                     evaluation.rolled_value,
-                    multiplier.get_lower_than()
+                    target
                 );
             } else {
                 tracing::debug!(
                     "Missed loser: txid={}, rolled={}, target={}",
                     tx_id,
                     evaluation.rolled_value,
-                    multiplier.get_lower_than()
+                    target
                 );
 
                 // Store losing game result
                 if let Err(e) = db::insert_game_result(
                     pool,
-                    &current_nonce.to_string(),
+                    &current_nonce.nonce,
                     evaluation.rolled_value,
                     &tx_id,
+                    vout,
                     None,
                     input_amount as i64,
                     None,
                     &sender_address.encode(),
                     false,
                     true, // Not a payout needed
-                    multiplier.multiplier() as i64,
+                    payout_multiplier as i64,
+                    client_seed.as_deref(),
+                    current_nonce.chain_position,
+                    game_type as i64,
+                    option.index() as i64,
+                    None,
+                    Some(evaluation.label),
+                    Some(&evaluation.detail),
+                    RollVersion::LATEST as i64,
+                    games.settings().house_edge_bps(game_type).map(i64::from),
                 )
                 .await
                 {
                     tracing::error!("Failed to store missed losing game: {:#}", e);
+                } else {
+                    let contribution = jackpot.contribution(input_amount);
+                    if contribution > 0 {
+                        if let Err(e) =
+                            db::insert_jackpot_contribution(pool, contribution as i64, &tx_id, vout)
+                                .await
+                        {
+                            tracing::error!("Failed to store jackpot contribution: {:#}", e);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Recorded unpaid, the payout is sent with the unpaid winners
+        if jackpot.is_win(&input) {
+            if dry_run {
+                tracing::info!("🎰 [DRY RUN] Would record JACKPOT win: txid={}", tx_id);
+            } else {
+                match db::claim_jackpot(
+                    pool,
+                    &tx_id,
+                    vout,
+                    &sender_address.encode(),
+                    ark_client.dust_value().to_sat() as i64,
+                )
+                .await
+                {
+                    Ok(Some(entry)) => tracing::info!(
+                        "🎰 Missed JACKPOT win found! txid={}, amount={} sats",
+                        tx_id,
+                        entry.amount.unsigned_abs()
+                    ),
+                    Ok(None) => tracing::info!(
+                        "🎰 Missed jackpot hit, but the pot is too small to pay out: txid={}",
+                        tx_id
+                    ),
+                    Err(e) => tracing::error!("Failed to store missed jackpot win: {:#}", e),
                 }
             }
         }
@@ -409,11 +663,12 @@ pub async fn process_missed_games(
 
     if dry_run {
         tracing::info!(
-            "📊 [DRY RUN] Summary: {} unpaid winners to retry, {} new games found ({} winners, {} donations), {} already processed, {} own transactions",
+            "📊 [DRY RUN] Summary: {} unpaid winners to retry, {} new games found ({} winners, {} donations, {} refunds), {} already processed, {} own transactions",
             retry_payouts,
             new_games,
             successful_payouts - retry_payouts,
             donation_count,
+            refund_count,
             already_processed,
             own_transactions
         );
@@ -435,9 +690,10 @@ pub async fn process_missed_games(
         Ok(())
     } else {
         tracing::info!(
-            "📊 Recovery summary: {} retry payouts, {} new games, {} already processed, {} own transactions",
+            "📊 Recovery summary: {} retry payouts, {} new games ({} refunds), {} already processed, {} own transactions",
             retry_payouts,
             new_games,
+            refund_count,
             already_processed,
             own_transactions
         );
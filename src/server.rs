@@ -1,34 +1,64 @@
+use crate::bet_intents::bet_target;
+use crate::bet_intents::BetIntentService;
+use crate::db;
+use crate::db::get_game_result_by_outpoint;
+use crate::db::get_game_results_by_input_tx_id;
 use crate::db::get_game_results_paginated;
+use crate::db::get_nonce;
 use crate::db::get_total_game_count;
+use crate::db::upsert_client_seed;
+use crate::games::over_under::BetTarget;
+use crate::games::over_under::Side;
+use crate::games::registry::GameRegistry;
+use crate::games::satoshis_number::RollVersion;
+use crate::games::satoshis_number::SatoshisNumberGame;
+use crate::games::Game;
+use crate::games::GameInput;
+use crate::games::GameOption;
+use crate::games::GameSettings;
+use crate::games::GameType;
+use crate::lottery_service::ticket_item;
+use crate::lottery_service::LotteryService;
+use crate::multiplier::MultiplierTable;
+use crate::multiplier::StoredMultiplier;
+use crate::nonce_service::hash_nonce;
 use crate::nonce_service::spawn_nonce_service;
+use crate::nonce_service::NonceService;
+use crate::payouts;
+use crate::payouts::PayoutExecutor;
 use crate::transaction_processor::spawn_transaction_monitor;
 use crate::websocket::SharedBroadcaster;
 use crate::websocket::WebSocketBroadcaster;
 use crate::ArkClient;
 use crate::Config;
+use anyhow::Context;
 use anyhow::Result;
+use ark_core::ArkAddress;
+use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
 use axum::extract::WebSocketUpgrade;
 use axum::http::HeaderValue;
 use axum::http::Method;
 use axum::http::StatusCode;
+use axum::response::IntoResponse;
 use axum::response::Json;
 use axum::response::Response;
 use axum::routing::get;
+use axum::routing::post;
 use axum::Router;
 use bitcoin::Amount;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::json;
 use serde_json::Value;
-use sha2::Digest;
-use sha2::Sha256;
 use sqlx::types::time::OffsetDateTime;
 use sqlx::Pool;
 use sqlx::Sqlite;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::net::TcpListener;
+use tokio::sync::mpsc;
 use tower_http::cors::CorsLayer;
 
 #[derive(Clone)]
@@ -37,18 +67,55 @@ pub struct AppState {
     pub pool: Pool<Sqlite>,
     pub broadcaster: SharedBroadcaster,
     pub nonce_service: crate::nonce_service::NonceService,
+    pub bet_intents: BetIntentService,
+    pub games: Arc<GameRegistry>,
+    /// Missing if the lottery is disabled
+    pub lottery: Option<LotteryService>,
     pub config: Config,
 }
 
 #[derive(Serialize)]
 struct GameAddressInfo {
     game_type: u8,
+    /// What a bet to this address is on, e.g. "2x" or "higher"
+    option: String,
     address: String,
     multiplier: String,
     multiplier_value: u64,
-    max_roll: u16,
+    /// Roll threshold, or the reference card of high/low, for the current nonce
+    max_roll: i64,
     win_probability: f64,
+    /// Share of the bets returned to players on average, in percent
+    rtp: f64,
+    /// 100 - `rtp`
+    house_edge: f64,
     max_bet_amount: u64,
+    /// Rolled values a bet to this address wins on, e.g. the numbers of a roulette bet
+    winning_outcomes: Option<Vec<i64>>,
+}
+
+#[derive(Serialize)]
+struct CatalogGame {
+    game_type: u8,
+    /// Identifier used in the config and the game history, e.g. "coin-flip"
+    id: String,
+    name: &'static str,
+    description: &'static str,
+    /// Settings the game is played with, e.g. its house edge in basis points
+    parameters: BTreeMap<&'static str, u64>,
+    /// How the outcome is drawn from the hash, for games whose odds do not follow from the roll
+    distribution: Option<&'static str>,
+    /// Bets get a one-time address from `POST /bet-intents` instead of a fixed address
+    address_per_bet: bool,
+    addresses: Vec<GameAddressInfo>,
+    limits: GameLimits,
+}
+
+#[derive(Serialize)]
+struct GameLimits {
+    max_payout_sats: u64,
+    /// Largest bet any of the fixed addresses takes, larger bets are kept as donations
+    max_bet_amount: Option<u64>,
 }
 
 #[derive(Deserialize)]
@@ -60,22 +127,51 @@ struct PaginationQuery {
 #[derive(Serialize, Clone)]
 pub struct GameHistoryItem {
     pub id: String,
+    pub game_type: String,
     #[serde(with = "bitcoin::amount::serde::as_sat")]
     pub amount_sent: Amount,
     pub multiplier: f64,
+    /// What the player bet on, e.g. "2x" or "higher"
+    pub option: String,
     pub result_number: i64,
+    /// Display form of `result_number` for games that do not roll a plain number, e.g. "heads"
+    pub outcome: Option<String>,
     pub target_number: i64,
     pub is_win: bool,
+    /// Everything paid to the player, including `refund`
     #[serde(with = "bitcoin::amount::serde::as_sat::opt")]
     pub payout: Option<Amount>,
+    /// Part of the bet returned without a win
+    #[serde(with = "bitcoin::amount::serde::as_sat::opt")]
+    pub refund: Option<Amount>,
+    /// Short name of the outcome, e.g. "win", missing for games stored before outcomes were
+    /// labelled
+    pub label: Option<String>,
+    /// Game specific details of the outcome
+    pub detail: Option<Value>,
     pub input_tx_id: String,
+    pub input_vout: Option<i64>,
     pub output_tx_id: Option<String>,
+    /// Output of `output_tx_id` that paid the player
+    pub output_vout: Option<i64>,
     pub nonce: Option<String>,
     pub nonce_hash: String,
+    pub client_seed: Option<String>,
+    /// How the roll was read from the hash, see [`RollVersion`]
+    pub roll_version: u8,
+    /// Position of the nonce in its hash chain, if it was taken from one
+    pub chain_position: Option<i64>,
     #[serde(with = "time::serde::timestamp")]
     pub timestamp: OffsetDateTime,
 }
 
+/// Parse the stored detail of an outcome, an unparsable detail is left out
+pub fn stored_detail(detail: Option<&str>) -> Option<Value> {
+    serde_json::from_str(detail?)
+        .inspect_err(|e| tracing::warn!("Invalid outcome detail: {e}"))
+        .ok()
+}
+
 #[derive(Serialize, Clone)]
 pub struct DonationItem {
     pub id: String,
@@ -87,11 +183,175 @@ pub struct DonationItem {
     pub timestamp: OffsetDateTime,
 }
 
+/// The crash point of a crash bet, sent before its result so the climb can be animated
+#[derive(Serialize, Clone)]
+pub struct CrashPointItem {
+    pub input_tx_id: String,
+    pub input_vout: u32,
+    /// In hundredths, e.g. 235 for 2.35x
+    pub crash_point: i64,
+    /// The multiplier the bet cashes out at, in hundredths
+    pub cashout_target: i64,
+    pub is_win: bool,
+    #[serde(with = "time::serde::timestamp")]
+    pub timestamp: OffsetDateTime,
+}
+
+/// The current jackpot, sent when a client connects and whenever the pot changes
+#[derive(Serialize, Clone)]
+pub struct JackpotItem {
+    #[serde(with = "bitcoin::amount::serde::as_sat")]
+    pub balance: Amount,
+    /// The bet that just won the pot, if that is what changed it
+    pub win: Option<JackpotWinItem>,
+    #[serde(with = "time::serde::timestamp")]
+    pub timestamp: OffsetDateTime,
+}
+
+#[derive(Serialize, Clone)]
+pub struct JackpotWinItem {
+    #[serde(with = "bitcoin::amount::serde::as_sat")]
+    pub amount: Amount,
+    pub winner: String,
+    pub input_tx_id: String,
+    pub input_vout: i64,
+}
+
+/// A round of the lottery. Its nonce is committed through `nonce_hash` when the round opens and
+/// only published once the round is drawn.
+#[derive(Serialize, Clone)]
+pub struct LotteryRoundItem {
+    pub id: i64,
+    /// "open", "closed" while waiting to be drawn, or "drawn"
+    pub status: &'static str,
+    /// Payments to this address buy tickets
+    pub address: String,
+    pub nonce_hash: String,
+    pub nonce: Option<String>,
+    #[serde(with = "bitcoin::amount::serde::as_sat")]
+    pub ticket_price: Amount,
+    /// Share of the pot kept by the house, in basis points
+    pub rake_bps: i64,
+    #[serde(with = "time::serde::timestamp")]
+    pub starts_at: OffsetDateTime,
+    #[serde(with = "time::serde::timestamp")]
+    pub ends_at: OffsetDateTime,
+    #[serde(with = "time::serde::timestamp::option")]
+    pub drawn_at: Option<OffsetDateTime>,
+    /// Everything paid in for the round
+    #[serde(with = "bitcoin::amount::serde::as_sat")]
+    pub pot: Amount,
+    pub ticket_count: i64,
+    pub purchase_count: usize,
+    /// Hash of every ticket purchase, see [`crate::lottery::tickets_hash`]
+    pub tickets_hash: Option<String>,
+    pub winning_ticket: Option<i64>,
+    /// Purchase holding the winning ticket
+    pub winner: Option<LotteryTicketItem>,
+    /// The pot less the rake, paid to the winner
+    #[serde(with = "bitcoin::amount::serde::as_sat::opt")]
+    pub payout: Option<Amount>,
+    pub payout_tx_id: Option<String>,
+}
+
+/// A payment to the lottery address
+#[derive(Serialize, Clone)]
+pub struct LotteryTicketItem {
+    /// Missing for a payment that came in without an open round, it is refunded
+    pub round_id: Option<i64>,
+    pub player: String,
+    pub input_tx_id: String,
+    pub input_vout: i64,
+    #[serde(with = "bitcoin::amount::serde::as_sat")]
+    pub amount: Amount,
+    /// Number of the first ticket within the round, counted from 0
+    pub first_ticket: i64,
+    pub ticket_count: i64,
+    #[serde(with = "time::serde::timestamp")]
+    pub timestamp: OffsetDateTime,
+}
+
+/// A duel between two players. Its nonce is the one that was active when the opponent's stake was
+/// created, published once the nonce is revealed.
+#[derive(Serialize, Clone)]
+pub struct DuelItem {
+    pub id: i64,
+    /// "open", "matched", "expired", "settled" or "refunded"
+    pub status: String,
+    #[serde(with = "bitcoin::amount::serde::as_sat")]
+    pub stake: Amount,
+    /// Share of both stakes kept by the house, in basis points
+    pub rake_bps: i64,
+    pub challenger: String,
+    pub challenger_tx_id: String,
+    pub challenger_vout: i64,
+    /// Only stakes created before then are matched against the challenge
+    #[serde(with = "time::serde::timestamp")]
+    pub expires_at: OffsetDateTime,
+    pub opponent: Option<String>,
+    pub opponent_tx_id: Option<String>,
+    pub opponent_vout: Option<i64>,
+    pub nonce_hash: Option<String>,
+    pub nonce: Option<String>,
+    pub winner: Option<String>,
+    /// Both stakes less the rake, paid to the winner
+    #[serde(with = "bitcoin::amount::serde::as_sat::opt")]
+    pub payout: Option<Amount>,
+    /// Transaction that paid the winner or refunded the challenger
+    pub payout_tx_id: Option<String>,
+    #[serde(with = "time::serde::timestamp")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::timestamp::option")]
+    pub matched_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::timestamp::option")]
+    pub settled_at: Option<OffsetDateTime>,
+}
+
+/// A duel as shown to players, its nonce is only included once it was revealed
+pub async fn duel_item(nonce_service: &NonceService, duel: &db::Duel) -> DuelItem {
+    let nonce = match duel.nonce {
+        Some(ref nonce) => nonce_service.get_revealable_nonce(nonce).await,
+        None => None,
+    };
+
+    DuelItem {
+        id: duel.id,
+        status: duel.status.clone(),
+        stake: Amount::from_sat(duel.stake_amount.max(0) as u64),
+        rake_bps: duel.rake_bps,
+        challenger: duel.challenger_address.clone(),
+        challenger_tx_id: duel.challenger_tx_id.clone(),
+        challenger_vout: duel.challenger_vout,
+        expires_at: duel.expires_at,
+        opponent: duel.opponent_address.clone(),
+        opponent_tx_id: duel.opponent_tx_id.clone(),
+        opponent_vout: duel.opponent_vout,
+        nonce_hash: duel.nonce_hash.clone(),
+        nonce,
+        winner: duel.winner_address.clone(),
+        payout: duel
+            .payout_amount
+            .map(|payout| Amount::from_sat(payout.max(0) as u64)),
+        payout_tx_id: duel.payout_tx_id.clone(),
+        created_at: duel.created_at,
+        matched_at: duel.matched_at,
+        settled_at: duel.settled_at,
+    }
+}
+
 #[derive(Serialize, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WebSocketMessage {
     GameResult(GameHistoryItem),
     Donation(DonationItem),
+    Jackpot(JackpotItem),
+    CrashPoint(CrashPointItem),
+    LotteryTicket(LotteryTicketItem),
+    LotteryDraw(LotteryRoundItem),
+    DuelOpened(DuelItem),
+    DuelMatched(DuelItem),
+    /// A duel whose winner was paid, or an expired one that was refunded
+    DuelSettled(DuelItem),
 }
 
 #[derive(Serialize)]
@@ -103,9 +363,193 @@ struct GameHistoryResponse {
     total_pages: i64,
 }
 
+#[derive(Deserialize)]
+struct VerifyRequest {
+    /// Stored game type, Satoshi's Number if omitted
+    game_type: Option<u8>,
+    nonce: String,
+    txid: String,
+    /// Output of the bet, omitted for games stored before bets were tracked per output
+    vout: Option<u32>,
+    /// Stored option index, see `GameOption::index`. Omitted for games stored before options were
+    /// recorded, which are decoded from their multiplier.
+    option: Option<u32>,
+    /// Stored multiplier value, e.g. 200 for 2.00x
+    multiplier: u64,
+    client_seed: Option<String>,
+    /// Stored roll version, see [`RollVersion`]. Defaults to the latest one, the one new games
+    /// are played with.
+    roll_version: Option<u8>,
+}
+
+#[derive(Deserialize)]
+struct BetIntentRequest {
+    /// "under" or "over"
+    side: String,
+    target: u16,
+}
+
+#[derive(Serialize)]
+struct BetIntentResponse {
+    /// One-time address to send the bet to
+    address: String,
+    side: String,
+    target: u16,
+    multiplier: f64,
+    multiplier_value: u64,
+    win_probability: f64,
+    max_bet_amount: u64,
+    #[serde(with = "time::serde::timestamp")]
+    expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::timestamp::option")]
+    paid_at: Option<OffsetDateTime>,
+    /// The intent ran out of time without a bet, bets sent to it now are refunded
+    expired: bool,
+}
+
+#[derive(Deserialize)]
+struct ClientSeedRequest {
+    address: String,
+    client_seed: String,
+    /// Hex encoded x-only key the default VTXO script of `address` is built from
+    public_key: String,
+    /// Hex encoded BIP-340 signature by `public_key` of the SHA256 of
+    /// [`client_seed_message`]
+    signature: String,
+    /// Unix timestamp at which the request was signed
+    signed_at: i64,
+}
+
+#[derive(Serialize)]
+struct ClientSeedResponse {
+    address: String,
+    client_seed: String,
+    #[serde(with = "time::serde::timestamp")]
+    updated_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum VerificationStatus {
+    /// The nonce matches its commitment and the recomputed roll matches the stored result
+    Verified,
+    /// The roll was recomputed from the provided inputs, there is no stored result to compare
+    Computed,
+    /// Either the nonce does not match its commitment or the stored result differs
+    Mismatch,
+    /// The nonce is still in use and will only be revealed once it is rotated
+    PendingReveal,
+    /// The transaction exceeded the max bet and was recorded as a donation, nothing was rolled
+    Donation,
+    /// No nonce was active when the bet was made, so it was refunded instead of played
+    Refunded,
+}
+
+/// Every step needed to go from nonce and txid to the game outcome
+#[derive(Serialize)]
+struct RollSteps {
+    /// `format!("{nonce}{txid}{client_seed}:{vout}")`, without the client seed if none was
+    /// registered and without the vout for games stored before bets were tracked per output
+    hash_input: String,
+    /// Hex encoded `sha256(hash_input)`
+    hash: String,
+    /// How the roll is read from the hash, see [`RollVersion`]
+    roll_version: u8,
+    /// Hex encoded bytes of the hash the roll is read from, the first 2 under roll version 1 and
+    /// the first 4 under version 2
+    roll_bytes: String,
+    /// The roll bytes as a big-endian number
+    rolled_number: u64,
+    /// What the result is compared against: the roll threshold, or the reference card dealt from
+    /// the nonce hash for high/low
+    target: i64,
+    is_win: bool,
+    /// What gets stored as the result: the rolled number, 0 for heads and 1 for tails, or the
+    /// drawn card
+    result_number: i64,
+    /// Display form of the result, e.g. the coin side of a coin flip
+    outcome: Option<String>,
+}
+
+#[derive(Serialize)]
+struct StoredOutcome {
+    rolled_number: i64,
+    is_win: bool,
+}
+
+#[derive(Serialize)]
+struct VerifyResponse {
+    status: VerificationStatus,
+    game_type: String,
+    option: String,
+    input_tx_id: String,
+    input_vout: Option<i64>,
+    multiplier: f64,
+    /// Stored game type, see [`GameType::from_discriminant`]
+    game_type_id: u8,
+    /// Stored option index, omitted for games stored before options were recorded
+    option_index: Option<u32>,
+    /// Stored multiplier value, e.g. 200 for 2.00x
+    multiplier_value: u64,
+    /// House edge of the game in basis points, omitted for games whose odds are set otherwise
+    house_edge_bps: Option<u16>,
+    nonce: Option<String>,
+    nonce_hash: String,
+    client_seed: Option<String>,
+    chain_position: Option<i64>,
+    /// Whether `sha256(nonce)` equals the committed nonce hash
+    nonce_hash_valid: Option<bool>,
+    #[serde(with = "time::serde::timestamp::option")]
+    expected_reveal_at: Option<OffsetDateTime>,
+    steps: Option<RollSteps>,
+    stored: Option<StoredOutcome>,
+}
+
+#[derive(Serialize)]
+struct NonceHistoryItem {
+    nonce_hash: String,
+    /// Only set once the nonce has been retired
+    nonce: Option<String>,
+    chain_position: Option<i64>,
+    #[serde(with = "time::serde::timestamp")]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::timestamp")]
+    expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::timestamp::option")]
+    activated_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::timestamp::option")]
+    retired_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::timestamp::option")]
+    revealed_at: Option<OffsetDateTime>,
+    /// Number of games evaluated against this nonce, donations and refunds excluded
+    game_count: i64,
+}
+
+#[derive(Serialize)]
+struct NonceHistoryResponse {
+    nonces: Vec<NonceHistoryItem>,
+    total: i64,
+    page: i64,
+    page_size: i64,
+    total_pages: i64,
+}
+
+/// Public commitment of a nonce hash chain. The seed is never published, the chain is verified
+/// by hashing each revealed nonce down to `terminal_hash`.
+#[derive(Serialize)]
+struct NonceChainItem {
+    terminal_hash: String,
+    length: i64,
+    /// Number of nonces used from this chain so far
+    position: i64,
+    #[serde(with = "time::serde::timestamp")]
+    created_at: OffsetDateTime,
+}
+
 #[derive(Serialize)]
 struct GameStatsItem {
     game_type: String,
+    /// Option of the address, e.g. "2x" or "higher"
     multiplier: String,
     address: String,
     number_of_games: usize,
@@ -117,6 +561,51 @@ struct GameStatsItem {
 struct StatsResponse {
     total_games: usize,
     game_stats: Vec<GameStatsItem>,
+    /// Missing if the jackpot is disabled
+    jackpot: Option<JackpotStats>,
+}
+
+#[derive(Serialize)]
+struct LotteryRoundResponse {
+    round: LotteryRoundItem,
+    /// Every purchase of the round in the order they were bought, which is the order they are
+    /// hashed in
+    tickets: Vec<LotteryTicketItem>,
+}
+
+#[derive(Serialize)]
+struct LotteryRoundsResponse {
+    rounds: Vec<LotteryRoundItem>,
+    total: i64,
+    page: i64,
+    page_size: i64,
+    total_pages: i64,
+}
+
+#[derive(Serialize)]
+struct DuelsResponse {
+    /// Stakes sent to this address open or match a duel
+    address: String,
+    rake_bps: u16,
+    timeout_minutes: u64,
+    /// Challenges waiting for an opponent, oldest first
+    open: Vec<DuelItem>,
+    /// Every duel, newest first
+    duels: Vec<DuelItem>,
+    total: i64,
+    page: i64,
+    page_size: i64,
+    total_pages: i64,
+}
+
+#[derive(Serialize)]
+struct JackpotStats {
+    #[serde(with = "bitcoin::amount::serde::as_sat")]
+    balance: Amount,
+    /// Share of every losing bet that goes into the pot, in basis points
+    contribution_bps: u16,
+    /// A bet wins the pot with a chance of 1 in `odds`
+    odds: u32,
 }
 
 pub async fn start_server(
@@ -129,6 +618,58 @@ pub async fn start_server(
     start_server_with_arc(ark_client_arc, port, pool, config).await
 }
 
+/// Refuse to start with multipliers that would decode or re-verify stored games against other
+/// odds than they were played with
+async fn check_stored_multipliers(
+    pool: &Pool<Sqlite>,
+    multipliers: &MultiplierTable,
+) -> Result<()> {
+    let stored = db::get_stored_multiplier_games(pool)
+        .await
+        .context("Failed to load stored multipliers")?
+        .into_iter()
+        .filter_map(|games| {
+            Some(StoredMultiplier {
+                index: u32::try_from(games.game_option?).ok()?,
+                value: u64::try_from(games.multiplier).ok()?,
+                roll_version: RollVersion::from_stored(games.roll_version)?,
+                highest_win: games.highest_win.and_then(|roll| u64::try_from(roll).ok()),
+                lowest_loss: games.lowest_loss.and_then(|roll| u64::try_from(roll).ok()),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    multipliers
+        .check_stored(&stored)
+        .context("Configured multipliers contradict stored games")?;
+
+    Ok(())
+}
+
+/// Store the configured house edges with the games that were played before edges were stored,
+/// which is the edge they were played with unless it was changed along with the upgrade
+async fn backfill_house_edges(pool: &Pool<Sqlite>, settings: &GameSettings) -> Result<()> {
+    for game_type in GameType::all() {
+        let Some(house_edge_bps) = settings.house_edge_bps(game_type) else {
+            continue;
+        };
+
+        let backfilled = db::backfill_house_edge(pool, game_type as i64, i64::from(house_edge_bps))
+            .await
+            .with_context(|| format!("Failed to backfill the house edge of {game_type}"))?;
+        if backfilled > 0 {
+            tracing::info!(
+                %game_type,
+                house_edge_bps,
+                backfilled,
+                "📝 Stored the house edge with earlier games"
+            );
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn start_server_with_arc(
     ark_client_arc: Arc<ArkClient>,
     port: u16,
@@ -142,13 +683,76 @@ pub async fn start_server_with_arc(
     let broadcaster = Arc::new(tokio::sync::RwLock::new(WebSocketBroadcaster::default()));
 
     // Start nonce service (generate new nonce every 24 hours)
-    let nonce_service = spawn_nonce_service(pool.clone(), 1, 1).await;
+    let nonce_service = spawn_nonce_service(
+        pool.clone(),
+        1,
+        1,
+        config.nonce_mode,
+        config.hash_chain_length,
+    )
+    .await?;
+
+    let games = Arc::new(config.game_registry());
+    check_stored_multipliers(&pool, &games.settings().multipliers).await?;
+    backfill_house_edges(&pool, games.settings()).await?;
+    for (game_type, game) in games.enabled() {
+        tracing::info!(%game_type, name = game.name(), "🎮 Game enabled");
+    }
+
+    // Bet intent addresses are added to the transaction monitor's subscription as they come in
+    let (subscription_updates, subscription_updates_rx) = mpsc::unbounded_channel();
+    let bet_intents = BetIntentService::new(
+        pool.clone(),
+        ark_client_arc.clone(),
+        games.settings().clone(),
+        config.bet_intent_ttl_minutes,
+        subscription_updates,
+    );
+    bet_intents.start_cleanup();
+
+    // Nothing sends payouts before the lottery and the transaction monitor start, so payouts a
+    // crash left in flight are settled right away
+    if let Err(e) = payouts::reconcile_in_flight(&pool, &ark_client_arc, time::Duration::ZERO).await
+    {
+        tracing::error!("🚨 Failed to reconcile in-flight payouts: {:#}", e);
+    }
+    let payout_executor = PayoutExecutor::start(pool.clone(), ark_client_arc.clone());
+
+    let lottery = config.lottery_settings().map(|settings| {
+        LotteryService::new(
+            pool.clone(),
+            ark_client_arc.clone(),
+            payout_executor.clone(),
+            broadcaster.clone(),
+            settings,
+            config.lottery_round_minutes,
+        )
+    });
+    if let Some(ref lottery) = lottery {
+        tracing::info!(
+            address = lottery.address().encode(),
+            round_minutes = config.lottery_round_minutes,
+            "🎟️ Lottery enabled"
+        );
+        lottery.start();
+    }
+    if let Some(settings) = config.duel_settings() {
+        tracing::info!(
+            address = ark_client_arc.get_duel_address().encode(),
+            rake_bps = settings.rake_bps,
+            timeout_minutes = settings.timeout_minutes,
+            "⚔️ Duels enabled"
+        );
+    }
 
     let state = AppState {
         ark_client: ark_client_arc.clone(),
         pool: pool.clone(),
         broadcaster: broadcaster.clone(),
         nonce_service: nonce_service.clone(),
+        bet_intents,
+        games: games.clone(),
+        lottery: lottery.clone(),
         config: config.clone(),
     };
 
@@ -181,6 +785,7 @@ pub async fn start_server_with_arc(
     // Start transaction monitoring in background
     spawn_transaction_monitor(
         ark_client_arc.clone(),
+        payout_executor,
         my_addresses,
         nonce_service,
         pool,
@@ -188,6 +793,11 @@ pub async fn start_server_with_arc(
         config.max_payout_sats,
         dust_amount,
         telegram_token,
+        games,
+        config.jackpot_settings(),
+        lottery,
+        config.duel_settings(),
+        subscription_updates_rx,
     )
     .await;
     tracing::info!("🔍 Transaction monitoring started with subscriptions");
@@ -226,10 +836,24 @@ pub async fn start_server_with_arc(
         .route("/address", get(get_address))
         .route("/boarding-address", get(get_boarding_address))
         .route("/game-addresses", get(get_game_addresses))
+        .route("/catalog", get(get_catalog))
         .route("/games", get(get_games))
         .route("/stats", get(get_stats))
         .route("/version", get(get_version))
         .route("/balance", get(get_balance))
+        .route("/verify", post(verify_roll))
+        .route("/verify/{input_txid}", get(verify_game))
+        .route("/verify/{input_txid}/{vout}", get(verify_game_output))
+        .route("/bet-intents", post(create_bet_intent))
+        .route("/bet-intents/{address}", get(get_bet_intent))
+        .route("/client-seed", post(set_client_seed))
+        .route("/client-seed/{address}", get(get_client_seed))
+        .route("/nonces", get(get_nonces))
+        .route("/nonce-chains", get(get_nonce_chains))
+        .route("/lottery/current", get(get_current_lottery_round))
+        .route("/lottery/rounds", get(get_lottery_rounds))
+        .route("/lottery/rounds/{id}", get(get_lottery_round))
+        .route("/duels", get(get_duels))
         .route("/ws", get(websocket_handler))
         .layer(cors)
         .with_state(state);
@@ -241,10 +865,18 @@ pub async fn start_server_with_arc(
     tracing::info!("📍 Address endpoint: http://{addr}/address");
     tracing::info!("🚢 Boarding address endpoint: http://{addr}/boarding-address");
     tracing::info!("🎮 Game addresses endpoint: http://{addr}/game-addresses");
+    tracing::info!("📚 Catalog endpoint: http://{addr}/catalog");
     tracing::info!("📊 Games history endpoint: http://{addr}/games");
     tracing::info!("📈 Stats endpoint: http://{addr}/stats");
     tracing::info!("ℹ️ Version endpoint: http://{addr}/version");
     tracing::info!("💰 Balance endpoint: http://{addr}/balance");
+    tracing::info!("🔎 Verify endpoint: http://{addr}/verify/{{input_txid}}/{{vout}}");
+    tracing::info!("🎯 Bet intents endpoint: http://{addr}/bet-intents");
+    tracing::info!("🌱 Client seed endpoint: http://{addr}/client-seed");
+    tracing::info!("🔐 Nonces endpoint: http://{addr}/nonces");
+    tracing::info!("⛓️ Nonce chains endpoint: http://{addr}/nonce-chains");
+    tracing::info!("🎟️ Lottery endpoint: http://{addr}/lottery/current");
+    tracing::info!("⚔️ Duels endpoint: http://{addr}/duels");
     tracing::info!("🔌 WebSocket endpoint: ws://{addr}/ws");
 
     axum::serve(listener, app).await?;
@@ -268,37 +900,124 @@ async fn get_boarding_address(State(state): State<AppState>) -> Result<Json<Valu
     })))
 }
 
+fn game_address_info(
+    game: &dyn Game,
+    game_type: GameType,
+    option: GameOption,
+    address: ArkAddress,
+    nonce_hash: &str,
+    max_payout_sats: u64,
+) -> GameAddressInfo {
+    let payout_multiplier = game.payout_multiplier(nonce_hash, &option);
+    let win_probability = game.win_probability(nonce_hash, &option) * 100.0;
+    let rtp = game.rtp(nonce_hash, &option) * 100.0;
+    // Calculate max bet amount: max_payout * 100 / multiplier
+    let max_bet_amount = (max_payout_sats * 100)
+        .checked_div(payout_multiplier)
+        .unwrap_or(0);
+
+    GameAddressInfo {
+        game_type: game_type as u8,
+        option: option.to_string(),
+        address: address.encode(),
+        multiplier: match option {
+            GameOption::Multiplier(multiplier) => multiplier.to_string(),
+            _ => format!("{:.2}x", payout_multiplier as f64 / 100.0),
+        },
+        multiplier_value: payout_multiplier,
+        max_roll: game.target(nonce_hash, &option),
+        win_probability,
+        rtp,
+        house_edge: 100.0 - rtp,
+        max_bet_amount,
+        winning_outcomes: game.winning_outcomes(&option),
+    }
+}
+
 async fn get_game_addresses(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     let game_addresses = state.ark_client.get_game_addresses();
+    // High/low deals its reference card from the nonce, so odds are only valid for the current one
+    let nonce_hash = state.nonce_service.get_current_nonce_hash().await;
 
     let addresses: Vec<GameAddressInfo> = game_addresses
         .into_iter()
-        .map(|(game_type, multiplier, address)| {
-            let win_probability = multiplier.get_lower_than() as f64 / 65536.0 * 100.0;
-            // Calculate max bet amount: max_payout * 100 / multiplier
-            let max_bet_amount = (state.config.max_payout_sats * 100) / multiplier.multiplier();
-
-            GameAddressInfo {
-                game_type: game_type as u8,
-                address: address.encode(),
-                multiplier: multiplier.to_string(),
-                multiplier_value: multiplier.multiplier(),
-                max_roll: multiplier.get_lower_than(),
-                win_probability,
-                max_bet_amount,
-            }
+        .filter(|(game_type, _, _)| state.games.is_enabled(*game_type))
+        .map(|(game_type, option, address)| {
+            game_address_info(
+                state.games.get(game_type),
+                game_type,
+                option,
+                address,
+                &nonce_hash,
+                state.config.max_payout_sats,
+            )
         })
         .collect();
 
     Ok(Json(json!({
         "game_addresses": addresses,
+        "nonce_hash": nonce_hash,
         "info": {
-            "roll_range": "0-65535",
-            "win_condition": "rolled_number < max_roll"
+            "roll_version": RollVersion::LATEST as u8,
+            "roll_range": format!("0-{}", RollVersion::LATEST.rolls() - 1),
+            "win_condition": "rolled_number < max_roll",
+            "high_low": "a card from 1 (A) to 13 (K) is drawn, higher or lower than the reference card max_roll wins"
         }
     })))
 }
 
+async fn get_catalog(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let game_addresses = state.ark_client.get_game_addresses();
+    let nonce_hash = state.nonce_service.get_current_nonce_hash().await;
+
+    let games: Vec<CatalogGame> = state
+        .games
+        .enabled()
+        .map(|(game_type, game)| {
+            let addresses: Vec<GameAddressInfo> = game_addresses
+                .iter()
+                .filter(|(address_game_type, _, _)| *address_game_type == game_type)
+                .map(|(_, option, address)| {
+                    game_address_info(
+                        game,
+                        game_type,
+                        *option,
+                        *address,
+                        &nonce_hash,
+                        state.config.max_payout_sats,
+                    )
+                })
+                .collect();
+
+            CatalogGame {
+                game_type: game_type as u8,
+                id: game_type.to_string(),
+                name: game.name(),
+                description: game.description(),
+                parameters: game
+                    .parameters()
+                    .into_iter()
+                    .map(|parameter| (parameter.name, parameter.value))
+                    .collect(),
+                distribution: game.distribution(),
+                address_per_bet: game_type
+                    .options(&state.games.settings().multipliers)
+                    .is_empty(),
+                limits: GameLimits {
+                    max_payout_sats: state.config.max_payout_sats,
+                    max_bet_amount: addresses.iter().map(|address| address.max_bet_amount).max(),
+                },
+                addresses,
+            }
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "games": games,
+        "nonce_hash": nonce_hash,
+    })))
+}
+
 async fn get_games(
     State(state): State<AppState>,
     Query(params): Query<PaginationQuery>,
@@ -316,39 +1035,58 @@ async fn get_games(
 
     let total_pages = (total as f64 / page_size as f64).ceil() as i64;
 
+    let settings = state.games.settings();
     let mut game_items: Vec<GameHistoryItem> = Vec::new();
 
     for game in games {
-        use crate::key_derivation::Multiplier;
-        let multiplier = Multiplier::from_value(game.multiplier as u64).ok_or_else(|| {
-            tracing::error!("Unknown multiplier value in database: {}", game.multiplier);
+        let game_type = GameType::from_discriminant(game.game_type).ok_or_else(|| {
+            tracing::error!("Unknown game type in database: {}", game.game_type);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let option = game_type
+            .stored_option(game.game_option, game.multiplier, &settings.multipliers)
+            .ok_or_else(|| {
+                tracing::error!(
+                    "Unknown option in database: {:?} (multiplier {})",
+                    game.game_option,
+                    game.multiplier
+                );
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        let roll_version = RollVersion::from_stored(game.roll_version).ok_or_else(|| {
+            tracing::error!("Unknown roll version in database: {}", game.roll_version);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
-        let target_number = multiplier.get_lower_than() as i64;
 
         let revealable_nonce = state.nonce_service.get_revealable_nonce(&game.nonce).await;
-        let nonce_hash = if revealable_nonce.is_some() {
-            // If we can reveal the nonce, calculate its hash for verification
-            let mut hasher = Sha256::new();
-            hasher.update(&game.nonce);
-            format!("{:x}", hasher.finalize())
-        } else {
-            // If we can't reveal it, it's the current nonce, so get its hash
-            state.nonce_service.get_current_nonce_hash().await
-        };
+        // The commitment is public either way, the game may be bound to an older nonce
+        let nonce_hash = hash_nonce(&game.nonce);
+        let played = state.games.get(game_type);
+        let target_number = played.target_for_version(&nonce_hash, &option, roll_version);
 
         game_items.push(GameHistoryItem {
             id: game.id.to_string(),
+            game_type: game_type.to_string(),
             amount_sent: Amount::from_sat(game.bet_amount as u64),
             multiplier: game.multiplier as f64 / 100.0,
+            option: option.to_string(),
             result_number: game.rolled_number,
+            outcome: played.outcome(game.rolled_number),
             target_number,
             is_win: game.is_winner,
             payout: game.winning_amount.map(|a| Amount::from_sat(a as u64)),
+            refund: game.refund_amount.map(|a| Amount::from_sat(a as u64)),
+            label: game.outcome_label,
+            detail: stored_detail(game.outcome_detail.as_deref()),
             input_tx_id: game.input_tx_id,
+            input_vout: game.input_vout,
             output_tx_id: game.output_tx_id,
+            output_vout: game.output_vout,
             nonce: revealable_nonce,
             nonce_hash,
+            client_seed: game.client_seed,
+            roll_version: roll_version as u8,
+            chain_position: game.chain_position,
             timestamp: game.timestamp,
         });
     }
@@ -381,7 +1119,7 @@ async fn get_stats(State(state): State<AppState>) -> Result<Json<StatsResponse>,
     let total_games = vtxos.len();
     let mut game_stats = Vec::new();
 
-    for (game_type, multiplier, ark_address) in game_addresses {
+    for (game_type, option, ark_address) in game_addresses {
         let per_address: Vec<_> = vtxos
             .iter()
             .filter(|vtxo| vtxo.script == ark_address.to_p2tr_script_pubkey())
@@ -391,16 +1129,640 @@ async fn get_stats(State(state): State<AppState>) -> Result<Json<StatsResponse>,
 
         game_stats.push(GameStatsItem {
             game_type: game_type.to_string(),
-            multiplier: multiplier.to_string(),
+            multiplier: option.to_string(),
             address: ark_address.encode(),
             number_of_games: per_address.len(),
             total_received,
         });
     }
 
+    let jackpot_settings = state.config.jackpot_settings();
+    let jackpot = if jackpot_settings.is_enabled() {
+        let balance = db::get_jackpot_balance(&state.pool).await.map_err(|e| {
+            tracing::error!("Failed to get jackpot balance: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        Some(JackpotStats {
+            balance: Amount::from_sat(balance.max(0) as u64),
+            contribution_bps: jackpot_settings.contribution_bps,
+            odds: jackpot_settings.odds,
+        })
+    } else {
+        None
+    };
+
     Ok(Json(StatsResponse {
         total_games,
         game_stats,
+        jackpot,
+    }))
+}
+
+/// Recompute the roll of a game and show each step of the derivation
+fn compute_roll_steps(game: &dyn Game, input: &GameInput, option: &GameOption) -> RollSteps {
+    let breakdown = SatoshisNumberGame::roll(input);
+    // The payout is not shown, only the roll
+    let evaluation = game.evaluate(input, option, 0);
+
+    RollSteps {
+        hash_input: breakdown.hash_input,
+        hash: hex::encode(breakdown.hash),
+        roll_version: input.roll_version as u8,
+        roll_bytes: hex::encode(&breakdown.hash[..input.roll_version.roll_bytes()]),
+        rolled_number: breakdown.roll,
+        target: game.target_for_version(&hash_nonce(input.nonce), option, input.roll_version),
+        is_win: evaluation.is_win,
+        result_number: evaluation.rolled_value,
+        outcome: game.outcome(evaluation.rolled_value),
+    }
+}
+
+/// Verify the bet of a transaction that paid a single one. A transaction that paid several is
+/// answered with 409 and the outpoints of its bets, see [`verify_game_output`].
+async fn verify_game(
+    State(state): State<AppState>,
+    Path(input_txid): Path<String>,
+) -> Result<Response, StatusCode> {
+    let mut games = get_game_results_by_input_tx_id(&state.pool, &input_txid)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load game {input_txid} for verification: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if games.len() > 1 {
+        let outpoints = games
+            .iter()
+            .map(|game| match game.input_vout {
+                Some(vout) => format!("{}:{vout}", game.input_tx_id),
+                None => game.input_tx_id.clone(),
+            })
+            .collect::<Vec<_>>();
+        let body = json!({
+            "error": "The transaction paid several bets, verify one by its output",
+            "outpoints": outpoints,
+        });
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+
+    let game = games.pop().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(verify_stored_game(&state, game).await?.into_response())
+}
+
+async fn verify_game_output(
+    State(state): State<AppState>,
+    Path((input_txid, vout)): Path<(String, u32)>,
+) -> Result<Json<VerifyResponse>, StatusCode> {
+    let game = get_game_result_by_outpoint(&state.pool, &input_txid, vout as i64)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load game {input_txid}:{vout} for verification: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    verify_stored_game(&state, game).await
+}
+
+async fn verify_stored_game(
+    state: &AppState,
+    game: db::GameResult,
+) -> Result<Json<VerifyResponse>, StatusCode> {
+    let settings = state.games.settings();
+    let game_type = GameType::from_discriminant(game.game_type).ok_or_else(|| {
+        tracing::error!("Unknown game type in database: {}", game.game_type);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let option = game_type
+        .stored_option(game.game_option, game.multiplier, &settings.multipliers)
+        .ok_or_else(|| {
+            tracing::error!(
+                "Unknown option in database: {:?} (multiplier {})",
+                game.game_option,
+                game.multiplier
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let committed_nonce = get_nonce(&state.pool, &game.nonce).await.map_err(|e| {
+        tracing::error!("Failed to load nonce for verification: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Games stored before edges were recorded get theirs at startup, see [`backfill_house_edges`]
+    let house_edge_bps = game
+        .house_edge_bps
+        .and_then(|bps| u16::try_from(bps).ok())
+        .or(settings.house_edge_bps(game_type));
+
+    let mut response = VerifyResponse {
+        status: VerificationStatus::Verified,
+        game_type: game_type.to_string(),
+        option: option.to_string(),
+        input_tx_id: game.input_tx_id.clone(),
+        input_vout: game.input_vout,
+        multiplier: game.multiplier as f64 / 100.0,
+        game_type_id: game_type as u8,
+        option_index: game.game_option.map(|index| index as u32),
+        multiplier_value: game.multiplier as u64,
+        house_edge_bps,
+        nonce: None,
+        nonce_hash: String::new(),
+        client_seed: game.client_seed.clone(),
+        chain_position: game.chain_position,
+        nonce_hash_valid: None,
+        expected_reveal_at: None,
+        steps: None,
+        stored: Some(StoredOutcome {
+            rolled_number: game.rolled_number,
+            is_win: game.is_winner,
+        }),
+    };
+
+    if game.rolled_number == db::REFUNDED_ROLL {
+        response.status = VerificationStatus::Refunded;
+        return Ok(Json(response));
+    }
+
+    let revealable_nonce = state.nonce_service.get_revealable_nonce(&game.nonce).await;
+    let Some(nonce) = revealable_nonce else {
+        response.status = VerificationStatus::PendingReveal;
+        response.nonce_hash = hash_nonce(&game.nonce);
+        response.expected_reveal_at = committed_nonce
+            .as_ref()
+            .map(|n| state.nonce_service.expected_reveal_at(n));
+        return Ok(Json(response));
+    };
+
+    let computed_hash = hash_nonce(&nonce);
+    let nonce_hash_valid = committed_nonce
+        .as_ref()
+        .is_some_and(|n| n.nonce_hash == computed_hash);
+    response.nonce_hash = committed_nonce
+        .map(|n| n.nonce_hash)
+        .unwrap_or(computed_hash);
+    response.nonce_hash_valid = Some(nonce_hash_valid);
+    response.nonce = Some(nonce.clone());
+
+    if game.rolled_number == -1 {
+        response.status = VerificationStatus::Donation;
+        return Ok(Json(response));
+    }
+
+    let roll_version = RollVersion::from_stored(game.roll_version).ok_or_else(|| {
+        tracing::error!("Unknown roll version in database: {}", game.roll_version);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let input = GameInput {
+        nonce: &nonce,
+        txid: &game.input_tx_id,
+        vout: game.input_vout.map(|vout| vout as u32),
+        client_seed: game.client_seed.as_deref(),
+        roll_version,
+    };
+    let game_played = state.games.played_with(game_type, house_edge_bps);
+    let steps = compute_roll_steps(game_played.as_ref(), &input, &option);
+    let matches_stored =
+        steps.result_number == game.rolled_number && steps.is_win == game.is_winner;
+    if !nonce_hash_valid || !matches_stored {
+        response.status = VerificationStatus::Mismatch;
+    }
+    response.steps = Some(steps);
+
+    Ok(Json(response))
+}
+
+async fn verify_roll(
+    State(state): State<AppState>,
+    Json(request): Json<VerifyRequest>,
+) -> Result<Json<VerifyResponse>, StatusCode> {
+    let game_type = match request.game_type {
+        Some(game_type) => {
+            GameType::from_discriminant(game_type as i64).ok_or(StatusCode::BAD_REQUEST)?
+        }
+        None => GameType::SatoshisNumber,
+    };
+    let settings = state.games.settings();
+    let option = game_type
+        .stored_option(
+            request.option.map(i64::from),
+            request.multiplier as i64,
+            &settings.multipliers,
+        )
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let nonce_hash = hash_nonce(&request.nonce);
+    let payout_multiplier = state
+        .games
+        .get(game_type)
+        .payout_multiplier(&nonce_hash, &option);
+
+    let roll_version = match request.roll_version {
+        Some(version) => RollVersion::from_stored(version as i64).ok_or(StatusCode::BAD_REQUEST)?,
+        None => RollVersion::LATEST,
+    };
+    let input = GameInput {
+        nonce: &request.nonce,
+        txid: &request.txid,
+        vout: request.vout,
+        client_seed: request.client_seed.as_deref(),
+        roll_version,
+    };
+    let steps = compute_roll_steps(state.games.get(game_type), &input, &option);
+
+    Ok(Json(VerifyResponse {
+        status: VerificationStatus::Computed,
+        game_type: game_type.to_string(),
+        option: option.to_string(),
+        input_tx_id: request.txid,
+        input_vout: request.vout.map(i64::from),
+        multiplier: payout_multiplier as f64 / 100.0,
+        game_type_id: game_type as u8,
+        option_index: request.option,
+        multiplier_value: request.multiplier,
+        house_edge_bps: settings.house_edge_bps(game_type),
+        nonce_hash,
+        nonce: Some(request.nonce),
+        client_seed: request.client_seed,
+        chain_position: None,
+        nonce_hash_valid: None,
+        expected_reveal_at: None,
+        steps: Some(steps),
+        stored: None,
+    }))
+}
+
+const MAX_CLIENT_SEED_LENGTH: usize = 64;
+
+/// How far `signed_at` of a client seed request may be from the time it is received, so that a
+/// signed request cannot be replayed later to put back an old seed
+const MAX_CLIENT_SEED_SIGNATURE_AGE: time::Duration = time::Duration::minutes(5);
+
+/// Message a player signs to register `client_seed` for `address`
+fn client_seed_message(address: &str, client_seed: &str, signed_at: i64) -> String {
+    format!("satsday client seed\n{address}\n{client_seed}\n{signed_at}")
+}
+
+async fn set_client_seed(
+    State(state): State<AppState>,
+    Json(request): Json<ClientSeedRequest>,
+) -> Result<Json<ClientSeedResponse>, StatusCode> {
+    let ark_address = ArkAddress::decode(&request.address).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let address = ark_address.encode();
+
+    let client_seed = request.client_seed.trim();
+    if client_seed.is_empty()
+        || client_seed.len() > MAX_CLIENT_SEED_LENGTH
+        || !client_seed
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let signed_at = OffsetDateTime::from_unix_timestamp(request.signed_at)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if (now - signed_at).abs() > MAX_CLIENT_SEED_SIGNATURE_AGE {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // Only the owner of the address may pick the seed its bets are played with
+    let public_key = request
+        .public_key
+        .parse::<bitcoin::XOnlyPublicKey>()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let signature = request
+        .signature
+        .parse::<bitcoin::secp256k1::schnorr::Signature>()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let message = client_seed_message(&address, client_seed, request.signed_at);
+    if let Err(e) = state.ark_client.verify_address_signature(
+        &ark_address,
+        public_key,
+        message.as_bytes(),
+        &signature,
+    ) {
+        tracing::warn!(address, "Rejected client seed: {e:#}");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    upsert_client_seed(&state.pool, &address, client_seed, now)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to store client seed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!(address, client_seed, "🌱 Registered client seed");
+
+    get_client_seed(State(state), Path(address)).await
+}
+
+fn bet_intent_response(
+    intent: db::BetIntent,
+    max_payout_sats: u64,
+) -> Result<BetIntentResponse, StatusCode> {
+    let bet = bet_target(&intent).ok_or_else(|| {
+        tracing::error!("Invalid bet stored for intent {}", intent.id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let payout_multiplier = intent.payout_multiplier as u64;
+
+    Ok(BetIntentResponse {
+        address: intent.address,
+        side: bet.side.to_string(),
+        target: bet.target,
+        multiplier: payout_multiplier as f64 / 100.0,
+        multiplier_value: payout_multiplier,
+        win_probability: bet.winning_rolls() as f64 / 65536.0 * 100.0,
+        max_bet_amount: (max_payout_sats * 100)
+            .checked_div(payout_multiplier)
+            .unwrap_or(0),
+        expires_at: intent.expires_at,
+        paid_at: intent.paid_at,
+        expired: intent.expired_at.is_some(),
+    })
+}
+
+async fn create_bet_intent(
+    State(state): State<AppState>,
+    Json(request): Json<BetIntentRequest>,
+) -> Result<Json<BetIntentResponse>, StatusCode> {
+    if !state.games.is_enabled(GameType::OverUnder) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let side = request
+        .side
+        .parse::<Side>()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let bet = BetTarget {
+        side,
+        target: request.target,
+    };
+    if state.bet_intents.offered_payout(&bet).is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let intent = state.bet_intents.create(bet).await.map_err(|e| {
+        tracing::error!("Failed to create bet intent: {e:#}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(bet_intent_response(
+        intent,
+        state.config.max_payout_sats,
+    )?))
+}
+
+async fn get_bet_intent(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<BetIntentResponse>, StatusCode> {
+    let intent = db::get_bet_intent_by_address(&state.pool, &address)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load bet intent: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(bet_intent_response(
+        intent,
+        state.config.max_payout_sats,
+    )?))
+}
+
+async fn get_client_seed(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<ClientSeedResponse>, StatusCode> {
+    let client_seed = db::get_client_seed(&state.pool, &address)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load client seed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ClientSeedResponse {
+        address: client_seed.address,
+        client_seed: client_seed.client_seed,
+        updated_at: client_seed.updated_at,
+    }))
+}
+
+async fn get_nonces(
+    State(state): State<AppState>,
+    Query(params): Query<PaginationQuery>,
+) -> Result<Json<NonceHistoryResponse>, StatusCode> {
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = params.page_size.unwrap_or(20).clamp(1, 100);
+
+    let nonces = db::get_nonces_paginated(&state.pool, page, page_size)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load nonces: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let total = db::get_total_nonce_count(&state.pool).await.map_err(|e| {
+        tracing::error!("Failed to count nonces: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let total_pages = (total as f64 / page_size as f64).ceil() as i64;
+
+    let nonce_items = nonces
+        .into_iter()
+        .map(|entry| NonceHistoryItem {
+            nonce_hash: entry.nonce_hash,
+            nonce: entry.revealed_at.map(|_| entry.nonce),
+            chain_position: entry.chain_position,
+            created_at: entry.created_at,
+            expires_at: entry.expires_at,
+            activated_at: entry.activated_at,
+            retired_at: entry.retired_at,
+            revealed_at: entry.revealed_at,
+            game_count: entry.game_count,
+        })
+        .collect();
+
+    Ok(Json(NonceHistoryResponse {
+        nonces: nonce_items,
+        total,
+        page,
+        page_size,
+        total_pages,
+    }))
+}
+
+async fn get_nonce_chains(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<NonceChainItem>>, StatusCode> {
+    let chains = db::get_nonce_chains(&state.pool).await.map_err(|e| {
+        tracing::error!("Failed to load nonce chains: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let chains = chains
+        .into_iter()
+        .map(|chain| NonceChainItem {
+            terminal_hash: chain.terminal_hash,
+            length: chain.length,
+            position: chain.position,
+            created_at: chain.created_at,
+        })
+        .collect();
+
+    Ok(Json(chains))
+}
+
+/// A round together with its tickets
+async fn lottery_round_response(
+    state: &AppState,
+    round: db::LotteryRound,
+) -> Result<LotteryRoundResponse, StatusCode> {
+    let lottery = state.lottery.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let tickets = db::get_lottery_tickets(&state.pool, round.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load lottery tickets: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(LotteryRoundResponse {
+        round: lottery.round_item(&round, &tickets),
+        tickets: tickets.iter().map(ticket_item).collect(),
+    })
+}
+
+async fn get_current_lottery_round(
+    State(state): State<AppState>,
+) -> Result<Json<LotteryRoundResponse>, StatusCode> {
+    if state.lottery.is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let round = db::get_latest_lottery_round(&state.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load lottery round: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(lottery_round_response(&state, round).await?))
+}
+
+async fn get_lottery_round(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<LotteryRoundResponse>, StatusCode> {
+    let round = db::get_lottery_round(&state.pool, id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load lottery round: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(lottery_round_response(&state, round).await?))
+}
+
+/// Drawn rounds, newest first
+async fn get_lottery_rounds(
+    State(state): State<AppState>,
+    Query(params): Query<PaginationQuery>,
+) -> Result<Json<LotteryRoundsResponse>, StatusCode> {
+    let lottery = state.lottery.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = params.page_size.unwrap_or(20).clamp(1, 100);
+
+    let rounds = db::get_drawn_lottery_rounds_paginated(&state.pool, page, page_size)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load lottery rounds: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let total = db::get_total_drawn_lottery_round_count(&state.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to count lottery rounds: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let total_pages = (total as f64 / page_size as f64).ceil() as i64;
+
+    let mut round_items = Vec::new();
+    for round in rounds {
+        let tickets = db::get_lottery_tickets(&state.pool, round.id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to load lottery tickets: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        round_items.push(lottery.round_item(&round, &tickets));
+    }
+
+    Ok(Json(LotteryRoundsResponse {
+        rounds: round_items,
+        total,
+        page,
+        page_size,
+        total_pages,
+    }))
+}
+
+/// Open challenges and every duel so far
+async fn get_duels(
+    State(state): State<AppState>,
+    Query(params): Query<PaginationQuery>,
+) -> Result<Json<DuelsResponse>, StatusCode> {
+    let settings = state.config.duel_settings().ok_or(StatusCode::NOT_FOUND)?;
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = params.page_size.unwrap_or(20).clamp(1, 100);
+
+    let open = db::get_open_duels(&state.pool).await.map_err(|e| {
+        tracing::error!("Failed to load open duels: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let duels = db::get_duels_paginated(&state.pool, page, page_size)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load duels: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let total = db::get_total_duel_count(&state.pool).await.map_err(|e| {
+        tracing::error!("Failed to count duels: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let total_pages = (total as f64 / page_size as f64).ceil() as i64;
+
+    let mut open_items = Vec::new();
+    for duel in &open {
+        open_items.push(duel_item(&state.nonce_service, duel).await);
+    }
+    let mut duel_items = Vec::new();
+    for duel in &duels {
+        duel_items.push(duel_item(&state.nonce_service, duel).await);
+    }
+
+    Ok(Json(DuelsResponse {
+        address: state.ark_client.get_duel_address().encode(),
+        rake_bps: settings.rake_bps,
+        timeout_minutes: settings.timeout_minutes,
+        open: open_items,
+        duels: duel_items,
+        total,
+        page,
+        page_size,
+        total_pages,
     }))
 }
 
@@ -448,49 +1810,63 @@ async fn handle_websocket(socket: axum::extract::ws::WebSocket, state: AppState)
     // Send historical data first
     match get_game_results_paginated(&state.pool, 1, 20).await {
         Ok(games) => {
+            let settings = state.games.settings();
             let mut game_items: Vec<GameHistoryItem> = Vec::new();
 
             for game in games {
-                use crate::key_derivation::Multiplier;
-                let multiplier = Multiplier::from_value(game.multiplier as u64).ok_or_else(|| {
-                    tracing::error!("Unknown multiplier value in database: {}", game.multiplier);
-                });
-
-                let multiplier = match multiplier {
-                    Ok(m) => m,
-                    Err(_) => {
-                        tracing::warn!(
-                            "Skipping game with invalid multiplier: {}",
-                            game.multiplier
-                        );
-                        continue;
-                    }
+                let Some(game_type) = GameType::from_discriminant(game.game_type) else {
+                    tracing::warn!("Skipping game with invalid game type: {}", game.game_type);
+                    continue;
+                };
+                let Some(option) = game_type.stored_option(
+                    game.game_option,
+                    game.multiplier,
+                    &settings.multipliers,
+                ) else {
+                    tracing::warn!(
+                        "Skipping game with invalid option: {:?} (multiplier {})",
+                        game.game_option,
+                        game.multiplier
+                    );
+                    continue;
+                };
+                let Some(roll_version) = RollVersion::from_stored(game.roll_version) else {
+                    tracing::warn!(
+                        "Skipping game with invalid roll version: {}",
+                        game.roll_version
+                    );
+                    continue;
                 };
-                let target_number = multiplier.get_lower_than() as i64;
 
                 let revealable_nonce = state.nonce_service.get_revealable_nonce(&game.nonce).await;
-                let nonce_hash = if revealable_nonce.is_some() {
-                    // If we can reveal the nonce, calculate its hash for verification
-                    let mut hasher = Sha256::new();
-                    hasher.update(&game.nonce);
-                    format!("{:x}", hasher.finalize())
-                } else {
-                    // If we can't reveal it, it's the current nonce, so get its hash
-                    state.nonce_service.get_current_nonce_hash().await
-                };
+                // The commitment is public either way, the game may be bound to an older nonce
+                let nonce_hash = hash_nonce(&game.nonce);
+                let played = state.games.get(game_type);
+                let target_number = played.target_for_version(&nonce_hash, &option, roll_version);
 
                 game_items.push(GameHistoryItem {
                     id: game.id.to_string(),
+                    game_type: game_type.to_string(),
                     amount_sent: Amount::from_sat(game.bet_amount as u64),
                     multiplier: game.multiplier as f64 / 100.0,
+                    option: option.to_string(),
                     result_number: game.rolled_number,
+                    outcome: played.outcome(game.rolled_number),
                     target_number,
                     is_win: game.is_winner,
                     payout: game.winning_amount.map(|a| Amount::from_sat(a as u64)),
+                    refund: game.refund_amount.map(|a| Amount::from_sat(a as u64)),
+                    label: game.outcome_label,
+                    detail: stored_detail(game.outcome_detail.as_deref()),
                     input_tx_id: game.input_tx_id,
+                    input_vout: game.input_vout,
                     output_tx_id: game.output_tx_id,
+                    output_vout: game.output_vout,
                     nonce: revealable_nonce,
                     nonce_hash,
+                    client_seed: game.client_seed,
+                    roll_version: roll_version as u8,
+                    chain_position: game.chain_position,
                     timestamp: game.timestamp,
                 });
             }
@@ -510,6 +1886,25 @@ async fn handle_websocket(socket: axum::extract::ws::WebSocket, state: AppState)
         }
     }
 
+    if state.config.jackpot_settings().is_enabled() {
+        match db::get_jackpot_balance(&state.pool).await {
+            Ok(balance) => {
+                let jackpot_msg = WebSocketMessage::Jackpot(JackpotItem {
+                    balance: Amount::from_sat(balance.max(0) as u64),
+                    win: None,
+                    timestamp: OffsetDateTime::now_utc(),
+                });
+
+                if let Ok(msg_str) = serde_json::to_string(&jackpot_msg) {
+                    let _ = sender.send(Message::Text(msg_str.into())).await;
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to get jackpot balance: {}", e);
+            }
+        }
+    }
+
     // Subscribe to real-time updates
     let mut rx = {
         let broadcaster = state.broadcaster.read().await;
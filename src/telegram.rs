@@ -286,6 +286,7 @@ pub async fn notify_game_played(
 }
 
 /// Helper function to notify about a win
+#[allow(clippy::too_many_arguments)]
 pub async fn notify_win(
     pool: &Pool<Sqlite>,
     token: &str,
@@ -294,7 +295,8 @@ pub async fn notify_win(
     payout_amount: u64,
     multiplier: f64,
     rolled_number: i64,
-    target_number: u16,
+    target_number: i64,
+    outcome_label: &str,
     game_tx_id: &str,
     payout_tx_id: &str,
 ) -> Result<()> {
@@ -305,6 +307,7 @@ pub async fn notify_win(
         💰 Bet: {} sats\n\
         🎯 Multiplier: {}x\n\
         🎲 Rolled: {} (needed &lt; {})\n\
+        🏷️ Outcome: {}\n\
         💸 Payout: <b>{} sats</b>\n\
         📥 Game TX: <code>{}</code>\n\
         📤 Payout TX: <code>{}</code>",
@@ -313,6 +316,7 @@ pub async fn notify_win(
         multiplier,
         rolled_number,
         target_number,
+        outcome_label,
         payout_amount,
         truncate_txid(game_tx_id),
         truncate_txid(payout_tx_id)
@@ -322,6 +326,7 @@ pub async fn notify_win(
 }
 
 /// Helper function to notify about a loss
+#[allow(clippy::too_many_arguments)]
 pub async fn notify_loss(
     pool: &Pool<Sqlite>,
     token: &str,
@@ -329,9 +334,14 @@ pub async fn notify_loss(
     bet_amount: u64,
     multiplier: f64,
     rolled_number: i64,
-    target_number: u16,
+    target_number: i64,
+    outcome_label: &str,
+    refund_amount: Option<u64>,
     game_tx_id: &str,
 ) -> Result<()> {
+    let refund = refund_amount
+        .map(|refund| format!("↩️ Refund: <b>{refund} sats</b>\n"))
+        .unwrap_or_default();
     let message = format!(
         "😢 <b>Loss</b>\n\
         \n\
@@ -339,12 +349,16 @@ pub async fn notify_loss(
         💰 Bet: {} sats\n\
         🎯 Multiplier: {}x\n\
         🎲 Rolled: {} (needed &lt; {})\n\
+        🏷️ Outcome: {}\n\
+        {}\
         🔗 TX: <code>{}</code>",
         truncate_address(player),
         bet_amount,
         multiplier,
         rolled_number,
         target_number,
+        outcome_label,
+        refund,
         truncate_txid(game_tx_id)
     );
 
@@ -1,13 +1,35 @@
+use crate::bet_intents::claim_bet_intent;
+use crate::bet_intents::SubscriptionUpdate;
 use crate::client::SubscriptionEvent;
 use crate::db;
-use crate::games::get_game;
+use crate::duel;
+use crate::duel::DuelSettings;
+use crate::duel::DuelSide;
+use crate::games::registry::GameRegistry;
+use crate::games::satoshis_number::RollVersion;
+use crate::games::GameEvaluation;
+use crate::games::GameInput;
+use crate::games::GameOption;
 use crate::games::GameType;
+use crate::jackpot::JackpotSettings;
 use crate::key_derivation::Multiplier;
+use crate::lottery_service::LotteryService;
+use crate::nonce_service::hash_nonce;
+use crate::nonce_service::ActiveNonce;
 use crate::nonce_service::NonceService;
+use crate::payouts::Paid;
+use crate::payouts::PayoutExecutor;
+use crate::payouts::PayoutSource;
+use crate::server::duel_item;
+use crate::server::stored_detail;
+use crate::server::CrashPointItem;
 use crate::server::DonationItem;
 use crate::server::GameHistoryItem;
+use crate::server::JackpotItem;
+use crate::server::JackpotWinItem;
 use crate::websocket::SharedBroadcaster;
 use crate::ArkClient;
+use anyhow::Context;
 use anyhow::Result;
 use ark_core::ArkAddress;
 use bitcoin::Amount;
@@ -16,24 +38,62 @@ use sqlx::Pool;
 use sqlx::Sqlite;
 use std::sync::Arc;
 use time;
+use tokio::sync::mpsc;
+use tokio::sync::Mutex;
+use tokio::time::interval;
 use tokio::time::sleep;
 use tokio::time::Duration;
 
+/// How often expired duels are refunded and failed duel payouts retried
+const DUEL_TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a duel keeps waiting for an opponent after it expired, so that a stake the Ark server
+/// timestamped just before the end still gets matched
+const DUEL_EXPIRY_DELAY: time::Duration = time::Duration::minutes(1);
+
+/// How long winners are collected before they are paid in a single Ark transaction
+const PAYOUT_BATCH_WINDOW: Duration = Duration::from_secs(2);
+
+/// Most winners paid in a single Ark transaction, a full batch is paid right away
+const MAX_PAYOUT_BATCH: usize = 16;
+
+/// How often a winner the batch could not pay is paid on its own, once per batch window
+const MAX_PAYOUT_RETRIES: u8 = 3;
+
 #[derive(Debug, Clone)]
 struct GameResult {
-    multiplier: Multiplier,
+    game_type: GameType,
+    option: GameOption,
+    /// Payout multiplier the bet was offered, e.g. 200 for 2.00x
+    payout_multiplier: u64,
     outpoint: OutPoint,
     sender_address: ArkAddress,
     sender: String,
     input_amount: u64,
-    current_nonce: u64,
+    current_nonce: ActiveNonce,
+    client_seed: Option<String>,
     rolled_number: i64,
     is_win: bool,
+    /// Everything paid to the player, the payout of a win plus any refund
     payout_amount: Option<u64>,
+    /// Part of the bet returned without a win
+    refund_amount: Option<u64>,
+    /// Short name of the outcome, e.g. "win", missing for donations
+    label: Option<&'static str>,
+    /// Game specific details of the outcome as a JSON object
+    detail: Option<String>,
+    /// What the roll was compared against, e.g. the roll threshold or the reference card
+    target: i64,
+    /// Display form of the rolled number, e.g. the coin side of a coin flip
+    outcome: Option<String>,
+    /// Whether the bet also won the jackpot
+    jackpot_win: bool,
 }
 
 pub struct TransactionProcessor {
     ark_client: Arc<ArkClient>,
+    /// Sends every payout, one Ark transaction at a time
+    payouts: PayoutExecutor,
     my_addresses: Vec<ArkAddress>,
     nonce_service: NonceService,
     db_pool: Pool<Sqlite>,
@@ -41,11 +101,22 @@ pub struct TransactionProcessor {
     max_payout_sats: u64,
     dust_amount: Amount,
     telegram_bot_token: Option<String>,
+    games: Arc<GameRegistry>,
+    jackpot: JackpotSettings,
+    lottery: Option<LotteryService>,
+    /// Missing if duels are disabled
+    duels: Option<DuelSettings>,
+    /// Winners waiting for the next payout batch with the id of their stored game result
+    pending_payouts: Mutex<Vec<(i64, GameResult)>>,
+    /// Winners a batch could not pay with the id of their stored game result and how often they
+    /// were retried, each is paid on its own in the next batch window
+    failed_payouts: Mutex<Vec<(i64, GameResult, u8)>>,
 }
 
 impl TransactionProcessor {
     pub fn new(
         ark_client: Arc<ArkClient>,
+        payouts: PayoutExecutor,
         my_addresses: Vec<ArkAddress>,
         nonce_service: NonceService,
         db_pool: Pool<Sqlite>,
@@ -53,9 +124,14 @@ impl TransactionProcessor {
         max_payout_sats: u64,
         dust_amount: Amount,
         telegram_bot_token: Option<String>,
+        games: Arc<GameRegistry>,
+        jackpot: JackpotSettings,
+        lottery: Option<LotteryService>,
+        duels: Option<DuelSettings>,
     ) -> Self {
         Self {
             ark_client,
+            payouts,
             my_addresses,
             nonce_service,
             db_pool,
@@ -63,25 +139,41 @@ impl TransactionProcessor {
             max_payout_sats,
             dust_amount,
             telegram_bot_token,
+            games,
+            jackpot,
+            lottery,
+            duels,
+            pending_payouts: Mutex::new(Vec::new()),
+            failed_payouts: Mutex::new(Vec::new()),
         }
     }
 
-    pub async fn start_monitoring(&self) {
+    pub async fn start_monitoring(
+        &self,
+        subscription_updates: mpsc::UnboundedReceiver<SubscriptionUpdate>,
+    ) {
         tracing::info!("🔍 Starting transaction monitoring with subscriptions...");
 
-        // Get all game addresses to subscribe to
-        let game_addresses = self.ark_client.get_game_addresses();
+        // Get all game and bet intent addresses to subscribe to
+        let mut game_addresses = self.ark_client.get_game_addresses();
+        game_addresses.extend(self.ark_client.get_bet_intent_addresses());
 
         // Collect addresses for subscription
-        let scripts: Vec<_> = game_addresses
+        let mut scripts: Vec<_> = game_addresses
             .iter()
             .map(|(_, _, address)| *address)
             .collect();
+        if let Some(ref lottery) = self.lottery {
+            scripts.push(lottery.address());
+        }
+        if self.duels.is_some() {
+            scripts.push(self.ark_client.get_duel_address());
+        }
 
         tracing::info!("📡 Subscribing to {} game addresses", scripts.len());
 
         // Subscribe to all game address scripts
-        let subscription_id = match self.ark_client.subscribe_to_scripts(scripts).await {
+        let subscription_id = match self.ark_client.subscribe_to_scripts(scripts, None).await {
             Ok(id) => id,
             Err(e) => {
                 tracing::error!("🚨 CRITICAL: Failed to subscribe to game addresses: {}", e);
@@ -96,7 +188,11 @@ impl TransactionProcessor {
         );
 
         // Get subscription stream and process events
-        let stream = match self.ark_client.get_subscription(subscription_id).await {
+        let stream = match self
+            .ark_client
+            .get_subscription(subscription_id.clone())
+            .await
+        {
             Ok(stream) => stream,
             Err(e) => {
                 tracing::error!("🚨 CRITICAL: Failed to get subscription stream: {}", e);
@@ -106,7 +202,8 @@ impl TransactionProcessor {
         };
 
         // Process the stream - if this returns, the stream has ended
-        self.process_subscription_stream(stream).await;
+        self.process_subscription_stream(stream, &subscription_id, subscription_updates)
+            .await;
 
         // If we reach this point, the subscription stream has ended unexpectedly
         tracing::error!("🚨 CRITICAL: Subscription stream ended unexpectedly");
@@ -119,22 +216,45 @@ impl TransactionProcessor {
         mut stream: std::pin::Pin<
             Box<dyn futures::Stream<Item = Result<SubscriptionEvent>> + Send + '_>,
         >,
+        subscription_id: &str,
+        mut subscription_updates: mpsc::UnboundedReceiver<SubscriptionUpdate>,
     ) {
         use futures::StreamExt;
 
         tracing::info!("🔄 Processing subscription stream...");
 
-        while let Some(event_result) = stream.next().await {
-            match event_result {
-                Ok(event) => {
-                    if let Err(e) = self.process_single_event(event).await {
-                        tracing::error!("Error processing subscription event: {}", e);
+        let mut duel_timer = interval(DUEL_TICK_INTERVAL);
+        let mut payout_timer = interval(PAYOUT_BATCH_WINDOW);
+
+        loop {
+            tokio::select! {
+                event_result = stream.next() => {
+                    let Some(event_result) = event_result else {
+                        break;
+                    };
+
+                    match event_result {
+                        Ok(event) => {
+                            if let Err(e) = self.process_single_event(event).await {
+                                tracing::error!("Error processing subscription event: {}", e);
+                            }
+                        }
+                        Err(e) => {
+                            tracing::error!("Error in subscription stream: {}", e);
+                            // Add a delay before continuing to avoid tight error loops
+                            sleep(Duration::from_secs(5)).await;
+                        }
                     }
                 }
-                Err(e) => {
-                    tracing::error!("Error in subscription stream: {}", e);
-                    // Add a delay before continuing to avoid tight error loops
-                    sleep(Duration::from_secs(5)).await;
+                Some(update) = subscription_updates.recv() => {
+                    self.update_subscription(subscription_id, update).await;
+                }
+                // Settling duels here keeps them in line with the matching of new stakes
+                _ = duel_timer.tick(), if self.duels.is_some() => {
+                    self.settle_duels().await;
+                }
+                _ = payout_timer.tick() => {
+                    self.pay_pending_winners().await;
                 }
             }
         }
@@ -142,55 +262,104 @@ impl TransactionProcessor {
         tracing::info!("📡 Subscription stream processing completed");
     }
 
+    /// Add a bet intent address to the open subscription
+    async fn update_subscription(&self, subscription_id: &str, update: SubscriptionUpdate) {
+        let SubscriptionUpdate::Subscribe(address) = update;
+        let result = self
+            .ark_client
+            .subscribe_to_scripts(vec![address], Some(subscription_id.to_string()))
+            .await;
+
+        if let Err(e) = result {
+            tracing::error!(?update, "Failed to update subscription: {:#}", e);
+        }
+    }
+
     async fn process_single_event(&self, event: SubscriptionEvent) -> Result<()> {
         let tx_id = event.txid.to_string();
-        tracing::info!(tx_id, ?event, "📨 Received subscription event for tx",);
+        let vout = event.vout;
+        tracing::info!(tx_id, vout, ?event, "📨 Received subscription event for tx",);
 
         // Check if this is our own transaction
         let is_own_tx = db::is_own_transaction(&self.db_pool, &tx_id).await;
-        let is_tx_processed = db::is_transaction_processed(&self.db_pool, &tx_id).await;
+        // Every output is its own bet, so a transaction may fund several games
+        let is_tx_processed = db::is_outpoint_processed(&self.db_pool, &tx_id, vout as i64).await;
 
         match (is_tx_processed, is_own_tx) {
             (Ok(false), Ok(false)) => {
                 tracing::trace!(target: "tx_processor", tx_id, "Processing new subscription event");
 
                 // Find which game address this transaction is for
-                if let Some((game_type, multiplier)) =
+                if let Some(lottery) = self.lottery_for_script(&event.script_pubkey, event.amount) {
+                    self.process_lottery_payment(lottery, &event).await?;
+                } else if let Some(duels) =
+                    self.duels_for_script(&event.script_pubkey, event.amount)
+                {
+                    self.process_duel_stake(duels, &event).await?;
+                } else if let Some((game_type, option, address)) =
                     self.find_game_for_script(&event.script_pubkey, event.amount)
                 {
-                    if let Some(game_result) =
-                        self.evaluate_game(game_type, &multiplier, &event).await?
+                    // Claim the intent before it can expire, a bet that comes in after it
+                    // expired is refunded
+                    let mut late_bet = false;
+                    if game_type == GameType::OverUnder {
+                        let created_at =
+                            time::OffsetDateTime::from_unix_timestamp(event.created_at)?;
+                        match claim_bet_intent(&self.db_pool, &address.encode(), created_at).await {
+                            Ok(late) => late_bet = late,
+                            Err(e) => {
+                                tracing::error!(tx_id, vout, "Failed to claim bet intent: {:#}", e);
+                            }
+                        }
+                    }
+
+                    if let Some(game_result) = self
+                        .evaluate_game(game_type, &option, &event, late_bet)
+                        .await?
                     {
-                        // Process individual events immediately (no batching for now)
+                        // The jackpot is paid on top of whatever the bet itself paid
+                        let jackpot_winner = game_result
+                            .jackpot_win
+                            .then_some((game_result.outpoint, game_result.sender_address));
+
+                        if game_result.game_type == GameType::Crash {
+                            self.broadcast_crash_point(&game_result).await;
+                        }
+
+                        // Winners are paid in batches, everything else is processed immediately
                         match game_result {
                             result
                                 if result.payout_amount.is_none()
                                     && result.input_amount
-                                        > self.get_donation_threshold(&result.multiplier) =>
+                                        > self.get_donation_threshold(result.payout_multiplier) =>
                             {
                                 self.process_donation(result).await?;
                             }
-                            result if result.is_win => {
-                                // For individual winners, use individual payout method
-                                self.process_individual_winner(result).await?;
+                            result if result.payout_amount.is_some() => {
+                                // Winners and partial refunds are paid with the next batch
+                                self.queue_winner(result).await?;
                             }
                             result => {
                                 self.process_loser(result).await?;
                             }
                         }
+
+                        if let Some((outpoint, winner)) = jackpot_winner {
+                            self.process_jackpot_win(outpoint, winner).await?;
+                        }
                     }
                 } else {
                     tracing::warn!("⚠️ Received event for unknown script pubkey");
                 }
             }
             (Ok(true), _) => {
-                tracing::trace!(target: "tx_processor", tx_id, "Transaction already processed, skipping");
+                tracing::trace!(target: "tx_processor", tx_id, vout, "Output already processed, skipping");
             }
             (_, Ok(true)) => {
                 tracing::trace!(target: "tx_processor", tx_id, "Own transaction, skipping");
             }
             (Err(e), Ok(_)) => {
-                tracing::error!(tx_id, "Error checking if transaction is processed: {}", e);
+                tracing::error!(tx_id, vout, "Error checking if output is processed: {}", e);
             }
             (_, Err(e)) => {
                 tracing::error!(tx_id, "Error checking if transaction is own: {}", e);
@@ -200,29 +369,344 @@ impl TransactionProcessor {
         Ok(())
     }
 
-    /// Find which game corresponds to a script pubkey
+    /// Find which game, and which of its addresses, corresponds to a script pubkey
     fn find_game_for_script(
         &self,
         script_pubkey: &bitcoin::ScriptBuf,
         amount: Amount,
-    ) -> Option<(GameType, Multiplier)> {
-        let game_addresses = self.ark_client.get_game_addresses();
+    ) -> Option<(GameType, GameOption, ArkAddress)> {
+        let mut game_addresses = self.ark_client.get_game_addresses();
+        game_addresses.extend(self.ark_client.get_bet_intent_addresses());
 
-        for (game_type, multiplier, address) in game_addresses {
+        for (game_type, option, address) in game_addresses {
             if amount <= self.dust_amount {
                 if address.to_sub_dust_script_pubkey() == *script_pubkey {
-                    return Some((game_type, multiplier));
+                    return Some((game_type, option, address));
                 }
             }
 
             if address.to_p2tr_script_pubkey() == *script_pubkey {
-                return Some((game_type, multiplier));
+                return Some((game_type, option, address));
             }
         }
 
         None
     }
 
+    /// The lottery, if the script pubkey is its address
+    fn lottery_for_script(
+        &self,
+        script_pubkey: &bitcoin::ScriptBuf,
+        amount: Amount,
+    ) -> Option<&LotteryService> {
+        let lottery = self.lottery.as_ref()?;
+
+        self.is_address_script(lottery.address(), script_pubkey, amount)
+            .then_some(lottery)
+    }
+
+    /// The duel settings, if duels are enabled and the script pubkey is the duel address
+    fn duels_for_script(
+        &self,
+        script_pubkey: &bitcoin::ScriptBuf,
+        amount: Amount,
+    ) -> Option<DuelSettings> {
+        let duels = self.duels?;
+
+        self.is_address_script(self.ark_client.get_duel_address(), script_pubkey, amount)
+            .then_some(duels)
+    }
+
+    /// Whether a payment of `amount` with the script pubkey went to `address`
+    fn is_address_script(
+        &self,
+        address: ArkAddress,
+        script_pubkey: &bitcoin::ScriptBuf,
+        amount: Amount,
+    ) -> bool {
+        (amount <= self.dust_amount && address.to_sub_dust_script_pubkey() == *script_pubkey)
+            || address.to_p2tr_script_pubkey() == *script_pubkey
+    }
+
+    /// Buy lottery tickets for the sender of a payment to the lottery address
+    async fn process_lottery_payment(
+        &self,
+        lottery: &LotteryService,
+        event: &SubscriptionEvent,
+    ) -> Result<()> {
+        let out_point = OutPoint {
+            txid: event.txid,
+            vout: event.vout,
+        };
+        if db::is_lottery_outpoint_processed(
+            &self.db_pool,
+            &event.txid.to_string(),
+            event.vout as i64,
+        )
+        .await?
+        {
+            tracing::trace!(target: "tx_processor", %out_point, "Lottery payment already processed, skipping");
+            return Ok(());
+        }
+
+        let Some(sender_address) = self.find_sender(out_point).await? else {
+            tracing::debug!(%out_point, "Ignoring lottery payment from own address");
+            return Ok(());
+        };
+
+        // Tickets go to the round that was open when the VTXO was created
+        let created_at = time::OffsetDateTime::from_unix_timestamp(event.created_at)?;
+        lottery
+            .buy_tickets(out_point, sender_address, event.amount.to_sat(), created_at)
+            .await
+    }
+
+    /// The player who sent the VTXO at `out_point`, `None` if we sent it ourselves
+    async fn find_sender(&self, out_point: OutPoint) -> Result<Option<ArkAddress>> {
+        let own_address = self
+            .my_addresses
+            .first()
+            .cloned()
+            .expect("to have own address");
+
+        Ok(self
+            .ark_client
+            .get_parent_vtxo(out_point)
+            .await?
+            .into_iter()
+            .find(|address| address.encode() != own_address.encode()))
+    }
+
+    /// Match a stake sent to the duel address against the oldest open challenge of the same
+    /// amount, or open a new challenge with it
+    async fn process_duel_stake(
+        &self,
+        settings: DuelSettings,
+        event: &SubscriptionEvent,
+    ) -> Result<()> {
+        let out_point = OutPoint {
+            txid: event.txid,
+            vout: event.vout,
+        };
+        let tx_id = event.txid.to_string();
+        if db::is_duel_outpoint_processed(&self.db_pool, &tx_id, event.vout as i64).await? {
+            tracing::trace!(target: "tx_processor", %out_point, "Duel stake already processed, skipping");
+            return Ok(());
+        }
+
+        let Some(sender_address) = self.find_sender(out_point).await? else {
+            tracing::debug!(%out_point, "Ignoring duel stake from own address");
+            return Ok(());
+        };
+        let player = sender_address.encode();
+        let stake = event.amount.to_sat();
+
+        // Like a bet, the stake is matched and rolled as of when its VTXO was created
+        let created_at = time::OffsetDateTime::from_unix_timestamp(event.created_at)?;
+        let Some(nonce) = self.nonce_service.get_nonce_at(created_at).await? else {
+            tracing::warn!(
+                %out_point,
+                %created_at,
+                player,
+                "⚠️ No unrevealed nonce was active when the VTXO was created, refunding stake"
+            );
+            let duel_id = db::insert_expired_duel(
+                &self.db_pool,
+                stake as i64,
+                settings.rake_bps as i64,
+                &player,
+                &tx_id,
+                event.vout as i64,
+                created_at,
+            )
+            .await?;
+            let duel = db::get_duel(&self.db_pool, duel_id)
+                .await?
+                .context("Duel missing after insert")?;
+            return self.settle_duel(duel).await;
+        };
+
+        let challenge =
+            db::find_open_duel(&self.db_pool, stake as i64, &player, created_at).await?;
+        if let Some(duel) = challenge {
+            let roll = duel::roll(
+                &nonce.nonce,
+                (&duel.challenger_tx_id, duel.challenger_vout as u32),
+                (&tx_id, event.vout),
+            );
+            let winner = match roll.winner {
+                DuelSide::Challenger => duel.challenger_address.clone(),
+                DuelSide::Opponent => player.clone(),
+            };
+            // The rake the challenge was opened with, later config changes only apply to new ones
+            let rake = DuelSettings {
+                rake_bps: u16::try_from(duel.rake_bps).unwrap_or(10_000),
+                ..settings
+            };
+
+            let matched = db::match_duel(
+                &self.db_pool,
+                duel.id,
+                &player,
+                &tx_id,
+                event.vout as i64,
+                &nonce.nonce,
+                &hash_nonce(&nonce.nonce),
+                &winner,
+                rake.rake(stake) as i64,
+                rake.payout(stake) as i64,
+            )
+            .await?;
+
+            if matched {
+                tracing::info!(
+                    duel_id = duel.id,
+                    %out_point,
+                    challenger = duel.challenger_address,
+                    opponent = player,
+                    stake,
+                    winner,
+                    "⚔️ Duel matched"
+                );
+
+                let duel = db::get_duel(&self.db_pool, duel.id)
+                    .await?
+                    .context("Duel missing after it was matched")?;
+                let item = duel_item(&self.nonce_service, &duel).await;
+                let broadcaster = self.broadcaster.read().await;
+                if let Err(e) = broadcaster.broadcast_duel_matched(item) {
+                    tracing::error!("Failed to broadcast duel match: {}", e);
+                }
+                drop(broadcaster);
+
+                self.settle_duel(duel).await?;
+                return Ok(());
+            }
+        }
+
+        let expires_at = created_at + time::Duration::minutes(settings.timeout_minutes as i64);
+        let duel_id = db::insert_duel(
+            &self.db_pool,
+            stake as i64,
+            settings.rake_bps as i64,
+            &player,
+            &tx_id,
+            event.vout as i64,
+            expires_at,
+        )
+        .await?;
+
+        tracing::info!(
+            duel_id,
+            %out_point,
+            challenger = player,
+            stake,
+            %expires_at,
+            "⚔️ Duel challenge opened"
+        );
+
+        if let Some(duel) = db::get_duel(&self.db_pool, duel_id).await? {
+            let item = duel_item(&self.nonce_service, &duel).await;
+            let broadcaster = self.broadcaster.read().await;
+            if let Err(e) = broadcaster.broadcast_duel_opened(item) {
+                tracing::error!("Failed to broadcast duel challenge: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pay the winner of a matched duel or refund the challenger of an expired one. A payout that
+    /// fails leaves the duel as it is, to be retried on the next duel tick.
+    async fn settle_duel(&self, duel: db::Duel) -> Result<()> {
+        let (address, amount, transaction_type) = match (duel.status.as_str(), &duel.winner_address)
+        {
+            ("matched", Some(winner)) => (
+                winner,
+                duel.payout_amount.unwrap_or_default(),
+                "duel_payout",
+            ),
+            ("expired", _) => (&duel.challenger_address, duel.stake_amount, "refund"),
+            _ => {
+                tracing::error!(
+                    duel_id = duel.id,
+                    status = duel.status,
+                    "Duel cannot be settled"
+                );
+                return Ok(());
+            }
+        };
+        let address = ArkAddress::decode(address)?;
+        let amount = Amount::from_sat(amount.max(0) as u64);
+
+        let Some(Paid {
+            txid: payout_tx_id, ..
+        }) = self
+            .send_payout(
+                PayoutSource::Duel(duel.id),
+                address,
+                amount,
+                transaction_type,
+            )
+            .await
+        else {
+            tracing::error!(
+                duel_id = duel.id,
+                amount = amount.to_sat(),
+                transaction_type,
+                "🚨 Failed to settle duel, retrying on the next duel tick"
+            );
+            return Ok(());
+        };
+
+        if !db::mark_duel_settled(&self.db_pool, duel.id, &payout_tx_id).await? {
+            return Ok(());
+        }
+
+        tracing::info!(
+            duel_id = duel.id,
+            amount = amount.to_sat(),
+            transaction_type,
+            payout_tx_id,
+            "💸 Duel settled"
+        );
+
+        if let Some(duel) = db::get_duel(&self.db_pool, duel.id).await? {
+            let item = duel_item(&self.nonce_service, &duel).await;
+            let broadcaster = self.broadcaster.read().await;
+            if let Err(e) = broadcaster.broadcast_duel_settled(item) {
+                tracing::error!("Failed to broadcast duel settlement: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expire challenges nobody matched in time, then refund them and retry unpaid winners
+    async fn settle_duels(&self) {
+        let now = time::OffsetDateTime::now_utc();
+        match db::expire_duels(&self.db_pool, now - DUEL_EXPIRY_DELAY).await {
+            Ok(0) => {}
+            Ok(expired) => tracing::info!(expired, "⌛ Duel challenges expired"),
+            Err(e) => tracing::error!("Failed to expire duels: {}", e),
+        }
+
+        let duels = match db::get_unsettled_duels(&self.db_pool).await {
+            Ok(duels) => duels,
+            Err(e) => {
+                tracing::error!("Failed to load unsettled duels: {}", e);
+                return;
+            }
+        };
+
+        for duel in duels {
+            let duel_id = duel.id;
+            if let Err(e) = self.settle_duel(duel).await {
+                tracing::error!(duel_id, "Failed to settle duel: {:#}", e);
+            }
+        }
+    }
+
     async fn broadcast_game_result(&self, game: GameHistoryItem) {
         let broadcaster = self.broadcaster.read().await;
         if let Err(e) = broadcaster.broadcast_game_result(game) {
@@ -230,6 +714,20 @@ impl TransactionProcessor {
         }
     }
 
+    async fn broadcast_crash_point(&self, result: &GameResult) {
+        let broadcaster = self.broadcaster.read().await;
+        if let Err(e) = broadcaster.broadcast_crash_point(CrashPointItem {
+            input_tx_id: result.outpoint.txid.to_string(),
+            input_vout: result.outpoint.vout,
+            crash_point: result.rolled_number,
+            cashout_target: result.target,
+            is_win: result.is_win,
+            timestamp: time::OffsetDateTime::now_utc(),
+        }) {
+            tracing::error!("Failed to broadcast crash point: {}", e);
+        }
+    }
+
     async fn broadcast_donation(&self, donation: DonationItem) {
         let broadcaster = self.broadcaster.read().await;
         if let Err(e) = broadcaster.broadcast_donation(donation) {
@@ -237,16 +735,39 @@ impl TransactionProcessor {
         }
     }
 
-    fn get_donation_threshold(&self, multiplier: &Multiplier) -> u64 {
-        // Calculate max input amount: max_payout * 100 / multiplier
-        (self.max_payout_sats * 100) / multiplier.multiplier()
+    async fn broadcast_jackpot(&self, win: Option<JackpotWinItem>) {
+        let balance = match db::get_jackpot_balance(&self.db_pool).await {
+            Ok(balance) => balance,
+            Err(e) => {
+                tracing::error!("Failed to get jackpot balance: {}", e);
+                return;
+            }
+        };
+
+        let broadcaster = self.broadcaster.read().await;
+        if let Err(e) = broadcaster.broadcast_jackpot(JackpotItem {
+            balance: Amount::from_sat(balance.max(0) as u64),
+            win,
+            timestamp: time::OffsetDateTime::now_utc(),
+        }) {
+            tracing::error!("Failed to broadcast jackpot: {}", e);
+        }
+    }
+
+    fn get_donation_threshold(&self, payout_multiplier: u64) -> u64 {
+        // Calculate max input amount: max_payout * 100 / multiplier. Options that can never pay
+        // out take no bets.
+        (self.max_payout_sats * 100)
+            .checked_div(payout_multiplier)
+            .unwrap_or(0)
     }
 
     async fn evaluate_game(
         &self,
         game_type: GameType,
-        multiplier: &Multiplier,
+        option: &GameOption,
         event: &SubscriptionEvent,
+        late_bet: bool,
     ) -> Result<Option<GameResult>> {
         let out_point = OutPoint {
             txid: event.txid,
@@ -271,52 +792,122 @@ impl TransactionProcessor {
             }
 
             let sender = sender_address.encode();
-            let current_nonce = self.nonce_service.get_current_nonce().await;
+            // The bet is bound to the nonce and client seed that were in effect when the VTXO was
+            // created, not to whatever is in effect by the time we get to process it
+            let created_at = time::OffsetDateTime::from_unix_timestamp(event.created_at)?;
+            let client_seed = db::get_client_seed_at(&self.db_pool, &sender, created_at).await?;
             let input_amount = event.amount.to_sat();
 
             tracing::info!(outpoint = ?event.txid, amount = ?event.amount, sender, "Found sender");
 
+            let refund_reason = if !self.games.is_enabled(game_type) {
+                Some("game is disabled")
+            } else if late_bet {
+                Some("bet intent expired")
+            } else {
+                None
+            };
+            if let Some(reason) = refund_reason {
+                tracing::warn!(
+                    outpoint = ?event.txid,
+                    %game_type,
+                    sender,
+                    reason,
+                    "⚠️ Bet cannot be played, refunding bet"
+                );
+                self.process_refund(
+                    game_type,
+                    option,
+                    out_point,
+                    sender_address,
+                    input_amount,
+                    client_seed,
+                )
+                .await?;
+                return Ok(None);
+            }
+
+            let Some(current_nonce) = self.nonce_service.get_nonce_at(created_at).await? else {
+                tracing::warn!(
+                    outpoint = ?event.txid,
+                    %created_at,
+                    sender,
+                    "⚠️ No unrevealed nonce was active when the VTXO was created, refunding bet"
+                );
+                self.process_refund(
+                    game_type,
+                    option,
+                    out_point,
+                    sender_address,
+                    input_amount,
+                    client_seed,
+                )
+                .await?;
+                return Ok(None);
+            };
+
+            let game = self.games.get(game_type);
+            // Games like high/low set their odds from the nonce the bet was placed against
+            let nonce_hash = hash_nonce(&current_nonce.nonce);
+            let payout_multiplier = game.payout_multiplier(&nonce_hash, option);
+            let target = game.target(&nonce_hash, option);
+
             // Check donation threshold
-            let donation_threshold = self.get_donation_threshold(multiplier);
+            let donation_threshold = self.get_donation_threshold(payout_multiplier);
             if input_amount > donation_threshold {
                 return Ok(Some(GameResult {
-                    multiplier: *multiplier,
+                    game_type,
+                    option: *option,
+                    payout_multiplier,
                     outpoint: out_point,
                     sender_address,
                     sender,
                     input_amount,
                     current_nonce,
+                    client_seed,
                     rolled_number: -1, // Special value for donations
                     is_win: false,
                     payout_amount: None,
+                    refund_amount: None,
+                    label: None,
+                    detail: None,
+                    target,
+                    outcome: None,
+                    jackpot_win: false,
                 }));
             }
 
             // Game logic - using the abstracted game system
-            let game = get_game(game_type);
-            let evaluation = game.evaluate(current_nonce, &out_point.txid.to_string(), multiplier);
-
-            let payout_amount = if evaluation.is_win {
-                Some(
-                    (input_amount as f64
-                        * evaluation
-                            .payout_multiplier
-                            .expect("to have a payout multiplier")) as u64,
-                )
-            } else {
-                None
+            let txid = out_point.txid.to_string();
+            let input = GameInput {
+                nonce: &current_nonce.nonce,
+                txid: &txid,
+                vout: Some(out_point.vout),
+                client_seed: client_seed.as_deref(),
+                roll_version: RollVersion::LATEST,
             };
+            let evaluation = game.evaluate(&input, option, input_amount);
+            let payout_amount = Some(evaluation.returned_sats()).filter(|amount| *amount > 0);
 
             return Ok(Some(GameResult {
-                multiplier: *multiplier,
+                game_type,
+                option: *option,
+                payout_multiplier,
                 outpoint: out_point,
                 sender_address,
                 sender,
                 input_amount,
                 current_nonce,
+                client_seed,
                 rolled_number: evaluation.rolled_value,
                 is_win: evaluation.is_win,
                 payout_amount,
+                refund_amount: evaluation.refund_sats,
+                label: Some(evaluation.label),
+                detail: Some(evaluation.detail),
+                target,
+                outcome: game.outcome(evaluation.rolled_value),
+                jackpot_win: self.jackpot.is_win(&input),
             }));
         }
 
@@ -338,6 +929,7 @@ impl TransactionProcessor {
                 &donation.sender,
                 donation.input_amount,
                 &donation.outpoint.txid.to_string(),
+                donation.outpoint.vout as i64,
             )
             .await
             {
@@ -348,16 +940,29 @@ impl TransactionProcessor {
         // Store as donation in database
         if let Err(e) = db::insert_game_result(
             &self.db_pool,
-            &donation.current_nonce.to_string(),
+            &donation.current_nonce.nonce,
             donation.rolled_number,
             &donation.outpoint.txid.to_string(),
+            donation.outpoint.vout as i64,
             None,
             donation.input_amount as i64,
             None,
             &donation.sender,
             false, // Not a win
             false, // Not processed as game
-            donation.multiplier.multiplier() as i64,
+            donation.payout_multiplier as i64,
+            donation.client_seed.as_deref(),
+            donation.current_nonce.chain_position,
+            donation.game_type as i64,
+            donation.option.index() as i64,
+            None,
+            None,
+            None,
+            RollVersion::LATEST as i64,
+            self.games
+                .settings()
+                .house_edge_bps(donation.game_type)
+                .map(i64::from),
         )
         .await
         {
@@ -365,7 +970,7 @@ impl TransactionProcessor {
         } else {
             // Broadcast donation notification
             let donation_item = DonationItem {
-                id: format!("donation-{}", donation.outpoint.txid),
+                id: format!("donation-{}", donation.outpoint),
                 amount: Amount::from_sat(donation.input_amount),
                 sender: donation.sender,
                 input_tx_id: donation.outpoint.txid.to_string(),
@@ -378,147 +983,425 @@ impl TransactionProcessor {
         Ok(())
     }
 
-    async fn process_individual_winner(&self, winner: GameResult) -> Result<()> {
-        let payout_sats = winner.payout_amount.unwrap_or(0);
-        let payout_amount = Amount::from_sat(payout_sats);
+    /// Send a bet back to the player when it cannot be bound to a nonce or its game is disabled
+    async fn process_refund(
+        &self,
+        game_type: GameType,
+        option: &GameOption,
+        out_point: OutPoint,
+        sender_address: ArkAddress,
+        input_amount: u64,
+        client_seed: Option<String>,
+    ) -> Result<()> {
+        // Stored unpaid before anything is sent, so the transaction counts as processed and the
+        // refund is paid at most once
+        let game_id = db::insert_game_result(
+            &self.db_pool,
+            "", // No nonce was active
+            db::REFUNDED_ROLL,
+            &out_point.txid.to_string(),
+            out_point.vout as i64,
+            None,
+            input_amount as i64,
+            Some(input_amount as i64),
+            &sender_address.encode(),
+            false, // Not a win
+            false,
+            // No nonce means no odds for games like high/low
+            self.games.get(game_type).payout_multiplier("", option) as i64,
+            client_seed.as_deref(),
+            None,
+            game_type as i64,
+            option.index() as i64,
+            None,
+            None,
+            None,
+            RollVersion::LATEST as i64,
+            self.games
+                .settings()
+                .house_edge_bps(game_type)
+                .map(i64::from),
+        )
+        .await
+        .context("Failed to store refund")?;
+
+        match self
+            .send_payout(
+                PayoutSource::GameResult(game_id),
+                sender_address,
+                Amount::from_sat(input_amount),
+                "refund",
+            )
+            .await
+        {
+            Some(refund) => {
+                tracing::info!(txid = refund.txid, amount = input_amount, "↩️ Bet refunded");
 
+                if let Err(e) = db::mark_payment_successful(
+                    &self.db_pool,
+                    game_id,
+                    &refund.txid,
+                    refund.vout as i64,
+                )
+                .await
+                {
+                    tracing::error!("Failed to mark refund paid: {}", e);
+                }
+            }
+            None => {
+                tracing::error!(
+                    amount = input_amount,
+                    "🚨 Failed to send refund, it will be retried with the unpaid winners"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store a winner unpaid and queue it for the next payout batch. The payout is planned against
+    /// the stored game, so it is never sent twice.
+    async fn queue_winner(&self, winner: GameResult) -> Result<()> {
         tracing::info!(
-            payout = payout_sats,
+            payout = winner.payout_amount.unwrap_or(0),
             sender = winner.sender,
-            "💸 Processing individual payout"
+            "💸 Queueing payout"
         );
 
+        let game_id = db::insert_game_result(
+            &self.db_pool,
+            &winner.current_nonce.nonce,
+            winner.rolled_number,
+            &winner.outpoint.txid.to_string(),
+            winner.outpoint.vout as i64,
+            None,
+            winner.input_amount as i64,
+            winner.payout_amount.map(|p| p as i64),
+            &winner.sender,
+            winner.is_win, // A partial refund is paid without a win
+            false,
+            winner.payout_multiplier as i64,
+            winner.client_seed.as_deref(),
+            winner.current_nonce.chain_position,
+            winner.game_type as i64,
+            winner.option.index() as i64,
+            winner.refund_amount.map(|r| r as i64),
+            winner.label,
+            winner.detail.as_deref(),
+            RollVersion::LATEST as i64,
+            self.games
+                .settings()
+                .house_edge_bps(winner.game_type)
+                .map(i64::from),
+        )
+        .await
+        .context("Failed to store winner game result")?;
+
+        let batch_full = {
+            let mut pending_payouts = self.pending_payouts.lock().await;
+            pending_payouts.push((game_id, winner));
+            pending_payouts.len() >= MAX_PAYOUT_BATCH
+        };
+
+        if batch_full {
+            self.pay_pending_winners().await;
+        }
+
+        Ok(())
+    }
+
+    /// Pay every queued winner in a single Ark transaction. Winners the batch could not pay are
+    /// retried on their own in the next batch windows, so that a single recipient cannot hold up
+    /// the rest and no retry holds up the bets in the meantime.
+    async fn pay_pending_winners(&self) {
+        self.retry_failed_payouts().await;
+
+        let pending_payouts = std::mem::take(&mut *self.pending_payouts.lock().await);
+        if pending_payouts.is_empty() {
+            return;
+        }
+
+        let requests = pending_payouts
+            .iter()
+            .map(|(game_id, winner)| {
+                (
+                    PayoutSource::GameResult(*game_id),
+                    winner.sender_address,
+                    Amount::from_sat(winner.payout_amount.unwrap_or(0)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        tracing::info!(count = requests.len(), "💸 Paying winners");
+
+        let results = self.payouts.pay_batch(&requests, "batch_payout").await;
+
+        for ((game_id, winner), result) in pending_payouts.into_iter().zip(results) {
+            match result {
+                Ok(paid) => self.finish_payout(game_id, winner, Some(paid)).await,
+                Err(e) => {
+                    tracing::warn!(
+                        game_id,
+                        "⚠️ Winner was not paid with the batch, retrying it on its own {e:#}"
+                    );
+                    self.failed_payouts.lock().await.push((game_id, winner, 0));
+                }
+            }
+        }
+    }
+
+    /// Pay every winner a batch could not pay on its own, one attempt each. Winners that still
+    /// fail are retried in the next batch window until [`MAX_PAYOUT_RETRIES`] is reached.
+    async fn retry_failed_payouts(&self) {
+        let failed_payouts = std::mem::take(&mut *self.failed_payouts.lock().await);
+
+        for (game_id, winner, retries) in failed_payouts {
+            let amount = Amount::from_sat(winner.payout_amount.unwrap_or(0));
+            let result = self
+                .payouts
+                .pay(
+                    PayoutSource::GameResult(game_id),
+                    winner.sender_address,
+                    amount,
+                    "individual_payout",
+                )
+                .await;
+
+            let retries = retries + 1;
+            match result {
+                Ok(paid) => {
+                    tracing::info!(game_id, txid = paid.txid, "💸 Winner paid on its own");
+                    self.finish_payout(game_id, winner, Some(paid)).await;
+                }
+                Err(e) if retries < MAX_PAYOUT_RETRIES => {
+                    tracing::warn!(game_id, retries, "⚠️ Failed to pay winner on its own {e:#}");
+                    self.failed_payouts
+                        .lock()
+                        .await
+                        .push((game_id, winner, retries));
+                }
+                Err(e) => {
+                    tracing::error!(game_id, retries, "🚨 Failed to pay winner on its own {e:#}");
+                    self.finish_payout(game_id, winner, None).await;
+                }
+            }
+        }
+    }
+
+    /// Record the payout of a winner and announce it, as a failed winner if it was not paid
+    async fn finish_payout(&self, game_id: i64, winner: GameResult, paid: Option<Paid>) {
+        match paid {
+            Some(ref paid) => {
+                if let Err(e) = db::mark_payment_successful(
+                    &self.db_pool,
+                    game_id,
+                    &paid.txid,
+                    paid.vout as i64,
+                )
+                .await
+                {
+                    tracing::error!("Failed to mark payout successful: {}", e);
+                }
+            }
+            None => {
+                tracing::error!(
+                    game_id,
+                    payout = winner.payout_amount.unwrap_or(0),
+                    "🚨 Failed to pay winner, it will be retried with the unpaid winners"
+                );
+            }
+        }
+
+        if let Err(e) = self.process_winner_result(winner, paid).await {
+            tracing::error!(game_id, "Failed to announce winner: {:#}", e);
+        }
+    }
+
+    /// Send a payout for `source` through the payout outbox, retrying with exponential backoff.
+    /// The transaction is stored as our own with `transaction_type`. Returns `None` if every
+    /// attempt failed.
+    async fn send_payout(
+        &self,
+        source: PayoutSource,
+        address: ArkAddress,
+        amount: Amount,
+        transaction_type: &str,
+    ) -> Option<Paid> {
         const MAX_RETRIES: u8 = 3;
         let mut retry_count = 0;
 
         loop {
             match self
-                .ark_client
-                .send_vtxo(winner.sender_address, payout_amount)
+                .payouts
+                .pay(source, address, amount, transaction_type)
                 .await
             {
-                Ok(txid) => {
+                Ok(paid) => {
                     tracing::info!(
-                        txid = txid.to_string(),
-                        payout = payout_sats,
-                        "💸 Individual payout sent successfully"
+                        txid = paid.txid,
+                        payout = amount.to_sat(),
+                        transaction_type,
+                        "💸 Payout sent successfully"
                     );
 
-                    // Store as our own transaction
-                    if let Err(e) = db::insert_own_transaction(
-                        &self.db_pool,
-                        &txid.to_string(),
-                        "individual_payout",
-                    )
-                    .await
-                    {
-                        tracing::error!("Failed to store individual payout transaction: {}", e);
-                    }
-
-                    // Process winner result
-                    self.process_winner_result(winner, Some(txid.to_string()))
-                        .await?;
-
-                    if let Err(err) = self.ark_client.sync_spendable_vtxos().await {
-                        tracing::error!("Failed syncing after sending {err:#}");
-                    }
-
-                    break;
+                    return Some(paid);
                 }
                 Err(e) => {
                     retry_count += 1;
                     tracing::error!(
                         retry = retry_count,
                         max_retries = MAX_RETRIES,
-                        payout = payout_sats,
-                        "🚨 Failed to send individual payout {e:#}"
+                        payout = amount.to_sat(),
+                        transaction_type,
+                        "🚨 Failed to send payout {e:#}"
                     );
 
                     if retry_count >= MAX_RETRIES {
-                        tracing::error!(
-                            "🚨 Max retries exceeded for individual payout, processing as failed winner"
-                        );
-                        self.process_winner_result(winner, None).await?;
-                        break;
-                    } else {
-                        // Wait before retrying (exponential backoff)
-                        let delay_ms = 1000 * (2_u64.pow(retry_count as u32 - 1));
-                        tracing::info!("Retrying individual payout in {}ms...", delay_ms);
-                        sleep(Duration::from_millis(delay_ms)).await;
+                        tracing::error!("🚨 Max retries exceeded for {transaction_type}");
+                        return None;
                     }
+
+                    // Wait before retrying (exponential backoff)
+                    let delay_ms = 1000 * (2_u64.pow(retry_count as u32 - 1));
+                    tracing::info!("Retrying payout in {}ms...", delay_ms);
+                    sleep(Duration::from_millis(delay_ms)).await;
                 }
             }
         }
-
-        Ok(())
     }
 
-    async fn process_winner_result(
-        &self,
-        winner: GameResult,
-        payout_txid: Option<String>,
-    ) -> Result<()> {
-        // Store game result in database
-        let game_result = db::insert_game_result(
+    /// Pay the whole jackpot to the player of the bet at `outpoint`. A payout that fails is
+    /// retried with the unpaid winners.
+    async fn process_jackpot_win(&self, outpoint: OutPoint, winner: ArkAddress) -> Result<()> {
+        let Some(entry) = db::claim_jackpot(
             &self.db_pool,
-            &winner.current_nonce.to_string(),
-            winner.rolled_number,
-            &winner.outpoint.txid.to_string(),
-            payout_txid.as_deref(),
-            winner.input_amount as i64,
-            winner.payout_amount.map(|p| p as i64),
-            &winner.sender,
-            true,                  // Is win
-            payout_txid.is_some(), // Processed successfully if payout_txid exists
-            winner.multiplier.multiplier() as i64,
+            &outpoint.txid.to_string(),
+            outpoint.vout as i64,
+            &winner.encode(),
+            self.dust_amount.to_sat() as i64,
         )
+        .await?
+        else {
+            tracing::info!(%outpoint, "🎰 Jackpot hit, but the pot is too small to pay out");
+            return Ok(());
+        };
+
+        let amount = Amount::from_sat(entry.amount.unsigned_abs());
+        tracing::info!(
+            %outpoint,
+            amount = amount.to_sat(),
+            winner = winner.encode(),
+            "🎰💰 JACKPOT won"
+        );
+
+        match self
+            .send_payout(
+                PayoutSource::Jackpot(entry.id),
+                winner,
+                amount,
+                "jackpot_payout",
+            )
+            .await
+        {
+            Some(payout) => {
+                if let Err(e) = db::mark_jackpot_paid(&self.db_pool, entry.id, &payout.txid).await {
+                    tracing::error!("Failed to mark jackpot paid: {}", e);
+                }
+            }
+            None => {
+                tracing::error!(
+                    amount = amount.to_sat(),
+                    "🚨 Failed to pay jackpot, it will be retried with the unpaid winners"
+                );
+            }
+        }
+
+        self.broadcast_jackpot(Some(JackpotWinItem {
+            amount,
+            winner: winner.encode(),
+            input_tx_id: outpoint.txid.to_string(),
+            input_vout: outpoint.vout as i64,
+        }))
         .await;
 
-        if let Err(e) = game_result {
-            tracing::error!("Failed to store winner game result: {}", e);
-        } else {
-            // Broadcast game result
-            let nonce_str = winner.current_nonce.to_string();
-            let revealable_nonce = self.nonce_service.get_revealable_nonce(&nonce_str).await;
-            let nonce_hash = self.nonce_service.get_current_nonce_hash().await;
+        Ok(())
+    }
 
-            let game_item = GameHistoryItem {
-                id: "latest".to_string(),
-                amount_sent: Amount::from_sat(winner.input_amount),
-                multiplier: winner.multiplier.multiplier() as f64 / 100.0,
-                result_number: winner.rolled_number,
-                target_number: winner.multiplier.get_lower_than() as i64,
-                is_win: true,
-                payout: winner.payout_amount.map(Amount::from_sat),
-                input_tx_id: winner.outpoint.txid.to_string(),
-                output_tx_id: payout_txid,
-                nonce: revealable_nonce,
-                nonce_hash,
-                timestamp: time::OffsetDateTime::now_utc(),
-            };
+    async fn process_winner_result(&self, winner: GameResult, payout: Option<Paid>) -> Result<()> {
+        // Broadcast game result
+        let revealable_nonce = self
+            .nonce_service
+            .get_revealable_nonce(&winner.current_nonce.nonce)
+            .await;
+        let nonce_hash = hash_nonce(&winner.current_nonce.nonce);
+
+        let game_item = GameHistoryItem {
+            id: "latest".to_string(),
+            game_type: winner.game_type.to_string(),
+            amount_sent: Amount::from_sat(winner.input_amount),
+            multiplier: winner.payout_multiplier as f64 / 100.0,
+            option: winner.option.to_string(),
+            result_number: winner.rolled_number,
+            outcome: winner.outcome.clone(),
+            target_number: winner.target,
+            is_win: winner.is_win,
+            payout: winner.payout_amount.map(Amount::from_sat),
+            refund: winner.refund_amount.map(Amount::from_sat),
+            label: winner.label.map(str::to_string),
+            detail: stored_detail(winner.detail.as_deref()),
+            input_tx_id: winner.outpoint.txid.to_string(),
+            input_vout: Some(winner.outpoint.vout as i64),
+            output_tx_id: payout.as_ref().map(|payout| payout.txid.clone()),
+            output_vout: payout.map(|payout| payout.vout as i64),
+            nonce: revealable_nonce,
+            nonce_hash,
+            client_seed: winner.client_seed.clone(),
+            roll_version: RollVersion::LATEST as u8,
+            chain_position: winner.current_nonce.chain_position,
+            timestamp: time::OffsetDateTime::now_utc(),
+        };
 
-            self.broadcast_game_result(game_item.clone()).await;
+        self.broadcast_game_result(game_item.clone()).await;
 
-            // Send Telegram notification for winner
-            if let Some(ref token) = self.telegram_bot_token {
-                if let Some(ref payout_tx) = game_item.output_tx_id {
-                    if let Err(e) = crate::telegram::notify_win(
+        // Send Telegram notification for winner, a partial refund is announced as a loss
+        if let Some(ref token) = self.telegram_bot_token {
+            let notified = match game_item.output_tx_id {
+                Some(ref payout_tx) if winner.is_win => {
+                    crate::telegram::notify_win(
                         &self.db_pool,
                         token,
                         &winner.sender,
                         winner.input_amount,
                         winner.payout_amount.unwrap_or(0),
-                        winner.multiplier.multiplier() as f64 / 100.0,
+                        winner.payout_multiplier as f64 / 100.0,
                         winner.rolled_number,
-                        winner.multiplier.get_lower_than(),
+                        winner.target,
+                        winner.label.unwrap_or(GameEvaluation::WIN),
                         &winner.outpoint.txid.to_string(),
                         payout_tx,
                     )
                     .await
-                    {
-                        tracing::error!("Failed to send telegram notification: {:#}", e);
-                    }
                 }
+                Some(_) => {
+                    crate::telegram::notify_loss(
+                        &self.db_pool,
+                        token,
+                        &winner.sender,
+                        winner.input_amount,
+                        winner.payout_multiplier as f64 / 100.0,
+                        winner.rolled_number,
+                        winner.target,
+                        winner.label.unwrap_or(GameEvaluation::LOSS),
+                        winner.refund_amount,
+                        &winner.outpoint.txid.to_string(),
+                    )
+                    .await
+                }
+                None => Ok(()),
+            };
+
+            if let Err(e) = notified {
+                tracing::error!("Failed to send telegram notification: {:#}", e);
             }
         }
 
@@ -529,46 +1412,87 @@ impl TransactionProcessor {
         tracing::info!(
             rolled_number = loser.rolled_number,
             bet = loser.input_amount,
-            nonce = loser.current_nonce,
+            nonce = %loser.current_nonce.nonce,
             "🏠 House won! Player lost their bet"
         );
 
         // Store losing game result
         let game_result = db::insert_game_result(
             &self.db_pool,
-            &loser.current_nonce.to_string(),
+            &loser.current_nonce.nonce,
             loser.rolled_number,
             &loser.outpoint.txid.to_string(),
+            loser.outpoint.vout as i64,
             None,
             loser.input_amount as i64,
             None,
             &loser.sender,
             false, // Not a win
             true,  // Processed (no payment needed for losses)
-            loser.multiplier.multiplier() as i64,
+            loser.payout_multiplier as i64,
+            loser.client_seed.as_deref(),
+            loser.current_nonce.chain_position,
+            loser.game_type as i64,
+            loser.option.index() as i64,
+            None,
+            loser.label,
+            loser.detail.as_deref(),
+            RollVersion::LATEST as i64,
+            self.games
+                .settings()
+                .house_edge_bps(loser.game_type)
+                .map(i64::from),
         )
         .await;
 
         if let Err(e) = game_result {
             tracing::error!("Failed to store loser game result: {}", e);
         } else {
+            let contribution = self.jackpot.contribution(loser.input_amount);
+            if contribution > 0 {
+                match db::insert_jackpot_contribution(
+                    &self.db_pool,
+                    contribution as i64,
+                    &loser.outpoint.txid.to_string(),
+                    loser.outpoint.vout as i64,
+                )
+                .await
+                {
+                    Ok(_) => self.broadcast_jackpot(None).await,
+                    Err(e) => tracing::error!("Failed to store jackpot contribution: {}", e),
+                }
+            }
+
             // Broadcast game result
-            let nonce_str = loser.current_nonce.to_string();
-            let revealable_nonce = self.nonce_service.get_revealable_nonce(&nonce_str).await;
-            let nonce_hash = self.nonce_service.get_current_nonce_hash().await;
+            let revealable_nonce = self
+                .nonce_service
+                .get_revealable_nonce(&loser.current_nonce.nonce)
+                .await;
+            let nonce_hash = hash_nonce(&loser.current_nonce.nonce);
 
             let game_item = GameHistoryItem {
                 id: "latest".to_string(),
+                game_type: loser.game_type.to_string(),
                 amount_sent: Amount::from_sat(loser.input_amount),
-                multiplier: loser.multiplier.multiplier() as f64 / 100.0,
+                multiplier: loser.payout_multiplier as f64 / 100.0,
+                option: loser.option.to_string(),
                 result_number: loser.rolled_number,
-                target_number: loser.multiplier.get_lower_than() as i64,
+                outcome: loser.outcome.clone(),
+                target_number: loser.target,
                 is_win: false,
                 payout: None,
+                refund: None,
+                label: loser.label.map(str::to_string),
+                detail: stored_detail(loser.detail.as_deref()),
                 input_tx_id: loser.outpoint.txid.to_string(),
+                input_vout: Some(loser.outpoint.vout as i64),
                 output_tx_id: None,
+                output_vout: None,
                 nonce: revealable_nonce,
                 nonce_hash,
+                client_seed: loser.client_seed.clone(),
+                roll_version: RollVersion::LATEST as u8,
+                chain_position: loser.current_nonce.chain_position,
                 timestamp: time::OffsetDateTime::now_utc(),
             };
 
@@ -581,9 +1505,11 @@ impl TransactionProcessor {
                     token,
                     &loser.sender,
                     loser.input_amount,
-                    loser.multiplier.multiplier() as f64 / 100.0,
+                    loser.payout_multiplier as f64 / 100.0,
                     loser.rolled_number,
-                    loser.multiplier.get_lower_than(),
+                    loser.target,
+                    loser.label.unwrap_or(GameEvaluation::LOSS),
+                    None,
                     &loser.outpoint.txid.to_string(),
                 )
                 .await
@@ -599,6 +1525,7 @@ impl TransactionProcessor {
 
 pub async fn spawn_transaction_monitor(
     ark_client: Arc<ArkClient>,
+    payouts: PayoutExecutor,
     my_addresses: Vec<ArkAddress>,
     nonce_service: NonceService,
     db_pool: Pool<Sqlite>,
@@ -606,9 +1533,15 @@ pub async fn spawn_transaction_monitor(
     max_payout_sats: u64,
     dust_amount: Amount,
     telegram_bot_token: Option<String>,
+    games: Arc<GameRegistry>,
+    jackpot: JackpotSettings,
+    lottery: Option<LotteryService>,
+    duels: Option<DuelSettings>,
+    subscription_updates: mpsc::UnboundedReceiver<SubscriptionUpdate>,
 ) {
     let processor = TransactionProcessor::new(
         ark_client,
+        payouts,
         my_addresses,
         nonce_service,
         db_pool,
@@ -616,19 +1549,32 @@ pub async fn spawn_transaction_monitor(
         max_payout_sats,
         dust_amount,
         telegram_bot_token,
+        games,
+        jackpot,
+        lottery,
+        duels,
     );
 
     tokio::spawn(async move {
-        processor.start_monitoring().await;
+        processor.start_monitoring(subscription_updates).await;
     });
 }
 
 /// Legacy function for backward compatibility
 /// Game evaluation logic has been moved to the games module
-#[deprecated(note = "Use games::get_game(GameType::SatoshisNumber).evaluate() instead")]
+#[deprecated(note = "Use GameRegistry::get(GameType::SatoshisNumber).evaluate() instead")]
 pub fn evaluate_game_outcome(nonce: u64, txid: &str, multiplier: &Multiplier) -> (i64, bool) {
-    let game = get_game(GameType::SatoshisNumber);
-    let evaluation = game.evaluate(nonce, txid, multiplier);
+    let games = GameRegistry::default();
+    let game = games.get(GameType::SatoshisNumber);
+    let nonce = nonce.to_string();
+    let input = GameInput {
+        nonce: &nonce,
+        txid,
+        vout: None,
+        client_seed: None,
+        roll_version: RollVersion::V1,
+    };
+    let evaluation = game.evaluate(&input, &GameOption::Multiplier(*multiplier), 0);
     (evaluation.rolled_value, evaluation.is_win)
 }
 
@@ -646,10 +1592,18 @@ mod tests {
         let results: Vec<bool> = (0..TEST_ITERATIONS)
             .into_par_iter()
             .map(|i| {
-                let game = get_game(GameType::SatoshisNumber);
-                let nonce = i as u64;
+                let games = GameRegistry::default();
+                let game = games.get(GameType::SatoshisNumber);
+                let nonce = i.to_string();
                 let txid = format!("test_txid_{i}");
-                let evaluation = game.evaluate(nonce, &txid, &multiplier);
+                let input = GameInput {
+                    nonce: &nonce,
+                    txid: &txid,
+                    vout: None,
+                    client_seed: None,
+                    roll_version: RollVersion::LATEST,
+                };
+                let evaluation = game.evaluate(&input, &GameOption::Multiplier(multiplier), 1000);
                 evaluation.is_win
             })
             .collect();
@@ -1,5 +1,10 @@
+use crate::server::CrashPointItem;
 use crate::server::DonationItem;
+use crate::server::DuelItem;
 use crate::server::GameHistoryItem;
+use crate::server::JackpotItem;
+use crate::server::LotteryRoundItem;
+use crate::server::LotteryTicketItem;
 use crate::server::WebSocketMessage;
 use std::sync::Arc;
 use tokio::sync::broadcast;
@@ -44,6 +49,34 @@ impl WebSocketBroadcaster {
         self.broadcast_message(WebSocketMessage::Donation(donation))
     }
 
+    pub fn broadcast_jackpot(&self, jackpot: JackpotItem) -> Result<(), String> {
+        self.broadcast_message(WebSocketMessage::Jackpot(jackpot))
+    }
+
+    pub fn broadcast_crash_point(&self, crash_point: CrashPointItem) -> Result<(), String> {
+        self.broadcast_message(WebSocketMessage::CrashPoint(crash_point))
+    }
+
+    pub fn broadcast_lottery_ticket(&self, ticket: LotteryTicketItem) -> Result<(), String> {
+        self.broadcast_message(WebSocketMessage::LotteryTicket(ticket))
+    }
+
+    pub fn broadcast_lottery_draw(&self, round: LotteryRoundItem) -> Result<(), String> {
+        self.broadcast_message(WebSocketMessage::LotteryDraw(round))
+    }
+
+    pub fn broadcast_duel_opened(&self, duel: DuelItem) -> Result<(), String> {
+        self.broadcast_message(WebSocketMessage::DuelOpened(duel))
+    }
+
+    pub fn broadcast_duel_matched(&self, duel: DuelItem) -> Result<(), String> {
+        self.broadcast_message(WebSocketMessage::DuelMatched(duel))
+    }
+
+    pub fn broadcast_duel_settled(&self, duel: DuelItem) -> Result<(), String> {
+        self.broadcast_message(WebSocketMessage::DuelSettled(duel))
+    }
+
     pub fn receiver_count(&self) -> usize {
         self.tx.receiver_count()
     }